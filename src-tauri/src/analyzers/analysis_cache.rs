@@ -0,0 +1,342 @@
+use std::{collections::HashMap, fs};
+
+use serde::{Deserialize, Serialize};
+use xxhash_rust::xxh3::{xxh3_64, Xxh3};
+
+use crate::{
+    editor::{editor::EditorEnvironment, editor_runtime::EditorRuntimeData},
+    project::repos::repository_tree::{RelativePath, RepositoryTreeEntry},
+    references::reference_graph::ReferenceEdge,
+};
+
+use super::{analyzer::Hint, baseline::get_baseline_file_path, lint_config::get_lint_config_file_path, registry::AnalyzerRegistry, suppressions::get_ignore_file_path};
+
+const ANALYSIS_CACHE_FOLDER_NAME: &str = "analysis";
+
+const ANALYSIS_CACHE_FILE_NAME: &str = "analysis_cache.json";
+
+/// Must be increased whenever the layout of the cache file changes.
+const ANALYSIS_CACHE_FORMAT_VERSION: u32 = 3;
+
+/// Hints and references of a single file from a previous analysis.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct CachedFile {
+    /// xxh3 hash of the content the hints were produced for.
+    pub content_hash: u64,
+    /// Hints of analyzers that support scope, other analyzers run on every analysis.
+    pub messages: Vec<Hint>,
+    /// Suppressed hints of the same analyzers.
+    pub suppressed_messages: Vec<Hint>,
+    /// Baselined hints of the same analyzers.
+    pub baselined_messages: Vec<Hint>,
+    pub references: Vec<ReferenceEdge>,
+    /// Referenced paths that did not resolve.
+    pub unresolved: Vec<RelativePath>,
+}
+
+/// Analysis results persisted in the workspace folder between app runs.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct AnalysisCache {
+    /// Hash of everything besides file contents that hints depend on, see `get_analysis_fingerprint`.
+    pub fingerprint: u64,
+    /// Cached files by repository folder and relative path.
+    pub repositories: HashMap<String, HashMap<String, CachedFile>>,
+}
+
+impl AnalysisCache {
+    pub fn get_file(&self, repository_folder: &str, relative_path: &RelativePath) -> Option<&CachedFile> {
+        self.repositories.get(repository_folder)?.get(&relative_path.value)
+    }
+}
+
+/// Hash of analyzer versions, rules, repository order, lint files and base game images.
+/// Cached results are valid only for the same fingerprint.
+pub fn get_analysis_fingerprint(registry: &AnalyzerRegistry, editor_runtime_data: &EditorRuntimeData) -> u64 {
+    let mut hasher = Xxh3::new();
+    hasher.update(&ANALYSIS_CACHE_FORMAT_VERSION.to_le_bytes());
+    hasher.update(env!("CARGO_PKG_VERSION").as_bytes());
+
+    for analyzer in registry.get_analyzers() {
+        hasher.update(analyzer.name().as_bytes());
+        hasher.update(&analyzer.version().to_le_bytes());
+    }
+    for rule in registry.get_rules() {
+        hasher.update(rule.id.as_bytes());
+        hasher.update(format!("{:?}", rule.default_category).as_bytes());
+    }
+
+    for repository_tree in &editor_runtime_data.repository_trees {
+        let folder_path = &repository_tree.repository_info.folder_path;
+        hasher.update(folder_path.as_bytes());
+        // the lint config, the ignore file and the baseline change hints of every file of the repository
+        let lint_config_hash = get_lint_config_file_path(folder_path)
+            .to_str()
+            .and_then(get_content_hash)
+            .unwrap_or(0);
+        hasher.update(&lint_config_hash.to_le_bytes());
+        let ignore_file_hash = get_ignore_file_path(folder_path)
+            .to_str()
+            .and_then(get_content_hash)
+            .unwrap_or(0);
+        hasher.update(&ignore_file_hash.to_le_bytes());
+        let baseline_hash = get_baseline_file_path(folder_path)
+            .to_str()
+            .and_then(get_content_hash)
+            .unwrap_or(0);
+        hasher.update(&baseline_hash.to_le_bytes());
+    }
+    for image in &editor_runtime_data.directory_images {
+        hasher.update(image.name.as_bytes());
+        for file in &image.files {
+            hasher.update(file.as_bytes());
+        }
+    }
+
+    hasher.digest()
+}
+
+/// xxh3 hash of the file content, `None` if the file can't be read.
+pub fn get_content_hash(file_path: &str) -> Option<u64> {
+    fs::read(file_path).ok().map(|content| xxh3_64(&content))
+}
+
+/// Content hashes of the files of every repository, taken from their tree entries.
+/// Other files, e.g. textures, matter only by their presence and get hash 0.
+pub fn get_content_hashes(editor_runtime_data: &EditorRuntimeData) -> HashMap<String, u64> {
+    editor_runtime_data
+        .repository_trees
+        .iter()
+        .flat_map(|repository_tree| repository_tree.entries.iter())
+        .filter_map(|entry| Some((entry.path.clone(), get_entry_content_hash(entry)?)))
+        .collect()
+}
+
+/// Hash of a file entry kept by `update_content_hashes`. `None` for folders and for analyzed
+/// files that were not hashed yet, so they never match a cached hash.
+pub fn get_entry_content_hash(entry: &RepositoryTreeEntry) -> Option<u64> {
+    match (entry.is_directory, is_hashed_file(&entry.path)) {
+        (true, _) => None,
+        (false, true) if entry.content_hash == 0 => None,
+        (false, true) => Some(entry.content_hash),
+        (false, false) => Some(0),
+    }
+}
+
+pub fn is_hashed_file(file_path: &str) -> bool {
+    file_path.ends_with(".json")
+}
+
+/// Cache from the workspace folder. Empty if there is none or it can't be read.
+pub fn load_analysis_cache(editor_env: &EditorEnvironment) -> AnalysisCache {
+    let folder = editor_env.get_or_create_folder(ANALYSIS_CACHE_FOLDER_NAME);
+    if folder.is_err() {
+        return AnalysisCache::default();
+    }
+
+    let content = fs::read_to_string(folder.unwrap().join(ANALYSIS_CACHE_FILE_NAME));
+    if content.is_err() {
+        return AnalysisCache::default();
+    }
+    serde_json::from_str(&content.unwrap()).unwrap_or_default()
+}
+
+pub fn save_analysis_cache(editor_env: &EditorEnvironment, analysis_cache: &AnalysisCache) -> Result<(), String> {
+    let folder = editor_env.get_or_create_folder(ANALYSIS_CACHE_FOLDER_NAME)?;
+    let content = serde_json::to_string(analysis_cache);
+    if content.is_err() {
+        return Err(String::from("Could not serialize analysis cache"));
+    }
+
+    if fs::write(folder.join(ANALYSIS_CACHE_FILE_NAME), content.unwrap()).is_err() {
+        return Err(String::from("Could not write analysis cache"));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        analyzers::{
+            analysis_session::AnalysisSession,
+            analyzer::{AnalysisContext, Analyzer, Category, FileAnalysisResult, Rule},
+        },
+        directory_image::DirectoryImage,
+        project::repos::repository_tree_snapshot::update_content_hashes,
+        test_folder::TestFolder,
+    };
+
+    use super::*;
+
+    const TEST_RULE: Rule = Rule {
+        id: "test-rule",
+        description: "Test rule",
+        default_category: Category::Warning,
+    };
+
+    const OTHER_TEST_RULE: Rule = Rule {
+        id: "other-test-rule",
+        description: "Other test rule",
+        default_category: Category::Warning,
+    };
+
+    const TEST_RULES: [Rule; 1] = [TEST_RULE];
+
+    const MORE_TEST_RULES: [Rule; 2] = [TEST_RULE, OTHER_TEST_RULE];
+
+    struct TestAnalyzer {
+        version: u32,
+        rules: &'static [Rule],
+    }
+
+    impl Analyzer for TestAnalyzer {
+        fn name(&self) -> &'static str {
+            "test analyzer"
+        }
+
+        fn rules(&self) -> &'static [Rule] {
+            self.rules
+        }
+
+        fn version(&self) -> u32 {
+            self.version
+        }
+
+        fn analyze(&self, _context: &AnalysisContext) -> Vec<FileAnalysisResult> {
+            Vec::new()
+        }
+    }
+
+    fn get_fingerprint(version: u32, rules: &'static [Rule], editor_runtime_data: &EditorRuntimeData) -> u64 {
+        let mut registry = AnalyzerRegistry::new();
+        registry.register(Box::new(TestAnalyzer { version, rules }));
+        get_analysis_fingerprint(&registry, editor_runtime_data)
+    }
+
+    fn create_runtime_data(test_folder: &TestFolder) -> EditorRuntimeData {
+        let mut editor_runtime_data = test_folder.create_runtime_data(&["tank"]);
+        for repository_tree in &mut editor_runtime_data.repository_trees {
+            update_content_hashes(repository_tree);
+        }
+        editor_runtime_data
+    }
+
+    fn write_tank_mod(test_folder: &TestFolder) {
+        test_folder.write("/tank/modinfo.json", r#"{"identifier": "com.tank"}"#);
+        test_folder.write("/tank/pa/units/unit_list.json", r#"{"units": ["/pa/units/tank.json"]}"#);
+        test_folder.write("/tank/pa/units/tank.json", r#"{"base_spec": "/pa/units/base.json"}"#);
+        test_folder.write("/tank/pa/units/base.json", "{}");
+        test_folder.write("/tank/pa/units/old.json", "{}");
+    }
+
+    /// Every hint with its file, in a stable order.
+    fn get_hints(results: &[FileAnalysisResult]) -> Vec<String> {
+        let mut hints: Vec<String> = results
+            .iter()
+            .flat_map(|result| {
+                let hints = result.messages.iter().chain(&result.suppressed_messages).chain(&result.baselined_messages);
+                hints.map(|hint| format!("{} {}", result.file_path, serde_json::to_string(hint).unwrap()))
+            })
+            .collect();
+        hints.sort();
+        hints
+    }
+
+    #[test]
+    fn fingerprint_changes_with_analyzers_and_rules() {
+        let test_folder = TestFolder::new();
+        write_tank_mod(&test_folder);
+        let editor_runtime_data = create_runtime_data(&test_folder);
+        let fingerprint = get_fingerprint(1, &TEST_RULES, &editor_runtime_data);
+
+        assert_eq!(get_fingerprint(1, &TEST_RULES, &editor_runtime_data), fingerprint);
+        assert_ne!(get_fingerprint(2, &TEST_RULES, &editor_runtime_data), fingerprint);
+        assert_ne!(get_fingerprint(1, &MORE_TEST_RULES, &editor_runtime_data), fingerprint);
+    }
+
+    #[test]
+    fn fingerprint_changes_with_lint_files_and_images() {
+        let test_folder = TestFolder::new();
+        write_tank_mod(&test_folder);
+        let mut editor_runtime_data = create_runtime_data(&test_folder);
+        let mut fingerprints: Vec<u64> = vec![get_fingerprint(1, &TEST_RULES, &editor_runtime_data)];
+
+        // other files are covered by their content hashes
+        test_folder.write("/tank/pa/units/tank.json", "{}");
+        assert_eq!(get_fingerprint(1, &TEST_RULES, &editor_runtime_data), fingerprints[0]);
+
+        test_folder.write("/tank/pa-linter.json", r#"{"rules": {"orphaned-file": "off"}}"#);
+        fingerprints.push(get_fingerprint(1, &TEST_RULES, &editor_runtime_data));
+        test_folder.write("/tank/.pa-linter-ignore", "/pa/units/old.json");
+        fingerprints.push(get_fingerprint(1, &TEST_RULES, &editor_runtime_data));
+        test_folder.write("/tank/pa-linter-baseline.json", "{}");
+        fingerprints.push(get_fingerprint(1, &TEST_RULES, &editor_runtime_data));
+        let files = vec!["pa/units/base.json".to_string()];
+        editor_runtime_data.directory_images.push(DirectoryImage::new("pa".to_string(), files, None));
+        fingerprints.push(get_fingerprint(1, &TEST_RULES, &editor_runtime_data));
+        editor_runtime_data.directory_images[0].files.push("pa/units/bot.json".to_string());
+        fingerprints.push(get_fingerprint(1, &TEST_RULES, &editor_runtime_data));
+
+        let mut unique_fingerprints = fingerprints.clone();
+        unique_fingerprints.sort();
+        unique_fingerprints.dedup();
+        assert_eq!(unique_fingerprints.len(), fingerprints.len());
+    }
+
+    #[test]
+    fn saved_cache_is_loaded_back() {
+        let test_folder = TestFolder::new();
+        write_tank_mod(&test_folder);
+        let editor_runtime_data = create_runtime_data(&test_folder);
+        let session = AnalysisSession::analyze(&editor_runtime_data);
+        let analysis_cache = session.to_cache(&editor_runtime_data);
+
+        save_analysis_cache(&editor_runtime_data.editor_env, &analysis_cache).unwrap();
+        let loaded_cache = load_analysis_cache(&editor_runtime_data.editor_env);
+
+        assert_eq!(loaded_cache.fingerprint, analysis_cache.fingerprint);
+        assert_eq!(
+            serde_json::to_value(&loaded_cache.repositories).unwrap(),
+            serde_json::to_value(&analysis_cache.repositories).unwrap()
+        );
+        let cached_session = AnalysisSession::analyze_with_cache(&editor_runtime_data, &loaded_cache);
+        assert_eq!(get_hints(cached_session.get_results()), get_hints(session.get_results()));
+    }
+
+    #[test]
+    fn files_changed_after_saving_are_analyzed_again() {
+        let test_folder = TestFolder::new();
+        write_tank_mod(&test_folder);
+        let editor_runtime_data = create_runtime_data(&test_folder);
+        let analysis_cache = AnalysisSession::analyze(&editor_runtime_data).to_cache(&editor_runtime_data);
+
+        test_folder.write("/tank/pa/units/tank.json", r#"{"base_spec": "/pa/units/missing.json"}"#);
+        std::fs::remove_file(test_folder.path().join("tank/pa/units/base.json")).unwrap();
+        let editor_runtime_data = create_runtime_data(&test_folder);
+
+        let cached_session = AnalysisSession::analyze_with_cache(&editor_runtime_data, &analysis_cache);
+        let session = AnalysisSession::analyze(&editor_runtime_data);
+        assert_eq!(get_hints(cached_session.get_results()), get_hints(session.get_results()));
+        assert!(get_hints(session.get_results()).iter().any(|hint| hint.contains("file-not-found")));
+    }
+
+    #[test]
+    fn corrupt_cache_is_discarded() {
+        let test_folder = TestFolder::new();
+        write_tank_mod(&test_folder);
+        let editor_runtime_data = create_runtime_data(&test_folder);
+        let editor_env = &editor_runtime_data.editor_env;
+        save_analysis_cache(editor_env, &AnalysisSession::analyze(&editor_runtime_data).to_cache(&editor_runtime_data))
+            .unwrap();
+
+        let cache_file_path = editor_env
+            .get_or_create_folder(ANALYSIS_CACHE_FOLDER_NAME)
+            .unwrap()
+            .join(ANALYSIS_CACHE_FILE_NAME);
+        let content = fs::read_to_string(&cache_file_path).unwrap();
+        fs::write(&cache_file_path, &content[..content.len() / 2]).unwrap();
+
+        let loaded_cache = load_analysis_cache(editor_env);
+        assert_eq!(loaded_cache.fingerprint, 0);
+        assert!(loaded_cache.repositories.is_empty());
+    }
+}
@@ -0,0 +1,564 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    editor::editor_runtime::EditorRuntimeData,
+    project::repos::repository_tree::{get_entry_relative_path, RelativePath},
+    references::reference_graph::{FileLocation, ReferenceEdge, ReferenceGraph},
+    vfs::virtual_file_system::VirtualFileSystem,
+};
+
+use super::{
+    analysis_cache::{
+        get_analysis_fingerprint, get_content_hashes, get_entry_content_hash, is_hashed_file, AnalysisCache, CachedFile,
+    },
+    analyzer::{merge_file_analysis_results, run_analyzers, AnalysisContext, FileAnalysisResult, Hint, LintSettings},
+    lint_config::is_lint_file,
+    modinfo_analyzer::MODINFO_RELATIVE_PATH,
+    registry::AnalyzerRegistry,
+};
+
+/// Hints that changed after files changed on disk.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct AnalysisUpdate {
+    /// All hints of every file whose hints changed.
+    pub results: Vec<FileAnalysisResult>,
+    /// Files that have no hints anymore.
+    pub cleared_files: Vec<String>,
+}
+
+/// Results and references of the last analysis.
+/// Kept between runs, so changed files can be analyzed without the rest of the project.
+pub struct AnalysisSession {
+    registry: AnalyzerRegistry,
+    /// Read once, changed lint files make the session analyze everything again.
+    lint_settings: LintSettings,
+    results: Vec<FileAnalysisResult>,
+    reference_graph: ReferenceGraph,
+    /// Content hashes of every repository file at the time of the last analysis.
+    file_hashes: HashMap<String, u64>,
+}
+
+impl AnalysisSession {
+    /// Analyzes every file of every repository.
+    pub fn analyze(editor_runtime_data: &EditorRuntimeData) -> AnalysisSession {
+        let registry = AnalyzerRegistry::with_default_analyzers();
+        let lint_settings = LintSettings::load(editor_runtime_data, &registry);
+        let context = AnalysisContext::new(editor_runtime_data, &registry, &lint_settings);
+        let results = run_analyzers(&context);
+        let reference_graph = context.reference_graph.into_inner();
+
+        AnalysisSession {
+            registry,
+            lint_settings,
+            results,
+            reference_graph,
+            file_hashes: get_content_hashes(editor_runtime_data),
+        }
+    }
+
+    /// Reuses hints of files that did not change since `analysis_cache` was saved
+    /// and analyzes only changed files, files that depend on them and global analyzers.
+    /// Everything is analyzed if the cache was saved for other analyzers, rules, repositories or images.
+    pub fn analyze_with_cache(editor_runtime_data: &EditorRuntimeData, analysis_cache: &AnalysisCache) -> AnalysisSession {
+        let registry = AnalyzerRegistry::with_default_analyzers();
+        if analysis_cache.fingerprint != get_analysis_fingerprint(&registry, editor_runtime_data) {
+            return AnalysisSession::analyze(editor_runtime_data);
+        }
+
+        let virtual_file_system = VirtualFileSystem::build(
+            &editor_runtime_data.directory_images,
+            &editor_runtime_data.repository_trees,
+        );
+        let file_hashes = get_content_hashes(editor_runtime_data);
+        let lint_settings = LintSettings::load(editor_runtime_data, &registry);
+        let mut session = AnalysisSession {
+            registry,
+            lint_settings,
+            results: Vec::new(),
+            reference_graph: ReferenceGraph::new(),
+            file_hashes: HashMap::new(),
+        };
+        let mut changed_file_paths: Vec<String> = Vec::new();
+
+        for repository_tree in &editor_runtime_data.repository_trees {
+            let folder_path = &repository_tree.repository_info.folder_path;
+            let cached_files = analysis_cache.repositories.get(folder_path);
+            let layer = virtual_file_system.get_repository_layer(&repository_tree.repository_info);
+            if cached_files.is_none() || layer.is_none() {
+                continue;
+            }
+            let (cached_files, layer) = (cached_files.unwrap(), layer.unwrap());
+
+            let entry_paths: HashMap<RelativePath, &String> = repository_tree
+                .entries
+                .iter()
+                .map(|entry| (get_entry_relative_path(repository_tree, entry), &entry.path))
+                .collect();
+
+            // files removed since the cache was saved are restored as well,
+            // so files depending on them are found and analyzed
+            for (relative_path, cached_file) in cached_files {
+                let relative_path = RelativePath::new(relative_path.clone());
+                let file_path = match entry_paths.get(&relative_path) {
+                    Some(entry_path) => entry_path.to_string(),
+                    None => get_file_path(folder_path, &relative_path),
+                };
+                if file_hashes.get(&file_path) != Some(&cached_file.content_hash) {
+                    changed_file_paths.push(file_path.clone());
+                }
+
+                session.file_hashes.insert(file_path.clone(), cached_file.content_hash);
+                session.reference_graph.add_edges(cached_file.references.clone());
+                let source = FileLocation::new(layer, &relative_path);
+                for unresolved_path in &cached_file.unresolved {
+                    session
+                        .reference_graph
+                        .add_unresolved(source.clone(), unresolved_path.clone());
+                }
+                // clean json files stay in the results, reports list them as analyzed
+                let is_json_file = entry_paths.contains_key(&relative_path) && is_hashed_file(&file_path);
+                let has_hints = !cached_file.messages.is_empty()
+                    || !cached_file.suppressed_messages.is_empty()
+                    || !cached_file.baselined_messages.is_empty();
+                if has_hints || is_json_file {
+                    session.results.push(FileAnalysisResult {
+                        file_path,
+                        repository_info: repository_tree.repository_info.clone(),
+                        messages: cached_file.messages.clone(),
+                        suppressed_messages: cached_file.suppressed_messages.clone(),
+                        baselined_messages: cached_file.baselined_messages.clone(),
+                    });
+                }
+            }
+        }
+
+        for repository_tree in &editor_runtime_data.repository_trees {
+            for entry in &repository_tree.entries {
+                if entry.is_directory {
+                    continue;
+                }
+                let relative_path = get_entry_relative_path(repository_tree, entry);
+                if analysis_cache
+                    .get_file(&repository_tree.repository_info.folder_path, &relative_path)
+                    .is_none()
+                {
+                    changed_file_paths.push(entry.path.clone());
+                }
+            }
+        }
+
+        session.reanalyze(editor_runtime_data, &changed_file_paths);
+        session
+    }
+
+    /// Hints and references of every repository file, to be saved with `save_analysis_cache`.
+    pub fn to_cache(&self, editor_runtime_data: &EditorRuntimeData) -> AnalysisCache {
+        let scoped_rules = get_scoped_rules(&self.registry);
+
+        let mut references: HashMap<&str, Vec<ReferenceEdge>> = HashMap::new();
+        for edge in self.reference_graph.get_edges() {
+            if let Some(file_path) = &edge.source.file_path {
+                references.entry(file_path).or_default().push(edge.clone());
+            }
+        }
+        let mut unresolved: HashMap<&str, Vec<RelativePath>> = HashMap::new();
+        for (relative_path, sources) in self.reference_graph.get_unresolved() {
+            for file_path in sources.iter().filter_map(|source| source.file_path.as_ref()) {
+                unresolved.entry(file_path).or_default().push(relative_path.clone());
+            }
+        }
+        let messages: HashMap<&str, &FileAnalysisResult> = self
+            .results
+            .iter()
+            .map(|result| (result.file_path.as_str(), result))
+            .collect();
+
+        let mut analysis_cache = AnalysisCache {
+            fingerprint: get_analysis_fingerprint(&self.registry, editor_runtime_data),
+            repositories: HashMap::new(),
+        };
+        for repository_tree in &editor_runtime_data.repository_trees {
+            let mut cached_files: HashMap<String, CachedFile> = HashMap::new();
+            for entry in &repository_tree.entries {
+                if entry.is_directory {
+                    continue;
+                }
+                let file_path = entry.path.as_str();
+                let result = messages.get(file_path);
+                let cached_file = CachedFile {
+                    content_hash: self.file_hashes.get(file_path).copied().unwrap_or_default(),
+                    messages: result
+                        .map(|result| get_scoped_hints(&result.messages, &scoped_rules))
+                        .unwrap_or_default(),
+                    suppressed_messages: result
+                        .map(|result| get_scoped_hints(&result.suppressed_messages, &scoped_rules))
+                        .unwrap_or_default(),
+                    baselined_messages: result
+                        .map(|result| get_scoped_hints(&result.baselined_messages, &scoped_rules))
+                        .unwrap_or_default(),
+                    references: references.remove(file_path).unwrap_or_default(),
+                    unresolved: unresolved.remove(file_path).unwrap_or_default(),
+                };
+                cached_files.insert(get_entry_relative_path(repository_tree, entry).value, cached_file);
+            }
+            analysis_cache
+                .repositories
+                .insert(repository_tree.repository_info.folder_path.clone(), cached_files);
+        }
+        analysis_cache
+    }
+
+    pub fn get_results(&self) -> &Vec<FileAnalysisResult> {
+        &self.results
+    }
+
+    pub fn get_reference_graph(&self) -> &ReferenceGraph {
+        &self.reference_graph
+    }
+
+    /// Analyzes `changed_file_paths` and the files that depend on them.
+    /// `editor_runtime_data` must already have trees with the changes.
+    pub fn reanalyze(&mut self, editor_runtime_data: &EditorRuntimeData, changed_file_paths: &[String]) -> AnalysisUpdate {
+        let old_messages = get_messages_by_file(&self.results);
+
+        let mut scope: HashSet<String> = HashSet::new();
+        let mut is_mount_order_changed = false;
+        let mut is_file_set_changed = false;
+        let mut is_lint_file_changed = false;
+        for file_path in changed_file_paths {
+            scope.insert(file_path.clone());
+            if self.file_hashes.contains_key(file_path) != Path::new(file_path).exists() {
+                is_file_set_changed = true;
+            }
+
+            let relative_path = match get_relative_path(editor_runtime_data, file_path) {
+                Some(relative_path) => relative_path,
+                None => continue,
+            };
+            // priority of the mod may have changed, so every reference may resolve differently
+            if relative_path.value == MODINFO_RELATIVE_PATH {
+                is_mount_order_changed = true;
+            }
+            // the lint config, the ignore file and the baseline may change hints of any file of the repository
+            if is_lint_file(&relative_path) {
+                is_lint_file_changed = true;
+            }
+            for dependent in self.reference_graph.get_dependents(&relative_path) {
+                if let Some(dependent_file_path) = dependent.file_path {
+                    scope.insert(dependent_file_path);
+                }
+            }
+        }
+
+        // suggestions for unresolved references are picked from every file of the project
+        if is_file_set_changed {
+            for source in self.reference_graph.get_unresolved_sources() {
+                if let Some(source_file_path) = source.file_path {
+                    scope.insert(source_file_path);
+                }
+            }
+        }
+
+        if is_mount_order_changed || is_lint_file_changed {
+            *self = AnalysisSession::analyze(editor_runtime_data);
+        } else {
+            self.analyze_scope(editor_runtime_data, scope);
+            for file_path in changed_file_paths {
+                self.update_file_hash(editor_runtime_data, file_path);
+            }
+        }
+
+        let new_messages = get_messages_by_file(&self.results);
+        let mut update = AnalysisUpdate::default();
+        for result in &self.results {
+            // files left without hints are only cleared
+            let messages = new_messages.get(&result.file_path);
+            if messages.is_some() && old_messages.get(&result.file_path) != messages {
+                update.results.push(result.clone());
+            }
+        }
+        for file_path in old_messages.keys() {
+            if !new_messages.contains_key(file_path) {
+                update.cleared_files.push(file_path.clone());
+            }
+        }
+        update.cleared_files.sort();
+        update
+    }
+
+    fn analyze_scope(&mut self, editor_runtime_data: &EditorRuntimeData, scope: HashSet<String>) {
+        let mut reference_graph = std::mem::take(&mut self.reference_graph);
+        reference_graph.remove_sources(|source| {
+            source
+                .file_path
+                .as_ref()
+                .is_some_and(|file_path| scope.contains(file_path))
+        });
+
+        let context = AnalysisContext::with_scope(
+            editor_runtime_data,
+            &self.registry,
+            &self.lint_settings,
+            scope.clone(),
+            reference_graph,
+        );
+        let new_results = run_analyzers(&context);
+        self.reference_graph = context.reference_graph.into_inner();
+
+        // analyzers without scope support report the whole project again
+        let scoped_rules = get_scoped_rules(&self.registry);
+        for result in &mut self.results {
+            let is_in_scope = scope.contains(&result.file_path);
+            result
+                .messages
+                .retain(|hint| scoped_rules.contains(hint.rule_id()) && !is_in_scope);
+            result
+                .suppressed_messages
+                .retain(|hint| scoped_rules.contains(hint.rule_id()) && !is_in_scope);
+            result
+                .baselined_messages
+                .retain(|hint| scoped_rules.contains(hint.rule_id()) && !is_in_scope);
+        }
+
+        merge_file_analysis_results(&mut self.results, new_results);
+        self.results.retain(|result| {
+            !result.messages.is_empty()
+                || !result.suppressed_messages.is_empty()
+                || !result.baselined_messages.is_empty()
+                || Path::new(&result.file_path).exists()
+        });
+    }
+
+    /// Takes the hash of `file_path` from its tree entry, the hash is removed if the file is gone.
+    fn update_file_hash(&mut self, editor_runtime_data: &EditorRuntimeData, file_path: &str) {
+        let content_hash = editor_runtime_data
+            .repository_trees
+            .iter()
+            .flat_map(|repository_tree| repository_tree.entries.iter())
+            .find(|entry| entry.path == file_path)
+            .and_then(get_entry_content_hash);
+        match content_hash {
+            Some(content_hash) => self.file_hashes.insert(file_path.to_string(), content_hash),
+            None => self.file_hashes.remove(file_path),
+        };
+    }
+}
+
+/// Rules of analyzers that support scope, their hints can be kept per file.
+fn get_scoped_rules(registry: &AnalyzerRegistry) -> HashSet<&'static str> {
+    registry
+        .get_analyzers()
+        .iter()
+        .filter(|analyzer| analyzer.supports_scope())
+        .flat_map(|analyzer| analyzer.rules().iter().map(|rule| rule.id))
+        .collect()
+}
+
+fn get_scoped_hints(hints: &[Hint], scoped_rules: &HashSet<&'static str>) -> Vec<Hint> {
+    hints
+        .iter()
+        .filter(|hint| scoped_rules.contains(hint.rule_id()))
+        .cloned()
+        .collect()
+}
+
+/// Serialized hints by file, to find files whose hints changed.
+/// Suppressed and baselined hints count as well, they can be shown on request.
+fn get_messages_by_file(results: &[FileAnalysisResult]) -> HashMap<String, String> {
+    results
+        .iter()
+        .filter(|result| {
+            !result.messages.is_empty() || !result.suppressed_messages.is_empty() || !result.baselined_messages.is_empty()
+        })
+        .map(|result| {
+            let hints = (&result.messages, &result.suppressed_messages, &result.baselined_messages);
+            (result.file_path.clone(), serde_json::to_string(&hints).unwrap_or_default())
+        })
+        .collect()
+}
+
+fn get_file_path(folder_path: &str, relative_path: &RelativePath) -> String {
+    Path::new(folder_path)
+        .join(relative_path.value.trim_start_matches('/'))
+        .to_string_lossy()
+        .to_string()
+}
+
+fn get_relative_path(editor_runtime_data: &EditorRuntimeData, file_path: &str) -> Option<RelativePath> {
+    editor_runtime_data
+        .repository_trees
+        .iter()
+        .find_map(|repository_tree| {
+            let relative_path = file_path.strip_prefix(&repository_tree.repository_info.folder_path)?;
+            match relative_path.starts_with(['/', '\\']) {
+                true => Some(RelativePath::new(relative_path.to_string())),
+                false => None,
+            }
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::PathBuf};
+
+    use crate::{
+        analyzers::json_analyzer::{CASE_MISMATCH_RULE, FILE_NOT_FOUND_RULE},
+        project::repos::repository_tree_snapshot::update_content_hashes,
+        test_folder::TestFolder,
+        watch::repository_watcher::update_repository_trees,
+    };
+
+    use super::*;
+
+    fn create_runtime_data(test_folder: &TestFolder, mod_folders: &[&str]) -> EditorRuntimeData {
+        let mut editor_runtime_data = test_folder.create_runtime_data(mod_folders);
+        for repository_tree in &mut editor_runtime_data.repository_trees {
+            update_content_hashes(repository_tree);
+        }
+        editor_runtime_data
+    }
+
+    /// Writes files, or removes them for `None`, and updates trees the way the watcher does.
+    /// Returns the changed files.
+    fn change_files(
+        test_folder: &TestFolder,
+        editor_runtime_data: &mut EditorRuntimeData,
+        files: &[(&str, Option<&str>)],
+    ) -> Vec<String> {
+        let mut changed_paths: Vec<PathBuf> = Vec::new();
+        for (relative_path, content) in files {
+            match content {
+                Some(content) => changed_paths.push(PathBuf::from(test_folder.write(relative_path, content))),
+                None => {
+                    let file_path = test_folder.path().join(relative_path.trim_start_matches('/'));
+                    fs::remove_file(&file_path).unwrap();
+                    changed_paths.push(file_path);
+                }
+            }
+        }
+
+        let changed_files = update_repository_trees(&mut editor_runtime_data.repository_trees, &changed_paths);
+        for repository_tree in &mut editor_runtime_data.repository_trees {
+            update_content_hashes(repository_tree);
+        }
+        changed_files
+    }
+
+    /// Hints of the json analyzer by file relative to the test folder, orphans and conflicts are left out.
+    fn get_json_hints(test_folder: &TestFolder, results: &[FileAnalysisResult]) -> Vec<(String, String)> {
+        let mut hints: Vec<(String, String)> = results
+            .iter()
+            .flat_map(|result| result.messages.iter().map(move |hint| (result, hint)))
+            .filter(|(_, hint)| [FILE_NOT_FOUND_RULE.id, CASE_MISMATCH_RULE.id].contains(&hint.rule_id()))
+            .map(|(result, hint)| (get_test_path(test_folder, &result.file_path), hint.message().to_string()))
+            .collect();
+        hints.sort();
+        hints
+    }
+
+    fn get_test_path(test_folder: &TestFolder, file_path: &str) -> String {
+        file_path.strip_prefix(&test_folder.path_string()).unwrap_or(file_path).to_string()
+    }
+
+    fn write_tank_mod(test_folder: &TestFolder, bot: &str) {
+        test_folder.write("/tank/modinfo.json", r#"{"identifier": "com.tank"}"#);
+        test_folder.write(
+            "/tank/pa/units/unit_list.json",
+            r#"{"units": ["/pa/units/tank.json", "/pa/units/bot.json"]}"#,
+        );
+        test_folder.write("/tank/pa/units/tank.json", r#"{"base_spec": "/pa/units/base.json"}"#);
+        test_folder.write("/tank/pa/units/base.json", "{}");
+        test_folder.write("/tank/pa/units/bot.json", bot);
+    }
+
+    #[test]
+    fn files_depending_on_changed_files_are_analyzed_again() {
+        let test_folder = TestFolder::new();
+        write_tank_mod(&test_folder, r#"{"base_spec": "/pa/units/missing.json"}"#);
+        let mut editor_runtime_data = create_runtime_data(&test_folder, &["tank"]);
+        let mut session = AnalysisSession::analyze(&editor_runtime_data);
+
+        let changed_files = change_files(&test_folder, &mut editor_runtime_data, &[("/tank/pa/units/base.json", None)]);
+        let update = session.reanalyze(&editor_runtime_data, &changed_files);
+
+        let update_paths: Vec<String> = update
+            .results
+            .iter()
+            .map(|result| get_test_path(&test_folder, &result.file_path))
+            .collect();
+        assert_eq!(update_paths, vec!["/tank/pa/units/tank.json"]);
+        assert!(update.cleared_files.is_empty());
+        assert_eq!(
+            get_json_hints(&test_folder, session.get_results()),
+            vec![
+                ("/tank/pa/units/bot.json".to_string(), "File not found".to_string()),
+                ("/tank/pa/units/tank.json".to_string(), "File not found".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn hints_of_files_out_of_scope_are_kept() {
+        let test_folder = TestFolder::new();
+        write_tank_mod(&test_folder, r#"{"base_spec": "/pa/units/missing.json"}"#);
+        test_folder.write("/tank/pa/units/tank.json", r#"{"base_spec": "/pa/units/missing.json"}"#);
+        let mut editor_runtime_data = create_runtime_data(&test_folder, &["tank"]);
+        let mut session = AnalysisSession::analyze(&editor_runtime_data);
+
+        let changed_files = change_files(&test_folder, &mut editor_runtime_data, &[("/tank/pa/units/bot.json", Some("{}"))]);
+        let update = session.reanalyze(&editor_runtime_data, &changed_files);
+
+        assert!(update.results.is_empty());
+        assert_eq!(update.cleared_files, changed_files);
+        assert_eq!(
+            get_json_hints(&test_folder, session.get_results()),
+            vec![("/tank/pa/units/tank.json".to_string(), "File not found".to_string())]
+        );
+        assert_eq!(session.file_hashes, get_content_hashes(&editor_runtime_data));
+    }
+
+    #[test]
+    fn changed_lint_file_analyzes_everything_again() {
+        let test_folder = TestFolder::new();
+        write_tank_mod(&test_folder, r#"{"base_spec": "/pa/units/missing.json"}"#);
+        let mut editor_runtime_data = create_runtime_data(&test_folder, &["tank"]);
+        let mut session = AnalysisSession::analyze(&editor_runtime_data);
+
+        let lint_config = r#"{"rules": {"file-not-found": "off"}}"#;
+        let changed_files = change_files(&test_folder, &mut editor_runtime_data, &[("/tank/pa-linter.json", Some(lint_config))]);
+        let update = session.reanalyze(&editor_runtime_data, &changed_files);
+
+        assert!(get_json_hints(&test_folder, session.get_results()).is_empty());
+        assert!(update.cleared_files.contains(&test_folder.path().join("tank/pa/units/bot.json").to_string_lossy().to_string()));
+    }
+
+    #[test]
+    fn changed_modinfo_analyzes_everything_again() {
+        let test_folder = TestFolder::new();
+        test_folder.write("/base/modinfo.json", r#"{"identifier": "com.base"}"#);
+        test_folder.write("/base/pa/units/base.json", "{}");
+        test_folder.write("/tank/modinfo.json", r#"{"identifier": "com.tank"}"#);
+        test_folder.write("/tank/pa/units/tank.json", r#"{"base_spec": "/pa/units/Base.json"}"#);
+        let mut editor_runtime_data = create_runtime_data(&test_folder, &["base", "tank"]);
+        let mut session = AnalysisSession::analyze(&editor_runtime_data);
+
+        // as if the repository info was read again from the changed modinfo
+        let modinfo = r#"{"identifier": "com.base.renamed"}"#;
+        let changed_files = change_files(&test_folder, &mut editor_runtime_data, &[("/base/modinfo.json", Some(modinfo))]);
+        editor_runtime_data.repository_trees[0].repository_info.mod_identifier = String::from("com.base.renamed");
+        session.reanalyze(&editor_runtime_data, &changed_files);
+
+        assert_eq!(
+            get_json_hints(&test_folder, session.get_results()),
+            vec![(
+                "/tank/pa/units/tank.json".to_string(),
+                "File exists only as /pa/units/base.json in com.base.renamed, letter case must match".to_string()
+            )]
+        );
+    }
+}
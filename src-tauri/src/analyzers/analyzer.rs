@@ -1,9 +1,12 @@
+use std::{collections::{HashMap, HashSet}, sync::OnceLock};
+
 use owo_colors::OwoColorize;
+use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
 
-use crate::{editor::{editor::EditorEnvironment, editor_runtime::EditorRuntimeData}, project::repos::repository::{Repository, RepositoryInfo}};
+use crate::{editor::editor_runtime::EditorRuntimeData, fixes::fix::Fix, logs::logbox::Logbox, project::repos::{repository::RepositoryInfo, repository_tree::RelativePath}, references::reference_graph::ReferenceGraph, vfs::{path_suggestions::PathSuggestionIndex, virtual_file_system::VirtualFileSystem}};
 
-use super::json_analyzer::JsonAnalyzeTask;
+use super::{baseline::{apply_baseline, load_baseline, HintFingerprint}, json_source_map::SourceSpan, lint_config::{load_lint_config, LintConfig}, registry::AnalyzerRegistry, suppressions::{apply_suppressions, load_ignore_file, IgnoreFile, JsonSuppressions}};
 
 
 #[derive(Serialize,Deserialize,Debug,Clone)]
@@ -12,7 +15,17 @@ pub struct FileAnalysisResult {
 
     pub repository_info: RepositoryInfo,
 
-    pub messages: Vec<Hint>
+    pub messages: Vec<Hint>,
+
+    /// Hints suppressed by the repository ignore file or the file itself.
+    /// They are counted, but shown only on request.
+    #[serde(default)]
+    pub suppressed_messages: Vec<Hint>,
+
+    /// Hints known from the repository baseline. Counted apart from suppressed hints,
+    /// they are shown only on request as well.
+    #[serde(default)]
+    pub baselined_messages: Vec<Hint>
 }
 
 #[derive(Serialize,Deserialize,Debug,Clone)]
@@ -20,29 +33,431 @@ pub struct FileAnalysisResult {
 pub enum Hint{
     JSON {
         category: Category,
+        rule_id: String,
         property_name: String,
         property_value: String,
+        /// JSON Pointer of the value, e.g. `/tools/0/spec_id`
+        pointer: String,
+        span: Option<SourceSpan>,
+        message: String,
+        /// Replacement that resolves the hint, if it can be fixed automatically.
+        fix: Option<Fix>,
+        /// Alternative values the user can choose from, best first.
+        #[serde(default)]
+        suggestions: Vec<Suggestion>
+    },
+    /// File is provided by several repositories, only `winner` is loaded by the game.
+    Conflict {
+        category: Category,
+        rule_id: String,
+        relative_path: String,
+        repositories: Vec<RepositoryInfo>,
+        winner: RepositoryInfo,
+        message: String
+    },
+    /// Repository file that is not reachable from any file the game loads by itself.
+    Orphan {
+        category: Category,
+        rule_id: String,
+        relative_path: String,
+        /// Size of the file in bytes.
+        size: u64,
+        message: String
+    },
+    /// Orphaned files of a repository in total, attached to its modinfo.json.
+    OrphanSummary {
+        category: Category,
+        rule_id: String,
+        file_count: usize,
+        wasted_bytes: u64,
         message: String
+    },
+    /// File could not be read or parsed. Line and column are 1-based.
+    Parse {
+        category: Category,
+        rule_id: String,
+        message: String,
+        line: Option<usize>,
+        column: Option<usize>
+    }
+}
+
+impl Hint {
+    pub fn rule_id(&self) -> &str {
+        match self {
+            Hint::JSON { rule_id, .. } => rule_id,
+            Hint::Conflict { rule_id, .. } => rule_id,
+            Hint::Orphan { rule_id, .. } => rule_id,
+            Hint::OrphanSummary { rule_id, .. } => rule_id,
+            Hint::Parse { rule_id, .. } => rule_id,
+        }
+    }
+
+    pub fn category(&self) -> Category {
+        match self {
+            Hint::JSON { category, .. } => *category,
+            Hint::Conflict { category, .. } => *category,
+            Hint::Orphan { category, .. } => *category,
+            Hint::OrphanSummary { category, .. } => *category,
+            Hint::Parse { category, .. } => *category,
+        }
+    }
+
+    /// Category set by a repository lint config instead of the default category of the rule.
+    pub fn set_category(&mut self, new_category: Category) {
+        match self {
+            Hint::JSON { category, .. } => *category = new_category,
+            Hint::Conflict { category, .. } => *category = new_category,
+            Hint::Orphan { category, .. } => *category = new_category,
+            Hint::OrphanSummary { category, .. } => *category = new_category,
+            Hint::Parse { category, .. } => *category = new_category,
+        }
+    }
+
+    /// Attaches `suggestions` to a json hint. Other hints have no value to replace.
+    pub fn with_suggestions(mut self, new_suggestions: Vec<Suggestion>) -> Hint {
+        if let Hint::JSON { suggestions, .. } = &mut self {
+            *suggestions = new_suggestions;
+        }
+        self
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            Hint::JSON { message, .. } => message,
+            Hint::Conflict { message, .. } => message,
+            Hint::Orphan { message, .. } => message,
+            Hint::OrphanSummary { message, .. } => message,
+            Hint::Parse { message, .. } => message,
+        }
     }
 }
 
+/// Value that may be what the author meant, e.g. an existing file close to a missing one.
 #[derive(Serialize,Deserialize,Debug,Clone)]
+pub struct Suggestion {
+    pub value: String,
+    pub message: String,
+    /// Replaces the hint value with `value`.
+    pub fix: Option<Fix>
+}
+
+#[derive(Serialize,Deserialize,Debug,Clone,Copy,PartialEq,Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum Category{
+    Error,
     Warning,
     Suggestion
 }
 
+/// Single check of an analyzer. `id` is stable between versions,
+/// so it can be used to refer to the rule from outside of the editor.
+#[derive(Serialize,Debug,Clone)]
+pub struct Rule {
+    pub id: &'static str,
+    pub description: &'static str,
+    pub default_category: Category,
+}
+
+/// Lint configs, ignore files and baselines of every repository, read once per analysis session.
+/// Changes of any of these files make the session analyze everything with settings read again.
+#[derive(Default)]
+pub struct LintSettings {
+    /// Lint configs by repository folder path.
+    lint_configs: HashMap<String, LintConfig>,
+    /// Ignore files by repository folder path.
+    ignore_files: HashMap<String, IgnoreFile>,
+    /// Number of baseline hints by fingerprint, by repository folder path.
+    baseline_counts: HashMap<String, HashMap<HintFingerprint, usize>>,
+}
+
+impl LintSettings {
+    pub fn load(editor_runtime_data: &EditorRuntimeData, registry: &AnalyzerRegistry) -> LintSettings {
+        let known_rules = registry.get_rules();
+        let mut lint_settings = LintSettings::default();
+        for repository_tree in &editor_runtime_data.repository_trees {
+            let folder_path = &repository_tree.repository_info.folder_path;
+            lint_settings
+                .lint_configs
+                .insert(folder_path.clone(), load_lint_config(folder_path, &known_rules));
+            lint_settings
+                .ignore_files
+                .insert(folder_path.clone(), load_ignore_file(folder_path, &known_rules));
+            if let Some(baseline) = load_baseline(folder_path) {
+                lint_settings
+                    .baseline_counts
+                    .insert(folder_path.clone(), baseline.get_counts());
+            }
+        }
+        lint_settings
+    }
+
+    /// Lint config of the repository, the default config for unknown repositories.
+    pub fn get_lint_config(&self, repository_info: &RepositoryInfo) -> &LintConfig {
+        static DEFAULT_LINT_CONFIG: OnceLock<LintConfig> = OnceLock::new();
+        self.lint_configs
+            .get(&repository_info.folder_path)
+            .unwrap_or_else(|| DEFAULT_LINT_CONFIG.get_or_init(LintConfig::default))
+    }
+}
+
+/// Data shared by all analyzers during one analysis run.
+pub struct AnalysisContext<'a> {
+    pub editor_runtime_data: &'a EditorRuntimeData,
+    /// Analyzers of the run, their rules are the rules lint settings can refer to.
+    pub registry: &'a AnalyzerRegistry,
+    pub lint_settings: &'a LintSettings,
+    /// Directory images and repositories merged in the game mount order.
+    /// References are resolved against it.
+    pub virtual_file_system: VirtualFileSystem,
+    /// Built on first use, most runs have no unresolved references.
+    path_suggestion_index: OnceLock<PathSuggestionIndex>,
+    /// References resolved by analyzers. Analyzers run files in parallel, so it is locked.
+    pub reference_graph: Mutex<ReferenceGraph>,
+    /// Files to analyze, `None` for all of them.
+    scope: Option<HashSet<String>>,
+    /// Suppressions in json files read by the json analyzer, by file path.
+    /// Analyzers run files in parallel, so it is locked.
+    pub json_suppressions: Mutex<HashMap<String, JsonSuppressions>>,
+}
+
+impl<'a> AnalysisContext<'a> {
+    pub fn new(
+        editor_runtime_data: &'a EditorRuntimeData,
+        registry: &'a AnalyzerRegistry,
+        lint_settings: &'a LintSettings,
+    ) -> AnalysisContext<'a> {
+        AnalysisContext {
+            editor_runtime_data,
+            registry,
+            lint_settings,
+            virtual_file_system: VirtualFileSystem::build(
+                &editor_runtime_data.directory_images,
+                &editor_runtime_data.repository_trees,
+            ),
+            path_suggestion_index: OnceLock::new(),
+            reference_graph: Mutex::new(ReferenceGraph::new()),
+            scope: None,
+            json_suppressions: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Context of an incremental run over `scope` files. `reference_graph` must already
+    /// have no references of the scope files, analyzers add them again.
+    pub fn with_scope(
+        editor_runtime_data: &'a EditorRuntimeData,
+        registry: &'a AnalyzerRegistry,
+        lint_settings: &'a LintSettings,
+        scope: HashSet<String>,
+        reference_graph: ReferenceGraph,
+    ) -> AnalysisContext<'a> {
+        let mut context = AnalysisContext::new(editor_runtime_data, registry, lint_settings);
+        context.scope = Some(scope);
+        context.reference_graph = Mutex::new(reference_graph);
+        context
+    }
+
+    /// Analyzers that support scope skip files for which this returns false.
+    pub fn is_in_scope(&self, file_path: &str) -> bool {
+        match &self.scope {
+            Some(scope) => scope.contains(file_path),
+            None => true,
+        }
+    }
+
+    /// Lint config of the repository, the default config for unknown repositories.
+    pub fn get_lint_config(&self, repository_info: &RepositoryInfo) -> &LintConfig {
+        self.lint_settings.get_lint_config(repository_info)
+    }
+
+    pub fn get_path_suggestion_index(&self) -> &PathSuggestionIndex {
+        self.path_suggestion_index
+            .get_or_init(|| PathSuggestionIndex::build(&self.virtual_file_system))
+    }
+}
+
+pub trait Analyzer: Send + Sync {
+    /// Name of the analyzer for logs.
+    fn name(&self) -> &'static str;
+
+    /// All rules this analyzer can emit hints for.
+    fn rules(&self) -> &'static [Rule];
+
+    /// Must be increased whenever the same files can get different hints,
+    /// so cached results of the previous version are not reused.
+    fn version(&self) -> u32 {
+        1
+    }
+
+    /// Whether hints of a file depend only on the file and files it references,
+    /// so an incremental run can analyze only files in `AnalysisContext` scope.
+    /// Other analyzers see the whole project on every run.
+    fn supports_scope(&self) -> bool {
+        false
+    }
+
+    fn analyze(&self, context: &AnalysisContext) -> Vec<FileAnalysisResult>;
+}
+
 
 pub fn analyze_repositories(editor_runtime_data: &EditorRuntimeData) -> Vec<FileAnalysisResult> {
-    let mut results:Vec<FileAnalysisResult> = Vec::new();
+    let (results, _) = analyze_repositories_with_references(editor_runtime_data);
+    results
+}
+
+/// Same as `analyze_repositories`, but also returns references found during the analysis.
+pub fn analyze_repositories_with_references(
+    editor_runtime_data: &EditorRuntimeData,
+) -> (Vec<FileAnalysisResult>, ReferenceGraph) {
+    let registry = AnalyzerRegistry::with_default_analyzers();
+    let lint_settings = LintSettings::load(editor_runtime_data, &registry);
+    let context = AnalysisContext::new(editor_runtime_data, &registry, &lint_settings);
+    let results = run_analyzers(&context);
 
-    let TIMECHECK = std::time::Instant::now();
-    let json_analyze_task = JsonAnalyzeTask::new(editor_runtime_data);
-    results.append(&mut json_analyze_task.run());
+    (results, context.reference_graph.into_inner())
+}
+
+/// Runs every analyzer of the context registry and applies lint settings to their hints.
+pub fn run_analyzers(context: &AnalysisContext) -> Vec<FileAnalysisResult> {
+    let mut results: Vec<FileAnalysisResult> = Vec::new();
+    let mut logbox = Logbox::new();
+    for analyzer in context.registry.get_analyzers() {
+        let timecheck = std::time::Instant::now();
+        let analyzer_results = analyzer.analyze(context);
+        merge_file_analysis_results(&mut results, analyzer_results);
 
-    let ELAPSED = TIMECHECK.elapsed().as_secs_f32();
-    println!("{} {}: execution time {} sec","FUNCTION".on_bright_black(), "json analyzer".blue(), ELAPSED.to_string());
+        let elapsed = timecheck.elapsed().as_secs_f32();
+        logbox.push_message(format!(
+            "{} {}: execution time {} sec",
+            "FUNCTION".on_bright_black(),
+            analyzer.name().blue(),
+            elapsed
+        ));
+    }
+    logbox.print();
+
+    apply_lint_settings(context, &mut results);
     results
 }
 
+/// Hints of a single analyzer without lint settings applied, for tests of analyzers.
+#[cfg(test)]
+pub fn run_analyzer(analyzer: &dyn Analyzer, editor_runtime_data: &EditorRuntimeData) -> Vec<FileAnalysisResult> {
+    let registry = AnalyzerRegistry::with_default_analyzers();
+    let lint_settings = LintSettings::load(editor_runtime_data, &registry);
+    let context = AnalysisContext::new(editor_runtime_data, &registry, &lint_settings);
+    analyzer.analyze(&context)
+}
+
+/// Drops and retags hints the way lint configs of their repositories say,
+/// then moves suppressed hints and hints of the baseline aside.
+fn apply_lint_settings(context: &AnalysisContext, results: &mut [FileAnalysisResult]) {
+    static EMPTY_IGNORE_FILE: OnceLock<IgnoreFile> = OnceLock::new();
+    // every baseline hint suppresses only one hint of the run
+    let mut baseline_counts = context.lint_settings.baseline_counts.clone();
+    let json_suppressions = context.json_suppressions.lock();
+    for result in results.iter_mut() {
+        let relative_path = match result.file_path.strip_prefix(&result.repository_info.folder_path) {
+            Some(relative_path) => RelativePath::new(relative_path.to_string()),
+            None => continue,
+        };
+        context
+            .get_lint_config(&result.repository_info)
+            .apply(&relative_path, &mut result.messages);
+
+        let ignore_file = context
+            .lint_settings
+            .ignore_files
+            .get(&result.repository_info.folder_path)
+            .unwrap_or_else(|| EMPTY_IGNORE_FILE.get_or_init(IgnoreFile::default));
+        apply_suppressions(
+            ignore_file,
+            json_suppressions.get(&result.file_path),
+            &result.file_path,
+            &relative_path,
+            &mut result.messages,
+            &mut result.suppressed_messages,
+        );
+        if let Some(counts) = baseline_counts.get_mut(&result.repository_info.folder_path) {
+            apply_baseline(counts, &relative_path, &mut result.messages, &mut result.baselined_messages);
+        }
+    }
+}
+
+/// Appends hints of `other` to the results of the same file,
+/// so every file is present only once.
+pub fn merge_file_analysis_results(results: &mut Vec<FileAnalysisResult>, other: Vec<FileAnalysisResult>) {
+    let mut result_indices: HashMap<String, usize> = results
+        .iter()
+        .enumerate()
+        .map(|(index, result)| (result.file_path.clone(), index))
+        .collect();
+
+    for mut other_result in other {
+        if let Some(index) = result_indices.get(&other_result.file_path) {
+            results[*index].messages.append(&mut other_result.messages);
+            results[*index].suppressed_messages.append(&mut other_result.suppressed_messages);
+            results[*index].baselined_messages.append(&mut other_result.baselined_messages);
+        } else {
+            result_indices.insert(other_result.file_path.clone(), results.len());
+            results.push(other_result);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_result(file_path: &str, messages: Vec<Hint>) -> FileAnalysisResult {
+        FileAnalysisResult {
+            file_path: file_path.to_string(),
+            repository_info: RepositoryInfo {
+                folder_path: String::from("/mods/tank"),
+                mod_identifier: String::from("com.tank"),
+                mod_priority: 100,
+            },
+            messages,
+            suppressed_messages: Vec::new(),
+            baselined_messages: Vec::new(),
+        }
+    }
+
+    fn create_hint(rule_id: &str) -> Hint {
+        Hint::Parse {
+            category: Category::Error,
+            rule_id: rule_id.to_string(),
+            message: String::new(),
+            line: None,
+            column: None,
+        }
+    }
+
+    #[test]
+    fn results_of_the_same_file_are_merged() {
+        let mut results = vec![create_result("/mods/tank/a.json", vec![create_hint("first")])];
+        merge_file_analysis_results(
+            &mut results,
+            vec![
+                create_result("/mods/tank/b.json", vec![create_hint("second")]),
+                create_result("/mods/tank/a.json", vec![create_hint("third")]),
+            ],
+        );
+
+        assert_eq!(results.len(), 2);
+        let rule_ids: Vec<&str> = results[0].messages.iter().map(|hint| hint.rule_id()).collect();
+        assert_eq!(rule_ids, vec!["first", "third"]);
+        assert_eq!(results[1].file_path, "/mods/tank/b.json");
+    }
+
+    #[test]
+    fn lint_config_of_unknown_repository_is_the_default_one() {
+        let lint_settings = LintSettings::default();
+        let repository_info = create_result("/mods/tank/a.json", Vec::new()).repository_info;
+
+        assert_eq!(
+            lint_settings.get_lint_config(&repository_info).get_rule_setting("json-parse-error"),
+            LintConfig::default().get_rule_setting("json-parse-error")
+        );
+    }
+}
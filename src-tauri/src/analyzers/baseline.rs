@@ -0,0 +1,243 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::project::repos::repository_tree::RelativePath;
+
+use super::analyzer::{FileAnalysisResult, Hint};
+
+/// Baseline of a repository, next to modinfo.json.
+pub const BASELINE_RELATIVE_PATH: &str = "/pa-linter-baseline.json";
+
+/// Must be increased whenever fingerprints are computed differently,
+/// older baselines are ignored then.
+pub const BASELINE_VERSION: u32 = 1;
+
+/// Identifies a hint between runs without its position,
+/// so hints stay known when lines above them change.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct HintFingerprint {
+    pub rule_id: String,
+    /// Path inside the repository, e.g. `/pa/units/land/tank/tank.json`.
+    pub relative_path: String,
+    /// JSON Pointer of the value, empty for hints about the whole file.
+    pub pointer: String,
+    pub value: String,
+}
+
+impl HintFingerprint {
+    pub fn new(relative_path: &RelativePath, hint: &Hint) -> HintFingerprint {
+        let (pointer, value) = match hint {
+            Hint::JSON {
+                pointer,
+                property_value,
+                ..
+            } => (pointer.clone(), property_value.clone()),
+            Hint::Conflict { relative_path, .. } | Hint::Orphan { relative_path, .. } => {
+                (String::new(), relative_path.clone())
+            }
+            // messages have counts and positions in them, so they are not part of the fingerprint
+            Hint::OrphanSummary { .. } | Hint::Parse { .. } => (String::new(), String::new()),
+        };
+        HintFingerprint {
+            rule_id: hint.rule_id().to_string(),
+            relative_path: relative_path.value.clone(),
+            pointer,
+            value,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BaselineHint {
+    #[serde(flatten)]
+    pub fingerprint: HintFingerprint,
+    /// Message at the time the baseline was written, only for people reading the file.
+    pub message: String,
+}
+
+/// Hints that existed when the linter was adopted. Only hints that are not
+/// in the baseline are reported, baseline hints are set aside as baselined.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Baseline {
+    pub version: u32,
+    /// Ordered by fingerprint, so the file changes only where hints changed.
+    /// The same fingerprint is listed once per hint.
+    pub hints: Vec<BaselineHint>,
+}
+
+impl Baseline {
+    /// Baseline of every reported and every baselined hint of the repository.
+    /// Suppressed hints are left out, the ignore file or the file itself covers them.
+    pub fn new(folder_path: &str, results: &[FileAnalysisResult]) -> Baseline {
+        let mut hints: Vec<BaselineHint> = Vec::new();
+
+        for result in results
+            .iter()
+            .filter(|result| result.repository_info.folder_path == folder_path)
+        {
+            let relative_path = match get_relative_path(result) {
+                Some(relative_path) => relative_path,
+                None => continue,
+            };
+            for hint in result.messages.iter().chain(result.baselined_messages.iter()) {
+                hints.push(BaselineHint {
+                    fingerprint: HintFingerprint::new(&relative_path, hint),
+                    message: hint.message().to_string(),
+                });
+            }
+        }
+
+        hints.sort_by(|a, b| a.fingerprint.cmp(&b.fingerprint));
+        Baseline {
+            version: BASELINE_VERSION,
+            hints,
+        }
+    }
+
+    /// Number of baseline hints by fingerprint.
+    pub fn get_counts(&self) -> HashMap<HintFingerprint, usize> {
+        let mut counts: HashMap<HintFingerprint, usize> = HashMap::new();
+        for hint in &self.hints {
+            *counts.entry(hint.fingerprint.clone()).or_default() += 1;
+        }
+        counts
+    }
+}
+
+pub fn get_baseline_file_path(folder_path: &str) -> PathBuf {
+    Path::new(folder_path).join(BASELINE_RELATIVE_PATH.trim_start_matches('/'))
+}
+
+/// Baseline of the repository at `folder_path`, `None` if there is none
+/// or it was written by a version with other fingerprints.
+pub fn load_baseline(folder_path: &str) -> Option<Baseline> {
+    let content = fs::read_to_string(get_baseline_file_path(folder_path)).ok()?;
+    let baseline: Baseline = serde_json::from_str(&content).ok()?;
+    match baseline.version == BASELINE_VERSION {
+        true => Some(baseline),
+        false => None,
+    }
+}
+
+pub fn save_baseline(folder_path: &str, baseline: &Baseline) -> Result<(), String> {
+    let file_path = get_baseline_file_path(folder_path);
+    let content = match serde_json::to_string_pretty(baseline) {
+        Ok(content) => content,
+        Err(error) => return Err(format!("Could not serialize baseline: {}", error)),
+    };
+    if fs::write(&file_path, content).is_err() {
+        return Err(format!("Could not write baseline to {}", file_path.display()));
+    }
+    Ok(())
+}
+
+/// Writes the baseline of every repository in `folder_paths` from `results`
+/// and returns the number of hints in them.
+pub fn write_baselines(folder_paths: &[String], results: &[FileAnalysisResult]) -> Result<usize, String> {
+    let mut hint_count = 0;
+    for folder_path in folder_paths {
+        let baseline = Baseline::new(folder_path, results);
+        save_baseline(folder_path, &baseline)?;
+        hint_count += baseline.hints.len();
+    }
+    Ok(hint_count)
+}
+
+/// Moves hints that `counts` has to `baselined_messages`, each baseline hint covers one hint.
+pub fn apply_baseline(
+    counts: &mut HashMap<HintFingerprint, usize>,
+    relative_path: &RelativePath,
+    messages: &mut Vec<Hint>,
+    baselined_messages: &mut Vec<Hint>,
+) {
+    let (baselined, kept): (Vec<Hint>, Vec<Hint>) = messages
+        .drain(..)
+        .partition(|hint| take_count(counts, &HintFingerprint::new(relative_path, hint)));
+    *messages = kept;
+    baselined_messages.extend(baselined);
+}
+
+/// Decreases the count of `fingerprint`, false if there was none left.
+fn take_count(counts: &mut HashMap<HintFingerprint, usize>, fingerprint: &HintFingerprint) -> bool {
+    match counts.get_mut(fingerprint) {
+        Some(count) if *count > 0 => {
+            *count -= 1;
+            true
+        }
+        _ => false,
+    }
+}
+
+fn get_relative_path(result: &FileAnalysisResult) -> Option<RelativePath> {
+    let relative_path = result.file_path.strip_prefix(&result.repository_info.folder_path)?;
+    Some(RelativePath::new(relative_path.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{analyzers::analyzer::Category, project::repos::repository::RepositoryInfo};
+
+    use super::*;
+
+    fn create_hint(message: &str) -> Hint {
+        Hint::Parse {
+            category: Category::Error,
+            rule_id: String::from("json-parse-error"),
+            message: message.to_string(),
+            line: Some(2),
+            column: Some(1),
+        }
+    }
+
+    fn create_result(
+        messages: Vec<Hint>,
+        suppressed_messages: Vec<Hint>,
+        baselined_messages: Vec<Hint>,
+    ) -> FileAnalysisResult {
+        FileAnalysisResult {
+            file_path: String::from("/mods/tank/pa/tank.json"),
+            repository_info: RepositoryInfo {
+                folder_path: String::from("/mods/tank"),
+                mod_identifier: String::from("com.tank"),
+                mod_priority: 100,
+            },
+            messages,
+            suppressed_messages,
+            baselined_messages,
+        }
+    }
+
+    #[test]
+    fn baseline_keeps_baselined_hints_and_leaves_out_suppressed_ones() {
+        let results = vec![create_result(
+            vec![create_hint("reported")],
+            vec![create_hint("suppressed")],
+            vec![create_hint("baselined")],
+        )];
+        let baseline = Baseline::new("/mods/tank", &results);
+
+        let messages: Vec<&str> = baseline.hints.iter().map(|hint| hint.message.as_str()).collect();
+        assert_eq!(messages, vec!["reported", "baselined"]);
+        assert_eq!(baseline.hints[0].fingerprint.relative_path, "/pa/tank.json");
+    }
+
+    #[test]
+    fn each_baseline_hint_covers_one_hint() {
+        let relative_path = RelativePath::new(String::from("/pa/tank.json"));
+        let results = vec![create_result(vec![create_hint("first")], Vec::new(), Vec::new())];
+        let baseline = Baseline::new("/mods/tank", &results);
+        let mut counts = baseline.get_counts();
+
+        let mut messages = vec![create_hint("first"), create_hint("second")];
+        let mut baselined_messages = Vec::new();
+        apply_baseline(&mut counts, &relative_path, &mut messages, &mut baselined_messages);
+
+        assert_eq!(messages.len(), 1);
+        assert_eq!(baselined_messages.len(), 1);
+    }
+}
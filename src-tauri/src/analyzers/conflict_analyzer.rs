@@ -0,0 +1,177 @@
+use crate::{
+    project::repos::{
+        repository::RepositoryInfo,
+        repository_tree::{RelativePath, RepositoryTree},
+    },
+    vfs::virtual_file_system::LayerSource,
+};
+
+use super::{
+    analyzer::{AnalysisContext, Analyzer, Category, FileAnalysisResult, Hint, Rule},
+    lint_config::is_lint_file,
+};
+
+pub const CONFLICTING_SHADOW_RULE: Rule = Rule {
+    id: "conflicting-shadow",
+    description: "File is provided by several registered mods, only one of them is loaded",
+    default_category: Category::Warning,
+};
+
+const CONFLICT_RULES: [Rule; 1] = [CONFLICTING_SHADOW_RULE];
+
+/// Files every mod has. They are never loaded through the virtual file system,
+/// neither are the files of the linter, see `is_lint_file`.
+const MOD_METADATA_FILES: [&str; 1] = ["/modinfo.json"];
+
+/// Reports relative paths provided by more than one repository.
+pub struct ConflictAnalyzer;
+
+impl Analyzer for ConflictAnalyzer {
+    fn name(&self) -> &'static str {
+        "conflict analyzer"
+    }
+
+    fn rules(&self) -> &'static [Rule] {
+        &CONFLICT_RULES
+    }
+
+    fn analyze(&self, context: &AnalysisContext) -> Vec<FileAnalysisResult> {
+        let virtual_file_system = &context.virtual_file_system;
+        let repository_trees = &context.editor_runtime_data.repository_trees;
+
+        let mut relative_paths: Vec<&RelativePath> = virtual_file_system.get_paths().collect();
+        relative_paths.sort_by(|a, b| a.value.cmp(&b.value));
+
+        let mut results: Vec<FileAnalysisResult> = Vec::new();
+        for relative_path in relative_paths {
+            if MOD_METADATA_FILES.contains(&relative_path.value.as_str()) || is_lint_file(relative_path) {
+                continue;
+            }
+
+            // layers are in mount order, so the last repository is the one the game loads
+            let repositories: Vec<&RepositoryInfo> = virtual_file_system
+                .get_layers_providing(relative_path)
+                .into_iter()
+                .filter_map(|layer| match &layer.source {
+                    LayerSource::Repository { repository_info } => Some(repository_info),
+                    LayerSource::Image { .. } => None,
+                })
+                .collect();
+            if repositories.len() < 2 {
+                continue;
+            }
+
+            let winner = *repositories.last().unwrap();
+            let message = format!(
+                "{} is provided by {}. {} wins with priority {}",
+                relative_path.value,
+                repositories
+                    .iter()
+                    .map(|repository_info| format!(
+                        "{} (priority {})",
+                        repository_info.mod_identifier, repository_info.mod_priority
+                    ))
+                    .collect::<Vec<String>>()
+                    .join(", "),
+                winner.mod_identifier,
+                winner.mod_priority
+            );
+
+            for repository_info in &repositories {
+                let file_path = get_file_path(repository_trees, repository_info, relative_path);
+                if file_path.is_none() {
+                    continue;
+                }
+
+                results.push(FileAnalysisResult {
+                    file_path: file_path.unwrap(),
+                    repository_info: (*repository_info).clone(),
+                    messages: vec![Hint::Conflict {
+                        category: CONFLICTING_SHADOW_RULE.default_category,
+                        rule_id: CONFLICTING_SHADOW_RULE.id.to_string(),
+                        relative_path: relative_path.value.clone(),
+                        repositories: repositories.iter().map(|repository_info| (*repository_info).clone()).collect(),
+                        winner: winner.clone(),
+                        message: message.clone(),
+                    }],
+                    suppressed_messages: Vec::new(),
+                    baselined_messages: Vec::new(),
+                });
+            }
+        }
+
+        results
+    }
+}
+
+fn get_file_path(
+    repository_trees: &[RepositoryTree],
+    repository_info: &RepositoryInfo,
+    relative_path: &RelativePath,
+) -> Option<String> {
+    let repository_tree = repository_trees
+        .iter()
+        .find(|repository_tree| repository_tree.repository_info.folder_path == repository_info.folder_path)?;
+    let entry = repository_tree.find_entry(relative_path)?;
+    Some(entry.path.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{analyzers::analyzer::run_analyzer, test_folder::TestFolder};
+
+    use super::*;
+
+    fn write_mod(test_folder: &TestFolder, name: &str, priority: i64, files: &[&str]) {
+        let modinfo = format!("{{\"identifier\": \"{}\", \"priority\": {}}}", name, priority);
+        test_folder.write(&format!("/{}/modinfo.json", name), modinfo);
+        for file in files {
+            test_folder.write(&format!("/{}{}", name, file), "{}");
+        }
+    }
+
+    #[test]
+    fn files_of_several_mods_are_reported_for_each_mod() {
+        let test_folder = TestFolder::new();
+        write_mod(&test_folder, "low", 50, &["/pa/units/tank.json", "/pa/units/bot.json"]);
+        write_mod(&test_folder, "high", 200, &["/pa/units/tank.json"]);
+        let editor_runtime_data = test_folder.create_runtime_data(&["low", "high"]);
+
+        let results = run_analyzer(&ConflictAnalyzer, &editor_runtime_data);
+        let mut file_paths: Vec<&str> = results.iter().map(|result| result.file_path.as_str()).collect();
+        file_paths.sort();
+        let high_path = test_folder.path().join("high/pa/units/tank.json");
+        let low_path = test_folder.path().join("low/pa/units/tank.json");
+        assert_eq!(file_paths, vec![high_path.to_str().unwrap(), low_path.to_str().unwrap()]);
+
+        match &results[0].messages[0] {
+            Hint::Conflict {
+                relative_path,
+                repositories,
+                winner,
+                message,
+                ..
+            } => {
+                assert_eq!(relative_path, "/pa/units/tank.json");
+                assert_eq!(repositories.len(), 2);
+                assert_eq!(winner.mod_identifier, "high");
+                assert_eq!(
+                    message,
+                    "/pa/units/tank.json is provided by low (priority 50), high (priority 200). high wins with priority 200"
+                );
+            }
+            hint => panic!("unexpected hint {:?}", hint),
+        }
+    }
+
+    #[test]
+    fn mod_metadata_and_linter_files_never_conflict() {
+        let test_folder = TestFolder::new();
+        let lint_files = ["/pa-linter.json", "/.pa-linter-ignore", "/pa-linter-baseline.json"];
+        write_mod(&test_folder, "first", 100, &lint_files);
+        write_mod(&test_folder, "second", 100, &lint_files);
+        let editor_runtime_data = test_folder.create_runtime_data(&["first", "second"]);
+
+        assert!(run_analyzer(&ConflictAnalyzer, &editor_runtime_data).is_empty());
+    }
+}
@@ -2,13 +2,15 @@ use std::{fs, io::Read, sync::atomic::AtomicI32};
 
 use atomic_counter::{AtomicCounter, RelaxedCounter};
 use owo_colors::OwoColorize;
+use parking_lot::Mutex;
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 use serde_json::{Map, Value};
 
 use crate::{
     analyzer::{AnalysisResult, Tip},
-    analyzers::analyzer::Category,
+    analyzers::analyzer::{AnalysisContext, Analyzer, Category, Rule},
     editor::editor_runtime::EditorRuntimeData,
+    fixes::fix::Fix,
     logs::logbox::Logbox,
     nodes::NodeId,
     project::{
@@ -20,19 +22,96 @@ use crate::{
             },
         },
     },
+    references::reference_graph::{FileLocation, ReferenceEdge},
+    vfs::{
+        path_suggestions::{PathSuggestionReason, MAX_PATH_SUGGESTIONS},
+        virtual_file_system::Layer,
+    },
+};
+
+use super::{
+    analyzer::{FileAnalysisResult, Hint, Suggestion},
+    json_hints::JsonHintFactory,
+    json_reader::{read_json_file, JsonFile, JsonReadError},
+    json_source_map::join_pointer,
+    lint_config::{is_lint_file, LintConfig},
+    string_classifier::{classify_string, FileKind},
+    suppressions::JsonSuppressions,
+};
+
+pub const MISSING_LEADING_SLASH_RULE: Rule = Rule {
+    id: "missing-leading-slash",
+    description: "Relative path must start with '/'",
+    default_category: Category::Warning,
+};
+
+pub const INCORRECT_SLASH_RULE: Rule = Rule {
+    id: "incorrect-slash",
+    description: "Relative path must use '/' instead of '\\'",
+    default_category: Category::Warning,
+};
+
+pub const FILE_NOT_FOUND_RULE: Rule = Rule {
+    id: "file-not-found",
+    description: "Referenced file exists neither in repositories nor in directory images",
+    default_category: Category::Warning,
+};
+
+pub const CASE_MISMATCH_RULE: Rule = Rule {
+    id: "case-mismatch",
+    description: "Referenced file exists only with different letter case, so it is not found on case-sensitive file systems",
+    default_category: Category::Warning,
+};
+
+pub const JSON_READ_ERROR_RULE: Rule = Rule {
+    id: "json-read-error",
+    description: "Json file could not be read or is not valid UTF-8",
+    default_category: Category::Error,
+};
+
+pub const JSON_PARSE_ERROR_RULE: Rule = Rule {
+    id: "json-parse-error",
+    description: "Json file could not be parsed",
+    default_category: Category::Error,
 };
 
-use super::analyzer::{FileAnalysisResult, Hint};
+const JSON_RULES: [Rule; 6] = [
+    MISSING_LEADING_SLASH_RULE,
+    INCORRECT_SLASH_RULE,
+    FILE_NOT_FOUND_RULE,
+    CASE_MISMATCH_RULE,
+    JSON_READ_ERROR_RULE,
+    JSON_PARSE_ERROR_RULE,
+];
+
+/// Checks string properties of every json file in repositories.
+pub struct JsonAnalyzer;
+
+impl Analyzer for JsonAnalyzer {
+    fn name(&self) -> &'static str {
+        "json analyzer"
+    }
+
+    fn rules(&self) -> &'static [Rule] {
+        &JSON_RULES
+    }
+
+    fn supports_scope(&self) -> bool {
+        true
+    }
+
+    fn analyze(&self, context: &AnalysisContext) -> Vec<FileAnalysisResult> {
+        JsonAnalyzeTask::new(context).run()
+    }
+}
 
 pub struct JsonAnalyzeTask<'a> {
-    editor_runtime_data: &'a EditorRuntimeData,
+    context: &'a AnalysisContext<'a>,
 }
 
 impl<'a> JsonAnalyzeTask<'a> {
-    pub fn new(editor_runtime_data: &'a EditorRuntimeData) -> JsonAnalyzeTask {
-        JsonAnalyzeTask {
-            editor_runtime_data,
-        }
+    pub fn new(context: &'a AnalysisContext<'a>) -> JsonAnalyzeTask<'a> {
+        JsonAnalyzeTask { context }
     }
 
     pub fn run(&self) -> Vec<FileAnalysisResult> {
@@ -42,17 +121,18 @@ impl<'a> JsonAnalyzeTask<'a> {
 
     fn analyze_repository_trees(&self) -> Vec<FileAnalysisResult> {
         let mut results: Vec<FileAnalysisResult> = Vec::new();
-        for repository_tree in &self.editor_runtime_data.repository_trees {
+        for repository_tree in &self.context.editor_runtime_data.repository_trees {
             let json_entries: Vec<&RepositoryTreeEntry> = repository_tree
                 .entries
                 .iter()
-                .filter(|entry| entry.path.ends_with(".json"))
+                .filter(|entry| entry.path.ends_with(".json") && self.context.is_in_scope(&entry.path))
                 .collect();
 
-            let mut tree_logbox = Logbox::new();
-            let mut COUNTER = RelaxedCounter::new(0);
+            let tree_logbox = Mutex::new(Logbox::new());
+            let tree_results: Mutex<Vec<FileAnalysisResult>> = Mutex::new(Vec::new());
+            let COUNTER = RelaxedCounter::new(0);
 
-            json_entries.par_iter().for_each(move|entry| {
+            json_entries.par_iter().for_each(|entry| {
                 let mut entry_logbox = Logbox::new();
                 entry_logbox.push_message(format!(
                     "{}: {:?}",
@@ -83,9 +163,10 @@ impl<'a> JsonAnalyzeTask<'a> {
                 }
 
                 if let Some(result) = result {
-                    results.push(result);
+                    tree_results.lock().push(result);
                 }
 
+                let mut tree_logbox = tree_logbox.lock();
                 tree_logbox.push_logbox(entry_logbox);
 
                 COUNTER.inc();
@@ -95,11 +176,8 @@ impl<'a> JsonAnalyzeTask<'a> {
                 }
             });
 
-            for entry in json_entries {
-                
-            }
-
-            tree_logbox.print();
+            results.append(&mut tree_results.into_inner());
+            tree_logbox.into_inner().print();
         }
 
         return results;
@@ -116,38 +194,90 @@ impl<'a> JsonAnalyzeTask<'a> {
             return None;
         }
 
-        let content = fs::read_to_string(&path).unwrap();
-        let json = serde_json::from_str(&content).unwrap();
+        let relative_path = get_entry_relative_path(repository_tree, entry);
+        let file_kind = FileKind::from_relative_path(&relative_path);
+        let source = self
+            .context
+            .virtual_file_system
+            .get_repository_layer(&repository_tree.repository_info)
+            .map(|layer| FileLocation::new(layer, &relative_path));
+        let lint_config = self.context.get_lint_config(&repository_tree.repository_info);
+        let messages = match read_json_file(&path) {
+            // paths in the lint config and the baseline are not references
+            Ok(_) if is_lint_file(&relative_path) => Vec::new(),
+            Ok(json_file) => {
+                // kept for applying suppressions to hints of every analyzer, so the file is not read again
+                self.context
+                    .json_suppressions
+                    .lock()
+                    .insert(path.clone(), JsonSuppressions::from_value(&json_file.value));
+                self.analyze_json(&json_file, file_kind, source, lint_config, logbox)
+            }
+            Err(error) => {
+                logbox.push_message(format!(
+                    "{}: {}",
+                    "could not read entry".bright_red(),
+                    error.get_message()
+                ));
+                vec![create_read_error_hint(&error)]
+            }
+        };
 
-        let messages = self.analyze_json(&json, logbox);
         Some(FileAnalysisResult {
             file_path: path,
             repository_info: repository_tree.repository_info.clone(),
             messages,
+            suppressed_messages: Vec::new(),
+            baselined_messages: Vec::new(),
         })
     }
 
-    fn analyze_json(&self, json: &serde_json::Value, logbox: &mut Logbox) -> Vec<Hint> {
-        let mut messages: Vec<Hint> = Vec::new();
-        self.iterate_json_value(&mut messages, &String::from("json"), json, logbox);
-
-        return messages;
+    fn analyze_json(
+        &self,
+        json_file: &JsonFile,
+        file_kind: FileKind,
+        source: Option<FileLocation>,
+        lint_config: &LintConfig,
+        logbox: &mut Logbox,
+    ) -> Vec<Hint> {
+        let mut walk = JsonFileWalk {
+            messages: Vec::new(),
+            hint_factory: JsonHintFactory::new(&json_file.content),
+            file_kind,
+            source,
+            lint_config,
+            references: Vec::new(),
+            unresolved: Vec::new(),
+        };
+        self.iterate_json_value(&mut walk, &String::from("json"), "", &json_file.value, logbox);
+
+        if !walk.references.is_empty() || !walk.unresolved.is_empty() {
+            let mut reference_graph = self.context.reference_graph.lock();
+            reference_graph.add_edges(walk.references);
+            if let Some(source) = walk.source {
+                for relative_path in walk.unresolved {
+                    reference_graph.add_unresolved(source.clone(), relative_path);
+                }
+            }
+        }
+        return walk.messages;
     }
 
     fn iterate_json_value(
         &self,
-        messages: &mut Vec<Hint>,
+        walk: &mut JsonFileWalk,
         v_keyname: &String,
+        pointer: &str,
         v: &serde_json::Value,
         logbox: &mut Logbox,
     ) {
         match v {
-            Value::String(s) => self.analyze_json_string(messages, &v_keyname, s, logbox),
+            Value::String(s) => self.analyze_json_string(walk, &v_keyname, pointer, s, logbox),
             Value::Object(o) => {
-                self.iterate_json_object(messages, &format!("{{{}}}", v_keyname), o, logbox)
+                self.iterate_json_object(walk, &format!("{{{}}}", v_keyname), pointer, o, logbox)
             }
             Value::Array(a) => {
-                self.iterate_json_array(messages, &format!("[{}]", v_keyname), a, logbox)
+                self.iterate_json_array(walk, &format!("[{}]", v_keyname), pointer, a, logbox)
             }
             Value::Null => return,
             Value::Bool(_) => return,
@@ -157,21 +287,23 @@ impl<'a> JsonAnalyzeTask<'a> {
 
     fn iterate_json_object(
         &self,
-        messages: &mut Vec<Hint>,
+        walk: &mut JsonFileWalk,
         property_name: &String,
+        pointer: &str,
         o: &Map<String, Value>,
         logbox: &mut Logbox,
     ) {
         //println!("{}:", o_keyname);
         for (keyname, v) in o {
-            self.iterate_json_value(messages, keyname, v, logbox)
+            self.iterate_json_value(walk, keyname, &join_pointer(pointer, keyname), v, logbox)
         }
     }
 
     fn iterate_json_array(
         &self,
-        messages: &mut Vec<Hint>,
+        walk: &mut JsonFileWalk,
         property_name: &String,
+        pointer: &str,
         a: &Vec<Value>,
         logbox: &mut Logbox,
     ) {
@@ -179,23 +311,23 @@ impl<'a> JsonAnalyzeTask<'a> {
         let mut i = 0;
         for v in a {
             let array_value_name = &(property_name.to_owned() + &format!("[{}]", i));
-            self.iterate_json_value(messages, array_value_name, v, logbox);
+            let array_value_pointer = &join_pointer(pointer, &i.to_string());
+            self.iterate_json_value(walk, array_value_name, array_value_pointer, v, logbox);
             i += 1;
         }
     }
 
-    /// Analyzes JSON string `s` and returns a vector of tips.
+    /// Checks a json string that may reference a file and pushes its hints to `walk`.
     ///
-    /// This function checks if the string is a valid relative path
-    /// to a JSON file. It checks if the string starts with a slash
-    /// and if it contains only forward slashes. If it does not, it
-    /// returns a vector of tips. If it does, it checks if it ends
-    /// with the ".json" extension and if it does not, it returns a
-    /// tip.
+    /// Strings the classifier does not consider file paths are skipped.
+    /// Paths without the leading slash or with backslashes get hints with a fix.
+    /// References to files of any type are recorded and checked for letter case,
+    /// but only missing json files are reported as not found.
     fn analyze_json_string(
         &self,
-        messages: &mut Vec<Hint>,
+        walk: &mut JsonFileWalk,
         property_name: &String,
+        pointer: &str,
         string_value: &String,
         logbox: &mut Logbox,
     ) {
@@ -203,120 +335,243 @@ impl<'a> JsonAnalyzeTask<'a> {
             return; // not a relative path
         }
 
-        if !has_first_slash(string_value) {
-            messages.push(Hint::JSON {
-                category: Category::Warning,
-                property_name: property_name.to_string(),
-                property_value: string_value.to_string(),
-                message: "Missing leading slash".to_string(),
-            });
-        }
-
-        if has_incorrect_slash(string_value) {
-            messages.push(Hint::JSON {
-                category: Category::Warning,
-                property_name: property_name.to_string(),
-                property_value: string_value.to_string(),
-                message: "Incorrect slash".to_string(),
-            });
-        }
-
-        // TODO: Сделать продвинутую проверку для разных типов файлов: джсон, звуки, эффекты
-        let property_value_file_type = string_value.split('.').last().unwrap();
-        if property_value_file_type != "json" {
+        // audio events, urls and text with slashes are not files
+        let string_kind = classify_string(walk.file_kind, pointer, string_value);
+        if !string_kind.is_path() {
+            logbox.push_message(format!(
+                "{} skipped property: {} ({:?})",
+                "classifier".on_blue(),
+                string_value.cyan(),
+                string_kind
+            ));
             return;
         }
 
-        // ПРОВЕРКА СВЯЗЕЙ
         // мы умные, поэтому проверять связи нужно сразу с исправленой строкой
         let mut fixed_string = string_value.replace("\\", "/");
         if !fixed_string.starts_with("/") {
             fixed_string = "/".to_owned() + &fixed_string;
         }
+        let format_fix = walk.create_string_fix(pointer, &fixed_string);
+
+        if !has_first_slash(string_value) {
+            walk.push_hint(
+                &MISSING_LEADING_SLASH_RULE,
+                property_name,
+                pointer,
+                string_value,
+                "Missing leading slash",
+                format_fix.clone(),
+            );
+        }
+
+        if has_incorrect_slash(string_value) {
+            walk.push_hint(
+                &INCORRECT_SLASH_RULE,
+                property_name,
+                pointer,
+                string_value,
+                "Incorrect slash",
+                format_fix,
+            );
+        }
 
+        // ПРОВЕРКА СВЯЗЕЙ
         let path_value = fixed_string.to_string();
         let Timecheck = std::time::Instant::now();
-        let found_result = repository_tree::find_repository_entry(
-            &RelativePath::new(path_value.clone()),
-            &self.editor_runtime_data.repository_trees,
-        );
+        let provider = self
+            .context
+            .virtual_file_system
+            .get_provider(&RelativePath::new(path_value.clone()));
 
-        let found_result_string = if found_result.is_some() {
-            "found"
-        } else {
-            "not found"
-        };
-        if (found_result.is_some()) {
+        // resolved files of any type are references, but only missing json files are reported
+        if let Some(provider) = provider {
             logbox.push_message(format!(
                 "{} searched property: {} in {:?} sec. {}: {}",
-                "tree".on_blue(),
+                "vfs".on_blue(),
                 path_value.cyan(),
                 Timecheck.elapsed().as_secs_f32(),
                 "result".on_blue(),
-                found_result_string.green().bold()
+                provider.name.green().bold()
             ));
+            walk.push_reference(provider, &RelativePath::new(path_value), pointer, string_value);
         } else {
+            walk.push_unresolved(RelativePath::new(path_value.clone()));
+
+            // files under trusted roots are provided by something the linter does not see
+            if walk.lint_config.is_trusted_path(&RelativePath::new(path_value.clone())) {
+                return;
+            }
+
+            // letter case matters on every platform but Windows, whatever the file type
+            let relative_path = RelativePath::new(path_value.clone());
+            if self.analyze_case_mismatch(walk, property_name, pointer, string_value, &relative_path) {
+                return;
+            }
+
+            // TODO: Сделать продвинутую проверку для разных типов файлов: джсон, звуки, эффекты
+            let property_value_file_type = string_value.split('.').last().unwrap();
+            if property_value_file_type != "json" {
+                return;
+            }
+
             logbox.push_message(format!(
                 "{} searched property: {} in {:?} sec. {}: {}",
-                "tree".on_blue(),
+                "vfs".on_blue(),
                 path_value.cyan(),
                 Timecheck.elapsed().as_secs_f32(),
                 "result".on_blue(),
-                found_result_string.bright_red().italic()
+                "not found".bright_red().italic()
             ));
 
-            let TIMECHECK = std::time::Instant::now();
-            let exist_in_images = does_file_exist_in_images(
-                &self.editor_runtime_data,
-                RelativePath::new(path_value.clone()),
-            );
-            let ELAPSED = TIMECHECK.elapsed().as_secs_f32();
-            if exist_in_images {
-                logbox.push_message(format!(
-                    "{} searched property: {} in {:?} sec. {}: {}",
-                    "image".on_yellow(),
-                    path_value.cyan(),
-                    ELAPSED,
-                    "result".on_yellow(),
-                    "found".green().bold()
-                ));
-            } else {
-                logbox.push_message(format!(
-                    "{} searched property: {} in {:?} sec. {}: {}",
-                    "image".on_yellow(),
-                    path_value.cyan(),
-                    ELAPSED,
-                    "result".on_yellow(),
-                    "not found".bright_red().italic()
-                ));
-            }
+            let suggestions = self.get_path_suggestions(walk, pointer, &relative_path);
+            let message = match suggestions.first() {
+                Some(suggestion) => format!("File not found. Did you mean {}?", suggestion.value),
+                None => String::from("File not found"),
+            };
+            let hint = walk
+                .hint_factory
+                .create_hint(&FILE_NOT_FOUND_RULE, property_name, pointer, string_value, &message, None)
+                .with_suggestions(suggestions);
+            walk.messages.push(hint);
         }
+    }
 
-        // Добавить проверку на наличие файла в снимках
-        if found_result.is_none() {
-            messages.push(Hint::JSON {
-                category: Category::Warning,
-                property_name: property_name.to_string(),
-                property_value: string_value.to_string(),
-                message: "File not found".to_string(),
-            })
+    /// Reports a reference that resolves only when case is ignored, as on Windows.
+    /// Returns false if no file matches it case-insensitively either.
+    fn analyze_case_mismatch(
+        &self,
+        walk: &mut JsonFileWalk,
+        property_name: &str,
+        pointer: &str,
+        string_value: &str,
+        relative_path: &RelativePath,
+    ) -> bool {
+        let spellings = self
+            .context
+            .get_path_suggestion_index()
+            .find_case_insensitive(relative_path);
+        if spellings.is_empty() {
+            return false;
         }
+
+        // paths of the virtual file system are spelled as on disk
+        let spelling = spellings[0];
+        let provider = self
+            .context
+            .virtual_file_system
+            .get_provider(spelling)
+            .map(|layer| layer.name.clone())
+            .unwrap_or_default();
+        let message = format!(
+            "File exists only as {} in {}, letter case must match",
+            spelling.value, provider
+        );
+        let fix = walk.create_string_fix(pointer, &spelling.value);
+        let suggestions = spellings
+            .iter()
+            .skip(1)
+            .map(|spelling| {
+                walk.hint_factory
+                    .create_suggestion(pointer, &spelling.value, "Differs only in letter case")
+            })
+            .collect();
+
+        let hint = walk
+            .hint_factory
+            .create_hint(&CASE_MISMATCH_RULE, property_name, pointer, string_value, &message, fix)
+            .with_suggestions(suggestions);
+        walk.messages.push(hint);
+        true
+    }
+
+    /// Existing files close to the unresolved `relative_path`, each with a fix that puts it into the value.
+    fn get_path_suggestions(&self, walk: &JsonFileWalk, pointer: &str, relative_path: &RelativePath) -> Vec<Suggestion> {
+        self.context
+            .get_path_suggestion_index()
+            .find_candidates(relative_path, MAX_PATH_SUGGESTIONS)
+            .into_iter()
+            .map(|candidate| {
+                let message = match candidate.reason {
+                    PathSuggestionReason::CaseMismatch => "Differs only in letter case",
+                    PathSuggestionReason::SameFileName => "Same file name in another folder",
+                    PathSuggestionReason::EditDistance => "Similar path",
+                };
+                walk.hint_factory
+                    .create_suggestion(pointer, &candidate.relative_path.value, message)
+            })
+            .collect()
     }
 }
 
-fn does_file_exist_in_images(
-    editor_runtime_data: &EditorRuntimeData,
-    file_path: RelativePath,
-) -> bool {
-    let directory_images = &editor_runtime_data.directory_images;
+/// Hints of the json file that is being analyzed.
+struct JsonFileWalk<'a> {
+    messages: Vec<Hint>,
+    hint_factory: JsonHintFactory<'a>,
+    /// Kind of the file, used to tell paths from other strings.
+    file_kind: FileKind,
+    /// The file itself, `None` if its repository is not mounted.
+    source: Option<FileLocation>,
+    /// Lint config of the file repository, for its trusted roots.
+    lint_config: &'a LintConfig,
+    /// References of the file that resolve.
+    references: Vec<ReferenceEdge>,
+    /// Paths of references that don't resolve.
+    unresolved: Vec<RelativePath>,
+}
+
+impl<'a> JsonFileWalk<'a> {
+    fn create_string_fix(&self, pointer: &str, replacement_value: &str) -> Option<Fix> {
+        self.hint_factory.create_string_fix(pointer, replacement_value)
+    }
+
+    fn push_reference(&mut self, target_layer: &Layer, target_path: &RelativePath, pointer: &str, value: &str) {
+        if let Some(source) = &self.source {
+            self.references.push(ReferenceEdge {
+                source: source.clone(),
+                target: FileLocation::new(target_layer, target_path),
+                pointer: pointer.to_string(),
+                value: value.to_string(),
+            });
+        }
+    }
 
-    for image in directory_images {
-        if image.files.contains(&file_path.value.to_string()) {
-            return true;
+    fn push_unresolved(&mut self, relative_path: RelativePath) {
+        if !self.unresolved.contains(&relative_path) {
+            self.unresolved.push(relative_path);
         }
     }
 
-    false
+    fn push_hint(
+        &mut self,
+        rule: &Rule,
+        property_name: &str,
+        pointer: &str,
+        property_value: &str,
+        message: &str,
+        fix: Option<Fix>,
+    ) {
+        let hint = self
+            .hint_factory
+            .create_hint(rule, property_name, pointer, property_value, message, fix);
+        self.messages.push(hint);
+    }
+}
+
+fn create_read_error_hint(error: &JsonReadError) -> Hint {
+    let rule = match error {
+        JsonReadError::Parse { .. } => &JSON_PARSE_ERROR_RULE,
+        _ => &JSON_READ_ERROR_RULE,
+    };
+    let position = error.get_position();
+
+    Hint::Parse {
+        category: rule.default_category,
+        rule_id: rule.id.to_string(),
+        message: error.get_message().to_string(),
+        line: position.map(|(line, _)| line),
+        column: position.map(|(_, column)| column),
+    }
 }
 
 fn has_slash(s: &str) -> bool {
@@ -334,3 +589,64 @@ fn has_incorrect_slash(s: &str) -> bool {
 fn has_first_slash(s: &str) -> bool {
     s.starts_with('/')
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{analyzers::analyzer::run_analyzer, test_folder::TestFolder};
+
+    use super::*;
+
+    fn analyze_base_spec(files: &[&str], base_spec: &str) -> Vec<Hint> {
+        let test_folder = TestFolder::new();
+        test_folder.write("/tank/modinfo.json", r#"{"identifier": "com.tank"}"#);
+        for file in files {
+            test_folder.write(&format!("/tank{}", file), "{}");
+        }
+        test_folder.write("/tank/pa/units/bot.json", format!("{{\"base_spec\": \"{}\"}}", base_spec));
+        let editor_runtime_data = test_folder.create_runtime_data(&["tank"]);
+
+        run_analyzer(&JsonAnalyzer, &editor_runtime_data)
+            .into_iter()
+            .filter(|result| result.file_path.ends_with("bot.json"))
+            .flat_map(|result| result.messages)
+            .collect()
+    }
+
+    #[test]
+    fn reference_with_other_letter_case_is_fixed_to_the_spelling_on_disk() {
+        let hints = analyze_base_spec(&["/pa/units/Tank.json", "/pa/units/TANK.json"], "/pa/units/tank.json");
+
+        assert_eq!(hints.len(), 1);
+        match &hints[0] {
+            Hint::JSON {
+                rule_id,
+                pointer,
+                message,
+                fix,
+                suggestions,
+                ..
+            } => {
+                assert_eq!(rule_id, CASE_MISMATCH_RULE.id);
+                assert_eq!(pointer, "/base_spec");
+                assert_eq!(message, "File exists only as /pa/units/TANK.json in com.tank, letter case must match");
+                assert_eq!(fix.as_ref().unwrap().replacement_text, "\"/pa/units/TANK.json\"");
+                assert_eq!(suggestions.len(), 1);
+                assert_eq!(suggestions[0].value, "/pa/units/Tank.json");
+            }
+            hint => panic!("unexpected hint {:?}", hint),
+        }
+    }
+
+    #[test]
+    fn exact_spelling_is_not_a_case_mismatch() {
+        assert!(analyze_base_spec(&["/pa/units/tank.json", "/pa/units/Tank.json"], "/pa/units/tank.json").is_empty());
+    }
+
+    #[test]
+    fn missing_file_is_not_a_case_mismatch() {
+        let hints = analyze_base_spec(&[], "/pa/units/tank.json");
+
+        assert_eq!(hints.len(), 1);
+        assert_eq!(hints[0].rule_id(), FILE_NOT_FOUND_RULE.id);
+    }
+}
@@ -0,0 +1,65 @@
+use crate::fixes::fix::Fix;
+
+use super::{
+    analyzer::{Hint, Rule, Suggestion},
+    json_source_map::{JsonSourceMap, SourceSpan},
+};
+
+/// Creates json hints located in the source text of a single file.
+pub struct JsonHintFactory<'a> {
+    content: &'a str,
+    source_map: Option<JsonSourceMap>,
+}
+
+impl<'a> JsonHintFactory<'a> {
+    pub fn new(content: &'a str) -> JsonHintFactory<'a> {
+        JsonHintFactory {
+            content,
+            source_map: JsonSourceMap::parse(content),
+        }
+    }
+
+    pub fn get_span(&self, pointer: &str) -> Option<SourceSpan> {
+        self.source_map
+            .as_ref()
+            .and_then(|source_map| source_map.get_span(pointer))
+            .copied()
+    }
+
+    /// Fix that replaces the string value at `pointer` with `replacement_value`.
+    pub fn create_string_fix(&self, pointer: &str, replacement_value: &str) -> Option<Fix> {
+        let span = self.get_span(pointer)?;
+        Fix::replace_string_literal(self.content, &span, replacement_value)
+    }
+
+    /// Suggestion to replace the string value at `pointer` with `value`.
+    pub fn create_suggestion(&self, pointer: &str, value: &str, message: &str) -> Suggestion {
+        Suggestion {
+            value: value.to_string(),
+            message: message.to_string(),
+            fix: self.create_string_fix(pointer, value),
+        }
+    }
+
+    pub fn create_hint(
+        &self,
+        rule: &Rule,
+        property_name: &str,
+        pointer: &str,
+        property_value: &str,
+        message: &str,
+        fix: Option<Fix>,
+    ) -> Hint {
+        Hint::JSON {
+            category: rule.default_category,
+            rule_id: rule.id.to_string(),
+            property_name: property_name.to_string(),
+            property_value: property_value.to_string(),
+            pointer: pointer.to_string(),
+            span: self.get_span(pointer),
+            message: message.to_string(),
+            fix,
+            suggestions: Vec::new(),
+        }
+    }
+}
@@ -0,0 +1,157 @@
+use std::fs;
+
+use serde_json::Value;
+
+/// Content of a json file together with its parsed value.
+pub struct JsonFile {
+    pub content: String,
+    pub value: Value,
+}
+
+#[derive(Debug, Clone)]
+pub enum JsonReadError {
+    /// File could not be read from disk.
+    Read { message: String },
+    /// File is not valid UTF-8. Position points at the first invalid byte.
+    Encoding {
+        message: String,
+        line: usize,
+        column: usize,
+    },
+    /// File is not valid json.
+    Parse {
+        message: String,
+        line: usize,
+        column: usize,
+    },
+}
+
+impl JsonReadError {
+    pub fn get_message(&self) -> &str {
+        match self {
+            JsonReadError::Read { message } => message,
+            JsonReadError::Encoding { message, .. } => message,
+            JsonReadError::Parse { message, .. } => message,
+        }
+    }
+
+    /// 1-based line and column of the error, if the error has a position.
+    pub fn get_position(&self) -> Option<(usize, usize)> {
+        match self {
+            JsonReadError::Read { .. } => None,
+            JsonReadError::Encoding { line, column, .. } => Some((*line, *column)),
+            JsonReadError::Parse { line, column, .. } => Some((*line, *column)),
+        }
+    }
+}
+
+pub fn read_json_file(path: &str) -> Result<JsonFile, JsonReadError> {
+    let bytes = fs::read(path);
+    if bytes.is_err() {
+        return Err(JsonReadError::Read {
+            message: format!("Could not read file: {}", bytes.err().unwrap()),
+        });
+    }
+
+    let content = String::from_utf8(bytes.unwrap());
+    if content.is_err() {
+        let error = content.err().unwrap();
+        let valid_part = &error.as_bytes()[..error.utf8_error().valid_up_to()];
+        // valid_up_to guarantees that the part before it is valid
+        let valid_part = std::str::from_utf8(valid_part).unwrap();
+        let (line, column) = get_text_end_position(valid_part);
+        return Err(JsonReadError::Encoding {
+            message: format!("File is not valid UTF-8: {}", error.utf8_error()),
+            line,
+            column,
+        });
+    }
+    let content = content.unwrap();
+
+    let value = serde_json::from_str::<Value>(&content);
+    if value.is_err() {
+        let error = value.err().unwrap();
+        // position is kept separately, so it is cut from the serde message
+        let error_message = error.to_string();
+        let error_message = error_message
+            .rsplit_once(" at line ")
+            .map(|(error_message, _)| error_message)
+            .unwrap_or(&error_message);
+        return Err(JsonReadError::Parse {
+            message: format!("Invalid json: {}", error_message),
+            // serde reports column 0 when the error is right after a line break
+            line: error.line().max(1),
+            column: error.column().max(1),
+        });
+    }
+
+    Ok(JsonFile {
+        content,
+        value: value.unwrap(),
+    })
+}
+
+/// Returns 1-based line and column of the character right after `text`.
+fn get_text_end_position(text: &str) -> (usize, usize) {
+    let line = text.matches('\n').count() + 1;
+    let line_start = text.rfind('\n').map(|index| index + 1).unwrap_or(0);
+    let column = text[line_start..].chars().count() + 1;
+    (line, column)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_folder::TestFolder;
+
+    use super::*;
+
+    #[test]
+    fn valid_json_is_read_with_its_content() {
+        let test_folder = TestFolder::new();
+        let file_path = test_folder.write("/unit.json", "{\"a\": 1}");
+        let json_file = read_json_file(&file_path).unwrap();
+
+        assert_eq!(json_file.content, "{\"a\": 1}");
+        assert_eq!(json_file.value["a"], 1);
+    }
+
+    #[test]
+    fn missing_file_has_no_position() {
+        let test_folder = TestFolder::new();
+        let error = read_json_file(&test_folder.path_string()).err().unwrap();
+
+        assert!(matches!(error, JsonReadError::Read { .. }));
+        assert_eq!(error.get_position(), None);
+    }
+
+    #[test]
+    fn invalid_utf8_points_at_the_first_invalid_byte() {
+        let test_folder = TestFolder::new();
+        let file_path = test_folder.write("/unit.json", b"{\n  \"a\": \"\xff\"}");
+        let error = read_json_file(&file_path).err().unwrap();
+
+        assert!(matches!(error, JsonReadError::Encoding { .. }));
+        assert!(error.get_message().starts_with("File is not valid UTF-8"));
+        assert_eq!(error.get_position(), Some((2, 9)));
+    }
+
+    #[test]
+    fn parse_error_message_has_no_position() {
+        let test_folder = TestFolder::new();
+        let file_path = test_folder.write("/unit.json", "{\"a\": 1,}");
+        let error = read_json_file(&file_path).err().unwrap();
+
+        assert_eq!(error.get_message(), "Invalid json: trailing comma");
+        assert_eq!(error.get_position(), Some((1, 9)));
+    }
+
+    #[test]
+    fn truncated_json_has_1_based_position() {
+        let test_folder = TestFolder::new();
+        let file_path = test_folder.write("/unit.json", "{\"a\": \n");
+        let error = read_json_file(&file_path).err().unwrap();
+
+        assert_eq!(error.get_message(), "Invalid json: EOF while parsing a value");
+        assert_eq!(error.get_position(), Some((2, 1)));
+    }
+}
@@ -0,0 +1,292 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+/// Position in a source text. Line and column are 1-based,
+/// column is counted in characters, offset is a byte offset.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SourcePosition {
+    pub line: usize,
+    pub column: usize,
+    pub offset: usize,
+}
+
+/// Span of a json value in a source text. `end` points right after the value.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SourceSpan {
+    pub start: SourcePosition,
+    pub end: SourcePosition,
+}
+
+/// Spans of all values of a json text by their JSON Pointer (RFC 6901).
+/// The root value has an empty pointer.
+#[derive(Debug, Clone, Default)]
+pub struct JsonSourceMap {
+    spans: HashMap<String, SourceSpan>,
+}
+
+impl JsonSourceMap {
+    /// Scans `content` and records span of every value.
+    /// Returns `None` if `content` is not valid json.
+    pub fn parse(content: &str) -> Option<JsonSourceMap> {
+        let mut scanner = JsonScanner {
+            content,
+            bytes: content.as_bytes(),
+            position: SourcePosition {
+                line: 1,
+                column: 1,
+                offset: 0,
+            },
+            spans: HashMap::new(),
+        };
+
+        scanner.skip_whitespace();
+        scanner.scan_value(String::new())?;
+        scanner.skip_whitespace();
+        if scanner.position.offset != scanner.bytes.len() {
+            return None;
+        }
+
+        Some(JsonSourceMap {
+            spans: scanner.spans,
+        })
+    }
+
+    pub fn get_span(&self, pointer: &str) -> Option<&SourceSpan> {
+        self.spans.get(pointer)
+    }
+}
+
+/// Appends `token` to `pointer` with RFC 6901 escaping.
+pub fn join_pointer(pointer: &str, token: &str) -> String {
+    format!("{}/{}", pointer, token.replace('~', "~0").replace('/', "~1"))
+}
+
+struct JsonScanner<'a> {
+    content: &'a str,
+    bytes: &'a [u8],
+    position: SourcePosition,
+    spans: HashMap<String, SourceSpan>,
+}
+
+impl<'a> JsonScanner<'a> {
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.position.offset).copied()
+    }
+
+    /// Moves to the next character. Columns count characters,
+    /// so continuation bytes of UTF-8 don't move the column.
+    fn advance(&mut self) {
+        let byte = self.bytes[self.position.offset];
+        self.position.offset += 1;
+        if byte == b'\n' {
+            self.position.line += 1;
+            self.position.column = 1;
+        } else if byte & 0b1100_0000 != 0b1000_0000 {
+            self.position.column += 1;
+        }
+    }
+
+    fn expect(&mut self, expected: u8) -> Option<()> {
+        if self.peek()? != expected {
+            return None;
+        }
+        self.advance();
+        Some(())
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(byte) = self.peek() {
+            if !matches!(byte, b' ' | b'\t' | b'\n' | b'\r') {
+                break;
+            }
+            self.advance();
+        }
+    }
+
+    fn scan_value(&mut self, pointer: String) -> Option<()> {
+        let start = self.position;
+        match self.peek()? {
+            b'{' => self.scan_object(&pointer)?,
+            b'[' => self.scan_array(&pointer)?,
+            b'"' => {
+                self.scan_string()?;
+            }
+            b't' => self.scan_literal("true")?,
+            b'f' => self.scan_literal("false")?,
+            b'n' => self.scan_literal("null")?,
+            b'-' | b'0'..=b'9' => self.scan_number()?,
+            _ => return None,
+        }
+
+        self.spans.insert(
+            pointer,
+            SourceSpan {
+                start,
+                end: self.position,
+            },
+        );
+        Some(())
+    }
+
+    fn scan_object(&mut self, pointer: &str) -> Option<()> {
+        self.expect(b'{')?;
+        self.skip_whitespace();
+        if self.peek()? == b'}' {
+            self.advance();
+            return Some(());
+        }
+
+        loop {
+            self.skip_whitespace();
+            let key = self.scan_string()?;
+            self.skip_whitespace();
+            self.expect(b':')?;
+            self.skip_whitespace();
+            self.scan_value(join_pointer(pointer, &key))?;
+            self.skip_whitespace();
+
+            match self.peek()? {
+                b',' => self.advance(),
+                b'}' => {
+                    self.advance();
+                    return Some(());
+                }
+                _ => return None,
+            }
+        }
+    }
+
+    fn scan_array(&mut self, pointer: &str) -> Option<()> {
+        self.expect(b'[')?;
+        self.skip_whitespace();
+        if self.peek()? == b']' {
+            self.advance();
+            return Some(());
+        }
+
+        let mut index = 0;
+        loop {
+            self.skip_whitespace();
+            self.scan_value(join_pointer(pointer, &index.to_string()))?;
+            self.skip_whitespace();
+            index += 1;
+
+            match self.peek()? {
+                b',' => self.advance(),
+                b']' => {
+                    self.advance();
+                    return Some(());
+                }
+                _ => return None,
+            }
+        }
+    }
+
+    /// Scans a string literal and returns its decoded value.
+    fn scan_string(&mut self) -> Option<String> {
+        let start_offset = self.position.offset;
+        self.expect(b'"')?;
+        loop {
+            match self.peek()? {
+                b'"' => {
+                    self.advance();
+                    break;
+                }
+                b'\\' => {
+                    self.advance();
+                    self.advance_checked()?;
+                }
+                _ => self.advance(),
+            }
+        }
+
+        serde_json::from_str(&self.content[start_offset..self.position.offset]).ok()
+    }
+
+    fn scan_literal(&mut self, literal: &str) -> Option<()> {
+        for expected in literal.bytes() {
+            self.expect(expected)?;
+        }
+        Some(())
+    }
+
+    fn scan_number(&mut self) -> Option<()> {
+        let start_offset = self.position.offset;
+        while let Some(byte) = self.peek() {
+            if !matches!(byte, b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9') {
+                break;
+            }
+            self.advance();
+        }
+
+        if self.position.offset == start_offset {
+            return None;
+        }
+        Some(())
+    }
+
+    fn advance_checked(&mut self) -> Option<()> {
+        self.peek()?;
+        self.advance();
+        Some(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_start(source_map: &JsonSourceMap, pointer: &str) -> (usize, usize) {
+        let span = source_map.get_span(pointer).unwrap();
+        (span.start.line, span.start.column)
+    }
+
+    #[test]
+    fn records_spans_of_nested_values() {
+        let content = "{\"a\": {\"b\": [1, [true, null]]}}";
+        let source_map = JsonSourceMap::parse(content).unwrap();
+
+        assert_eq!(get_start(&source_map, ""), (1, 1));
+        assert_eq!(get_start(&source_map, "/a/b"), (1, 13));
+        assert_eq!(get_start(&source_map, "/a/b/1/0"), (1, 18));
+        let span = source_map.get_span("/a/b/1/1").unwrap();
+        assert_eq!(&content[span.start.offset..span.end.offset], "null");
+    }
+
+    #[test]
+    fn escapes_keys_and_skips_escaped_quotes() {
+        let content = r#"{"a/b": "x\"y", "c~d": "é", "e": 1}"#;
+        let source_map = JsonSourceMap::parse(content).unwrap();
+
+        let span = source_map.get_span("/a~1b").unwrap();
+        assert_eq!(&content[span.start.offset..span.end.offset], r#""x\"y""#);
+        assert!(source_map.get_span("/c~0d").is_some());
+        assert_eq!(get_start(&source_map, "/e"), (1, 34));
+    }
+
+    #[test]
+    fn counts_lines_with_crlf_and_columns_in_characters() {
+        let content = "{\r\n  \"é\": \"ü\",\r\n  \"b\": 2\r\n}";
+        let source_map = JsonSourceMap::parse(content).unwrap();
+
+        assert_eq!(get_start(&source_map, "/é"), (2, 8));
+        assert_eq!(get_start(&source_map, "/b"), (3, 8));
+        let span = source_map.get_span("").unwrap();
+        assert_eq!((span.end.line, span.end.column), (4, 2));
+    }
+
+    #[test]
+    fn rejects_invalid_json() {
+        assert!(JsonSourceMap::parse("{\"a\": }").is_none());
+        assert!(JsonSourceMap::parse("[1, 2").is_none());
+        assert!(JsonSourceMap::parse("{} {}").is_none());
+        assert!(JsonSourceMap::parse("\"unterminated").is_none());
+    }
+
+    #[test]
+    fn joins_pointer_tokens() {
+        assert_eq!(join_pointer("", "a/b~c"), "/a~1b~0c");
+        assert_eq!(join_pointer("/x", "0"), "/x/0");
+    }
+}
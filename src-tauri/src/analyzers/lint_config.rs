@@ -0,0 +1,461 @@
+use std::{collections::HashMap, path::{Path, PathBuf}};
+
+use glob::{MatchOptions, Pattern};
+use serde_json::{Map, Value};
+
+use crate::project::repos::repository_tree::RelativePath;
+
+use super::{
+    analyzer::{Category, Hint, Rule},
+    baseline::BASELINE_RELATIVE_PATH,
+    json_reader::{read_json_file, JsonFile},
+    json_source_map::join_pointer,
+    suppressions::IGNORE_FILE_RELATIVE_PATH,
+};
+
+pub const LINT_CONFIG_INVALID_RULE: Rule = Rule {
+    id: "lint-config-invalid",
+    description: "pa-linter.json has a field or value the linter does not understand, it is ignored",
+    default_category: Category::Error,
+};
+
+pub const LINT_CONFIG_UNKNOWN_RULE_RULE: Rule = Rule {
+    id: "lint-config-unknown-rule",
+    description: "pa-linter.json configures a rule that does not exist",
+    default_category: Category::Warning,
+};
+
+pub const LINT_IGNORE_INVALID_RULE: Rule = Rule {
+    id: "lint-ignore-invalid",
+    description: ".pa-linter-ignore has an invalid path pattern or an unknown rule, that part of the line suppresses nothing",
+    default_category: Category::Warning,
+};
+
+/// Lint config of a repository, next to modinfo.json.
+pub const LINT_CONFIG_RELATIVE_PATH: &str = "/pa-linter.json";
+
+const RULES_FIELD: &str = "rules";
+const INCLUDE_FIELD: &str = "include";
+const EXCLUDE_FIELD: &str = "exclude";
+const TRUSTED_ROOTS_FIELD: &str = "trusted_roots";
+
+/// `*` must not match '/', only `**` crosses folders.
+const PATH_MATCH_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+/// What the config says about a rule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuleSetting {
+    /// Hints of the rule are dropped.
+    Off,
+    /// Hints keep the default category of the rule.
+    On,
+    /// Hints get this category instead of the default one.
+    Category(Category),
+}
+
+impl RuleSetting {
+    fn from_name(name: &str) -> Option<RuleSetting> {
+        match name {
+            "off" => Some(RuleSetting::Off),
+            "on" => Some(RuleSetting::On),
+            "error" => Some(RuleSetting::Category(Category::Error)),
+            "warning" => Some(RuleSetting::Category(Category::Warning)),
+            "suggestion" => Some(RuleSetting::Category(Category::Suggestion)),
+            _ => None,
+        }
+    }
+}
+
+/// Per-repository settings read from `pa-linter.json`:
+///
+/// ```json
+/// {
+///     "rules": { "orphaned-file": "off", "missing-leading-slash": "error" },
+///     "include": ["/pa/**"],
+///     "exclude": ["/pa/units/test/**"],
+///     "trusted_roots": ["/pa/units/other_mod"]
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct LintConfig {
+    /// Settings by rule id, rules without a setting are on with their default category.
+    pub rules: HashMap<String, RuleSetting>,
+    /// Files to report hints for, every file if empty.
+    pub include: Vec<Pattern>,
+    /// Files to never report hints for, wins over `include`.
+    pub exclude: Vec<Pattern>,
+    /// Files under these folders are provided at runtime, e.g. by another mod,
+    /// so references to them are not checked.
+    pub trusted_roots: Vec<RelativePath>,
+}
+
+/// Invalid part of a config, reported as a hint of the config file.
+#[derive(Debug, Clone)]
+pub struct LintConfigError {
+    pub rule: &'static Rule,
+    pub property_name: String,
+    pub pointer: String,
+    pub value: String,
+    pub message: String,
+}
+
+impl LintConfig {
+    /// Reads the valid parts of `value`, everything else is returned as errors.
+    pub fn parse(value: &Value, known_rules: &[&'static Rule]) -> (LintConfig, Vec<LintConfigError>) {
+        let mut config = LintConfig::default();
+        let mut errors: Vec<LintConfigError> = Vec::new();
+
+        let fields = match value.as_object() {
+            Some(fields) => fields,
+            None => {
+                errors.push(create_invalid_error("config", "", value, "Lint config must contain an object"));
+                return (config, errors);
+            }
+        };
+
+        for (field_name, field_value) in fields {
+            let pointer = join_pointer("", field_name);
+            match field_name.as_str() {
+                RULES_FIELD => config.parse_rules(field_value, &pointer, known_rules, &mut errors),
+                INCLUDE_FIELD => config.include = parse_patterns(field_name, field_value, &pointer, &mut errors),
+                EXCLUDE_FIELD => config.exclude = parse_patterns(field_name, field_value, &pointer, &mut errors),
+                TRUSTED_ROOTS_FIELD => {
+                    config.trusted_roots = get_strings(field_name, field_value, &pointer, &mut errors)
+                        .into_iter()
+                        .map(|(_, root)| RelativePath::new(root.trim_end_matches(['/', '\\']).to_string()))
+                        .collect();
+                }
+                _ => errors.push(create_invalid_error(
+                    field_name,
+                    &pointer,
+                    field_value,
+                    &format!(
+                        "Unknown field \"{}\", expected {}, {}, {} or {}",
+                        field_name, RULES_FIELD, INCLUDE_FIELD, EXCLUDE_FIELD, TRUSTED_ROOTS_FIELD
+                    ),
+                )),
+            }
+        }
+
+        (config, errors)
+    }
+
+    fn parse_rules(
+        &mut self,
+        value: &Value,
+        pointer: &str,
+        known_rules: &[&'static Rule],
+        errors: &mut Vec<LintConfigError>,
+    ) {
+        let rules: &Map<String, Value> = match value.as_object() {
+            Some(rules) => rules,
+            None => {
+                errors.push(create_invalid_error(RULES_FIELD, pointer, value, "\"rules\" must be an object of rule ids"));
+                return;
+            }
+        };
+
+        for (rule_id, setting) in rules {
+            let rule_pointer = join_pointer(pointer, rule_id);
+            if !known_rules.iter().any(|rule| rule.id == rule_id) {
+                errors.push(LintConfigError {
+                    rule: &LINT_CONFIG_UNKNOWN_RULE_RULE,
+                    property_name: rule_id.clone(),
+                    pointer: rule_pointer,
+                    value: get_value_text(setting),
+                    message: format!("Unknown rule \"{}\"", rule_id),
+                });
+                continue;
+            }
+
+            match setting.as_str().and_then(RuleSetting::from_name) {
+                Some(setting) => {
+                    self.rules.insert(rule_id.clone(), setting);
+                }
+                None => errors.push(create_invalid_error(
+                    rule_id,
+                    &rule_pointer,
+                    setting,
+                    "Rule setting must be \"off\", \"on\", \"error\", \"warning\" or \"suggestion\"",
+                )),
+            }
+        }
+    }
+
+    pub fn get_rule_setting(&self, rule_id: &str) -> RuleSetting {
+        self.rules.get(rule_id).copied().unwrap_or(RuleSetting::On)
+    }
+
+    /// Whether hints of the file at `relative_path` are reported.
+    pub fn is_path_included(&self, relative_path: &RelativePath) -> bool {
+        let matches = |pattern: &Pattern| pattern.matches_with(&relative_path.value, PATH_MATCH_OPTIONS);
+        (self.include.is_empty() || self.include.iter().any(matches)) && !self.exclude.iter().any(matches)
+    }
+
+    pub fn is_trusted_path(&self, relative_path: &RelativePath) -> bool {
+        self.trusted_roots
+            .iter()
+            .any(|root| relative_path.value.starts_with(&(root.value.clone() + "/")))
+    }
+
+    /// Drops hints of disabled rules and of excluded files, overrides categories of the rest.
+    /// Hints of the linter files are never excluded by the config patterns.
+    pub fn apply(&self, relative_path: &RelativePath, hints: &mut Vec<Hint>) {
+        if !is_lint_file(relative_path) && !self.is_path_included(relative_path) {
+            hints.clear();
+            return;
+        }
+
+        hints.retain(|hint| self.get_rule_setting(hint.rule_id()) != RuleSetting::Off);
+        for hint in hints.iter_mut() {
+            if let RuleSetting::Category(category) = self.get_rule_setting(hint.rule_id()) {
+                hint.set_category(category);
+            }
+        }
+    }
+}
+
+/// Files of the repository read by the linter, not by the game.
+pub fn is_lint_file(relative_path: &RelativePath) -> bool {
+    [LINT_CONFIG_RELATIVE_PATH, IGNORE_FILE_RELATIVE_PATH, BASELINE_RELATIVE_PATH].contains(&relative_path.value.as_str())
+}
+
+pub fn get_lint_config_file_path(folder_path: &str) -> PathBuf {
+    Path::new(folder_path).join(LINT_CONFIG_RELATIVE_PATH.trim_start_matches('/'))
+}
+
+/// Config file of the repository at `folder_path`, `None` if there is none or it is not valid json.
+/// Unreadable files are reported by the json analyzer.
+pub fn read_lint_config_file(folder_path: &str) -> Option<JsonFile> {
+    let file_path = get_lint_config_file_path(folder_path);
+    if !file_path.is_file() {
+        return None;
+    }
+    read_json_file(file_path.to_str()?).ok()
+}
+
+/// Config of the repository at `folder_path`, the default config if there is none.
+pub fn load_lint_config(folder_path: &str, known_rules: &[&'static Rule]) -> LintConfig {
+    match read_lint_config_file(folder_path) {
+        Some(json_file) => LintConfig::parse(&json_file.value, known_rules).0,
+        None => LintConfig::default(),
+    }
+}
+
+fn parse_patterns(field_name: &str, value: &Value, pointer: &str, errors: &mut Vec<LintConfigError>) -> Vec<Pattern> {
+    let mut patterns: Vec<Pattern> = Vec::new();
+    for (item_pointer, pattern) in get_strings(field_name, value, pointer, errors) {
+        // patterns are written like references, with or without the leading '/'
+        match Pattern::new(&RelativePath::new(pattern.clone()).value) {
+            Ok(pattern) => patterns.push(pattern),
+            Err(error) => errors.push(create_invalid_error(
+                field_name,
+                &item_pointer,
+                &Value::String(pattern),
+                &format!("Invalid glob pattern: {}", error.msg),
+            )),
+        }
+    }
+    patterns
+}
+
+/// String items of an array with their pointers. Anything else is reported.
+fn get_strings(field_name: &str, value: &Value, pointer: &str, errors: &mut Vec<LintConfigError>) -> Vec<(String, String)> {
+    let items = match value.as_array() {
+        Some(items) => items,
+        None => {
+            errors.push(create_invalid_error(
+                field_name,
+                pointer,
+                value,
+                &format!("\"{}\" must be an array of strings", field_name),
+            ));
+            return Vec::new();
+        }
+    };
+
+    let mut strings: Vec<(String, String)> = Vec::new();
+    for (index, item) in items.iter().enumerate() {
+        let item_pointer = join_pointer(pointer, &index.to_string());
+        match item.as_str() {
+            Some(string) => strings.push((item_pointer, string.to_string())),
+            None => errors.push(create_invalid_error(
+                field_name,
+                &item_pointer,
+                item,
+                &format!("\"{}\" must be an array of strings", field_name),
+            )),
+        }
+    }
+    strings
+}
+
+fn create_invalid_error(property_name: &str, pointer: &str, value: &Value, message: &str) -> LintConfigError {
+    LintConfigError {
+        rule: &LINT_CONFIG_INVALID_RULE,
+        property_name: property_name.to_string(),
+        pointer: pointer.to_string(),
+        value: get_value_text(value),
+        message: message.to_string(),
+    }
+}
+
+fn get_value_text(value: &Value) -> String {
+    match value {
+        Value::String(string) => string.clone(),
+        _ => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::test_folder::TestFolder;
+
+    use super::*;
+
+    const KNOWN_RULE: Rule = Rule {
+        id: "known-rule",
+        description: "",
+        default_category: Category::Warning,
+    };
+
+    fn parse(value: Value) -> (LintConfig, Vec<LintConfigError>) {
+        LintConfig::parse(&value, &[&KNOWN_RULE])
+    }
+
+    fn create_hint(rule_id: &str) -> Hint {
+        Hint::Parse {
+            category: Category::Warning,
+            rule_id: rule_id.to_string(),
+            message: String::new(),
+            line: None,
+            column: None,
+        }
+    }
+
+    fn get_path(value: &str) -> RelativePath {
+        RelativePath::new(value.to_string())
+    }
+
+    #[test]
+    fn valid_config_is_parsed() {
+        let (config, errors) = parse(json!({
+            "rules": { "known-rule": "error" },
+            "include": ["pa/**"],
+            "exclude": ["/pa/units/test/**"],
+            "trusted_roots": ["/pa/units/other_mod/"]
+        }));
+
+        assert!(errors.is_empty());
+        assert_eq!(config.get_rule_setting("known-rule"), RuleSetting::Category(Category::Error));
+        assert_eq!(config.get_rule_setting("other-rule"), RuleSetting::On);
+        assert_eq!(config.include[0].as_str(), "/pa/**");
+        assert_eq!(config.trusted_roots[0].value, "/pa/units/other_mod");
+    }
+
+    #[test]
+    fn unknown_rules_are_reported() {
+        let (config, errors) = parse(json!({ "rules": { "missing-rule": "off" } }));
+
+        assert!(config.rules.is_empty());
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].rule.id, LINT_CONFIG_UNKNOWN_RULE_RULE.id);
+        assert_eq!(errors[0].pointer, "/rules/missing-rule");
+        assert_eq!(errors[0].value, "off");
+    }
+
+    #[test]
+    fn bad_categories_are_reported() {
+        let (config, errors) = parse(json!({ "rules": { "known-rule": "fatal" } }));
+        assert!(config.rules.is_empty());
+        assert_eq!(errors[0].rule.id, LINT_CONFIG_INVALID_RULE.id);
+        assert_eq!(errors[0].pointer, "/rules/known-rule");
+
+        let (_, errors) = parse(json!({ "rules": { "known-rule": 1 } }));
+        assert_eq!(errors[0].value, "1");
+    }
+
+    #[test]
+    fn invalid_fields_are_reported_and_valid_ones_kept() {
+        let (config, errors) = parse(json!({
+            "rules": ["known-rule"],
+            "include": "pa/**",
+            "exclude": ["/pa/[", 5, "/pa/test/**"],
+            "severity": "error"
+        }));
+
+        let mut pointers: Vec<&str> = errors.iter().map(|error| error.pointer.as_str()).collect();
+        pointers.sort();
+        assert_eq!(pointers, vec!["/exclude/0", "/exclude/1", "/include", "/rules", "/severity"]);
+        assert!(errors.iter().all(|error| error.rule.id == LINT_CONFIG_INVALID_RULE.id));
+        assert_eq!(config.exclude.len(), 1);
+    }
+
+    #[test]
+    fn config_must_be_an_object() {
+        let (_, errors) = parse(json!(["known-rule"]));
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].pointer, "");
+    }
+
+    #[test]
+    fn invalid_json_loads_the_default_config() {
+        let test_folder = TestFolder::new();
+        test_folder.write(LINT_CONFIG_RELATIVE_PATH, r#"{"rules": {"known-rule": "off"}"#);
+
+        assert!(read_lint_config_file(&test_folder.path_string()).is_none());
+        let config = load_lint_config(&test_folder.path_string(), &[&KNOWN_RULE]);
+        assert_eq!(config.get_rule_setting("known-rule"), RuleSetting::On);
+    }
+
+    #[test]
+    fn rule_settings_drop_hints_and_override_categories() {
+        let (config, _) = parse(json!({ "rules": { "known-rule": "suggestion" } }));
+        let mut hints = vec![create_hint("known-rule"), create_hint("other-rule")];
+        config.apply(&get_path("/pa/units/tank.json"), &mut hints);
+        assert_eq!(hints[0].category(), Category::Suggestion);
+        assert_eq!(hints[1].category(), Category::Warning);
+
+        let (config, _) = parse(json!({ "rules": { "known-rule": "off" } }));
+        let mut hints = vec![create_hint("known-rule"), create_hint("other-rule")];
+        config.apply(&get_path("/pa/units/tank.json"), &mut hints);
+        assert_eq!(hints.len(), 1);
+        assert_eq!(hints[0].rule_id(), "other-rule");
+    }
+
+    #[test]
+    fn excluded_files_have_no_hints_except_linter_files() {
+        let (config, _) = parse(json!({ "include": ["/pa/**"], "exclude": ["/pa/units/test/*"] }));
+
+        assert!(config.is_path_included(&get_path("/pa/units/tank.json")));
+        assert!(!config.is_path_included(&get_path("/ui/main.json")));
+        assert!(!config.is_path_included(&get_path("/pa/units/test/tank.json")));
+        // '*' stays inside the folder
+        assert!(config.is_path_included(&get_path("/pa/units/test/tank/tank.json")));
+
+        let mut hints = vec![create_hint("known-rule")];
+        config.apply(&get_path("/ui/main.json"), &mut hints);
+        assert!(hints.is_empty());
+
+        let mut hints = vec![create_hint("known-rule")];
+        config.apply(&get_path(LINT_CONFIG_RELATIVE_PATH), &mut hints);
+        assert_eq!(hints.len(), 1);
+    }
+
+    #[test]
+    fn only_files_under_trusted_roots_are_trusted() {
+        let (config, _) = parse(json!({ "trusted_roots": ["/pa/units/other_mod", "pa/ammo/"] }));
+
+        assert!(config.is_trusted_path(&get_path("/pa/units/other_mod/bot.json")));
+        assert!(config.is_trusted_path(&get_path("/pa/ammo/shell/shell.json")));
+        assert!(!config.is_trusted_path(&get_path("/pa/units/other_mod_2/bot.json")));
+        assert!(!config.is_trusted_path(&get_path("/pa/units/other_mod")));
+    }
+}
@@ -0,0 +1,93 @@
+use super::{
+    analyzer::{AnalysisContext, Analyzer, FileAnalysisResult, Hint, Rule},
+    json_hints::JsonHintFactory,
+    lint_config::{
+        get_lint_config_file_path, read_lint_config_file, LintConfig, LINT_CONFIG_INVALID_RULE,
+        LINT_CONFIG_UNKNOWN_RULE_RULE, LINT_IGNORE_INVALID_RULE,
+    },
+    suppressions::{get_ignore_file_path, read_ignore_file, IgnoreFile},
+};
+
+const LINT_CONFIG_RULES: [Rule; 3] = [
+    LINT_CONFIG_INVALID_RULE,
+    LINT_CONFIG_UNKNOWN_RULE_RULE,
+    LINT_IGNORE_INVALID_RULE,
+];
+
+/// Reports errors of repository lint configs and ignore files.
+/// Valid parts of them are applied anyway.
+pub struct LintConfigAnalyzer;
+
+impl Analyzer for LintConfigAnalyzer {
+    fn name(&self) -> &'static str {
+        "lint config analyzer"
+    }
+
+    fn rules(&self) -> &'static [Rule] {
+        &LINT_CONFIG_RULES
+    }
+
+    fn analyze(&self, context: &AnalysisContext) -> Vec<FileAnalysisResult> {
+        let known_rules = context.registry.get_rules();
+        let mut results: Vec<FileAnalysisResult> = Vec::new();
+
+        for repository_tree in &context.editor_runtime_data.repository_trees {
+            let folder_path = &repository_tree.repository_info.folder_path;
+            if let Some(content) = read_ignore_file(folder_path) {
+                let (_, errors) = IgnoreFile::parse(&content, &known_rules);
+                if !errors.is_empty() {
+                    results.push(FileAnalysisResult {
+                        file_path: get_ignore_file_path(folder_path).to_str().unwrap().to_string(),
+                        repository_info: repository_tree.repository_info.clone(),
+                        messages: errors
+                            .into_iter()
+                            .map(|error| Hint::Parse {
+                                category: LINT_IGNORE_INVALID_RULE.default_category,
+                                rule_id: LINT_IGNORE_INVALID_RULE.id.to_string(),
+                                message: error.message,
+                                line: Some(error.line),
+                                column: Some(error.column),
+                            })
+                            .collect(),
+                        suppressed_messages: Vec::new(),
+                        baselined_messages: Vec::new(),
+                    });
+                }
+            }
+
+            let json_file = match read_lint_config_file(folder_path) {
+                Some(json_file) => json_file,
+                None => continue,
+            };
+
+            let (_, errors) = LintConfig::parse(&json_file.value, &known_rules);
+            if errors.is_empty() {
+                continue;
+            }
+
+            let hint_factory = JsonHintFactory::new(&json_file.content);
+            let messages: Vec<Hint> = errors
+                .iter()
+                .map(|error| {
+                    hint_factory.create_hint(
+                        error.rule,
+                        &error.property_name,
+                        &error.pointer,
+                        &error.value,
+                        &error.message,
+                        None,
+                    )
+                })
+                .collect();
+            results.push(FileAnalysisResult {
+                file_path: get_lint_config_file_path(folder_path).to_str().unwrap().to_string(),
+                repository_info: repository_tree.repository_info.clone(),
+                messages,
+                suppressed_messages: Vec::new(),
+                baselined_messages: Vec::new(),
+            });
+        }
+
+        results
+    }
+}
@@ -1,2 +1,18 @@
+pub mod analysis_cache;
+pub mod analysis_session;
 pub mod analyzer;
-pub mod json_analyzer;
\ No newline at end of file
+pub mod baseline;
+pub mod conflict_analyzer;
+pub mod json_analyzer;
+pub mod json_hints;
+pub mod json_reader;
+pub mod json_source_map;
+pub mod lint_config;
+pub mod lint_config_analyzer;
+pub mod modinfo_analyzer;
+pub mod orphan_analyzer;
+pub mod registry;
+pub mod scene_analyzer;
+pub mod spec_analyzer;
+pub mod string_classifier;
+pub mod suppressions;
\ No newline at end of file
@@ -0,0 +1,386 @@
+use serde_json::{Map, Value};
+
+use crate::project::repos::repository_tree::RelativePath;
+
+use super::{
+    analyzer::{AnalysisContext, Analyzer, Category, FileAnalysisResult, Hint, Rule},
+    json_hints::JsonHintFactory,
+    json_reader::read_json_file,
+    json_source_map::join_pointer,
+};
+
+pub const MODINFO_MISSING_FIELD_RULE: Rule = Rule {
+    id: "modinfo-missing-field",
+    description: "modinfo.json has no required field",
+    default_category: Category::Error,
+};
+
+pub const MODINFO_INVALID_TYPE_RULE: Rule = Rule {
+    id: "modinfo-invalid-type",
+    description: "modinfo.json field has a value of wrong type",
+    default_category: Category::Error,
+};
+
+pub const MODINFO_INVALID_CONTEXT_RULE: Rule = Rule {
+    id: "modinfo-invalid-context",
+    description: "modinfo.json context must be \"client\" or \"server\"",
+    default_category: Category::Error,
+};
+
+pub const MODINFO_INVALID_IDENTIFIER_RULE: Rule = Rule {
+    id: "modinfo-invalid-identifier",
+    description: "modinfo.json identifier must be lowercase reverse domain notation, e.g. com.author.mod_name",
+    default_category: Category::Warning,
+};
+
+pub const MODINFO_INVALID_DATE_RULE: Rule = Rule {
+    id: "modinfo-invalid-date",
+    description: "modinfo.json date must have YYYY/MM/DD format",
+    default_category: Category::Warning,
+};
+
+const MODINFO_RULES: [Rule; 5] = [
+    MODINFO_MISSING_FIELD_RULE,
+    MODINFO_INVALID_TYPE_RULE,
+    MODINFO_INVALID_CONTEXT_RULE,
+    MODINFO_INVALID_IDENTIFIER_RULE,
+    MODINFO_INVALID_DATE_RULE,
+];
+
+pub const MODINFO_RELATIVE_PATH: &str = "/modinfo.json";
+
+/// Fields every modinfo.json must have and the type of their values.
+const REQUIRED_FIELDS: [(&str, FieldType); 8] = [
+    ("identifier", FieldType::String),
+    ("display_name", FieldType::String),
+    ("version", FieldType::String),
+    ("context", FieldType::String),
+    ("build", FieldType::String),
+    ("date", FieldType::String),
+    ("signature", FieldType::String),
+    ("priority", FieldType::Integer),
+];
+
+/// Optional fields that are checked only when present.
+const OPTIONAL_FIELDS: [(&str, FieldType); 2] = [
+    ("scenes", FieldType::Scenes),
+    ("dependencies", FieldType::StringArray),
+];
+
+const MODINFO_CONTEXTS: [&str; 2] = ["client", "server"];
+
+#[derive(Clone, Copy)]
+enum FieldType {
+    String,
+    Integer,
+    StringArray,
+    /// Object of scene names to arrays of file urls.
+    Scenes,
+}
+
+impl FieldType {
+    fn get_name(&self) -> &'static str {
+        match self {
+            FieldType::String => "a string",
+            FieldType::Integer => "an integer",
+            FieldType::StringArray => "an array of strings",
+            FieldType::Scenes => "an object of string arrays",
+        }
+    }
+
+    fn matches(&self, value: &Value) -> bool {
+        match self {
+            FieldType::String => value.is_string(),
+            FieldType::Integer => value.is_i64() || value.is_u64(),
+            FieldType::StringArray => is_string_array(value),
+            FieldType::Scenes => match value.as_object() {
+                Some(scenes) => scenes.values().all(is_string_array),
+                None => false,
+            },
+        }
+    }
+}
+
+/// Validates modinfo.json of every repository.
+pub struct ModinfoAnalyzer;
+
+impl Analyzer for ModinfoAnalyzer {
+    fn name(&self) -> &'static str {
+        "modinfo analyzer"
+    }
+
+    fn rules(&self) -> &'static [Rule] {
+        &MODINFO_RULES
+    }
+
+    fn supports_scope(&self) -> bool {
+        true
+    }
+
+    fn analyze(&self, context: &AnalysisContext) -> Vec<FileAnalysisResult> {
+        let mut results: Vec<FileAnalysisResult> = Vec::new();
+
+        for repository_tree in &context.editor_runtime_data.repository_trees {
+            let entry = repository_tree.find_entry(&RelativePath::new(MODINFO_RELATIVE_PATH.to_string()));
+            if entry.is_none() {
+                continue;
+            }
+            let entry = entry.unwrap();
+            if !context.is_in_scope(&entry.path) {
+                continue;
+            }
+
+            // unreadable files are reported by the json analyzer
+            let json_file = match read_json_file(&entry.path) {
+                Ok(json_file) => json_file,
+                Err(_) => continue,
+            };
+
+            let hint_factory = JsonHintFactory::new(&json_file.content);
+            let messages = match json_file.value.as_object() {
+                Some(modinfo) => analyze_modinfo(modinfo, &hint_factory),
+                None => vec![hint_factory.create_hint(
+                    &MODINFO_INVALID_TYPE_RULE,
+                    "modinfo",
+                    "",
+                    "",
+                    "modinfo.json must contain an object",
+                    None,
+                )],
+            };
+
+            if !messages.is_empty() {
+                results.push(FileAnalysisResult {
+                    file_path: entry.path.clone(),
+                    repository_info: repository_tree.repository_info.clone(),
+                    messages,
+                    suppressed_messages: Vec::new(),
+                    baselined_messages: Vec::new(),
+                });
+            }
+        }
+
+        results
+    }
+}
+
+fn analyze_modinfo(modinfo: &Map<String, Value>, hint_factory: &JsonHintFactory) -> Vec<Hint> {
+    let mut messages: Vec<Hint> = Vec::new();
+
+    for (field_name, field_type) in REQUIRED_FIELDS {
+        match modinfo.get(field_name) {
+            Some(value) => check_field_type(&mut messages, hint_factory, field_name, field_type, value),
+            // there is no value to point at, so the hint points at the whole object
+            None => messages.push(hint_factory.create_hint(
+                &MODINFO_MISSING_FIELD_RULE,
+                field_name,
+                "",
+                "",
+                &format!("Missing required field \"{}\"", field_name),
+                None,
+            )),
+        }
+    }
+
+    for (field_name, field_type) in OPTIONAL_FIELDS {
+        if let Some(value) = modinfo.get(field_name) {
+            check_field_type(&mut messages, hint_factory, field_name, field_type, value);
+        }
+    }
+
+    if let Some(context) = modinfo.get("context").and_then(|value| value.as_str()) {
+        if !MODINFO_CONTEXTS.contains(&context) {
+            messages.push(hint_factory.create_hint(
+                &MODINFO_INVALID_CONTEXT_RULE,
+                "context",
+                &join_pointer("", "context"),
+                context,
+                &format!("Unknown context \"{}\", must be \"client\" or \"server\"", context),
+                None,
+            ));
+        }
+    }
+
+    if let Some(identifier) = modinfo.get("identifier").and_then(|value| value.as_str()) {
+        if !is_valid_identifier(identifier) {
+            messages.push(hint_factory.create_hint(
+                &MODINFO_INVALID_IDENTIFIER_RULE,
+                "identifier",
+                &join_pointer("", "identifier"),
+                identifier,
+                "Identifier must be lowercase reverse domain notation, e.g. com.author.mod_name",
+                None,
+            ));
+        }
+    }
+
+    if let Some(date) = modinfo.get("date").and_then(|value| value.as_str()) {
+        if !is_valid_date(date) {
+            messages.push(hint_factory.create_hint(
+                &MODINFO_INVALID_DATE_RULE,
+                "date",
+                &join_pointer("", "date"),
+                date,
+                "Date must have YYYY/MM/DD format",
+                None,
+            ));
+        }
+    }
+
+    messages
+}
+
+fn check_field_type(
+    messages: &mut Vec<Hint>,
+    hint_factory: &JsonHintFactory,
+    field_name: &str,
+    field_type: FieldType,
+    value: &Value,
+) {
+    if field_type.matches(value) {
+        return;
+    }
+
+    messages.push(hint_factory.create_hint(
+        &MODINFO_INVALID_TYPE_RULE,
+        field_name,
+        &join_pointer("", field_name),
+        &value.to_string(),
+        &format!("Field \"{}\" must be {}", field_name, field_type.get_name()),
+        None,
+    ));
+}
+
+fn is_string_array(value: &Value) -> bool {
+    match value.as_array() {
+        Some(array) => array.iter().all(|item| item.is_string()),
+        None => false,
+    }
+}
+
+/// At least two dot separated parts of lowercase letters, digits, '_' and '-'.
+fn is_valid_identifier(identifier: &str) -> bool {
+    let parts: Vec<&str> = identifier.split('.').collect();
+    parts.len() >= 2
+        && parts.iter().all(|part| {
+            !part.is_empty()
+                && part
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-')
+        })
+}
+
+fn is_valid_date(date: &str) -> bool {
+    let parts: Vec<&str> = date.split('/').collect();
+    if parts.len() != 3
+        || parts[0].len() != 4
+        || parts[1].len() != 2
+        || parts[2].len() != 2
+        || !parts.iter().all(|part| part.chars().all(|c| c.is_ascii_digit()))
+    {
+        return false;
+    }
+
+    let month: u32 = parts[1].parse().unwrap();
+    let day: u32 = parts[2].parse().unwrap();
+    (1..=12).contains(&month) && (1..=31).contains(&day)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    const VALID_MODINFO: &str = r#"{
+    "identifier": "com.author.tank_mod",
+    "display_name": "Tank mod",
+    "version": "1.0",
+    "context": "client",
+    "build": "123456",
+    "date": "2024/05/31",
+    "signature": " ",
+    "priority": 100,
+    "scenes": { "live_game": ["coui://ui/mods/tank/live_game.js"] }
+}"#;
+
+    fn analyze(content: &str) -> Vec<Hint> {
+        let value: Value = serde_json::from_str(content).unwrap();
+        analyze_modinfo(value.as_object().unwrap(), &JsonHintFactory::new(content))
+    }
+
+    fn get_rule_ids(hints: &[Hint]) -> Vec<&str> {
+        hints.iter().map(|hint| hint.rule_id()).collect()
+    }
+
+    #[test]
+    fn identifiers_are_lowercase_reverse_domain_names() {
+        assert!(is_valid_identifier("com.author.tank_mod"));
+        assert!(is_valid_identifier("com.author-2.tank"));
+        assert!(!is_valid_identifier("tank"));
+        assert!(!is_valid_identifier("com.Author.tank"));
+        assert!(!is_valid_identifier("com..tank"));
+        assert!(!is_valid_identifier("com.author.tank mod"));
+    }
+
+    #[test]
+    fn dates_have_year_month_and_day() {
+        assert!(is_valid_date("2024/05/31"));
+        assert!(!is_valid_date("2024-05-31"));
+        assert!(!is_valid_date("24/05/31"));
+        assert!(!is_valid_date("2024/13/01"));
+        assert!(!is_valid_date("2024/05/00"));
+        assert!(!is_valid_date("2024/5/31"));
+    }
+
+    #[test]
+    fn valid_modinfo_has_no_hints() {
+        assert!(analyze(VALID_MODINFO).is_empty());
+    }
+
+    #[test]
+    fn missing_fields_point_at_the_whole_object() {
+        let hints = analyze(r#"{"identifier": "com.author.tank", "priority": 100}"#);
+
+        assert_eq!(hints.len(), 6);
+        assert!(get_rule_ids(&hints).iter().all(|rule_id| *rule_id == MODINFO_MISSING_FIELD_RULE.id));
+        match &hints[0] {
+            Hint::JSON { pointer, message, .. } => {
+                assert_eq!(pointer, "");
+                assert_eq!(message, "Missing required field \"display_name\"");
+            }
+            hint => panic!("unexpected hint {:?}", hint),
+        }
+    }
+
+    #[test]
+    fn fields_of_wrong_type_and_value_are_reported() {
+        let mut modinfo: Value = serde_json::from_str(VALID_MODINFO).unwrap();
+        modinfo["priority"] = json!("high");
+        modinfo["context"] = json!("both");
+        modinfo["identifier"] = json!("TankMod");
+        modinfo["date"] = json!("31.05.2024");
+        modinfo["scenes"] = json!({ "live_game": "coui://ui/mods/tank/live_game.js" });
+        modinfo["dependencies"] = json!([1]);
+        let hints = analyze(&serde_json::to_string_pretty(&modinfo).unwrap());
+
+        assert_eq!(
+            get_rule_ids(&hints),
+            vec![
+                MODINFO_INVALID_TYPE_RULE.id,
+                MODINFO_INVALID_TYPE_RULE.id,
+                MODINFO_INVALID_TYPE_RULE.id,
+                MODINFO_INVALID_CONTEXT_RULE.id,
+                MODINFO_INVALID_IDENTIFIER_RULE.id,
+                MODINFO_INVALID_DATE_RULE.id,
+            ]
+        );
+        match &hints[0] {
+            Hint::JSON { pointer, span, .. } => {
+                assert_eq!(pointer, "/priority");
+                assert!(span.is_some());
+            }
+            hint => panic!("unexpected hint {:?}", hint),
+        }
+    }
+}
@@ -0,0 +1,306 @@
+use std::collections::HashSet;
+
+use crate::{
+    project::repos::repository_tree::{get_entry_relative_path, RelativePath, RepositoryTree},
+    references::reference_graph::FileLocation,
+    vfs::virtual_file_system::{LayerSource, VirtualFileSystem},
+};
+
+use super::{
+    analyzer::{AnalysisContext, Analyzer, Category, FileAnalysisResult, Hint, Rule},
+    json_reader::read_json_file,
+    lint_config::is_lint_file,
+    modinfo_analyzer::MODINFO_RELATIVE_PATH,
+    scene_analyzer::{get_coui_relative_path, SCENES_PROPERTY},
+};
+
+pub const ORPHANED_FILE_RULE: Rule = Rule {
+    id: "orphaned-file",
+    description: "File is not referenced from anything the game loads and only makes the mod bigger",
+    default_category: Category::Suggestion,
+};
+
+pub const ORPHANED_FILES_SUMMARY_RULE: Rule = Rule {
+    id: "orphaned-files-summary",
+    description: "Total size of orphaned files of a mod",
+    default_category: Category::Suggestion,
+};
+
+const ORPHAN_RULES: [Rule; 2] = [ORPHANED_FILE_RULE, ORPHANED_FILES_SUMMARY_RULE];
+
+/// The game loads the unit list by itself, every unit is reachable from it.
+const UNIT_LIST_RELATIVE_PATH: &str = "/pa/units/unit_list.json";
+
+/// Files types that are loaded only through references of json files.
+const ORPHAN_EXTENSIONS: [&str; 3] = ["json", "papa", "png"];
+
+/// Interface files are loaded by scripts, which are not followed.
+const SCRIPT_LOADED_FOLDER: &str = "/ui/";
+
+/// Reports repository files that can't be reached from any root through the reference graph.
+/// Roots are modinfo.json, unit_list.json, scene scripts and files that shadow base game files.
+/// Must run after the json analyzer, which fills the reference graph.
+pub struct OrphanAnalyzer;
+
+impl Analyzer for OrphanAnalyzer {
+    fn name(&self) -> &'static str {
+        "orphan analyzer"
+    }
+
+    fn rules(&self) -> &'static [Rule] {
+        &ORPHAN_RULES
+    }
+
+    fn analyze(&self, context: &AnalysisContext) -> Vec<FileAnalysisResult> {
+        let virtual_file_system = &context.virtual_file_system;
+        let repository_trees = &context.editor_runtime_data.repository_trees;
+
+        let roots = get_roots(virtual_file_system, repository_trees);
+        let reachable = context.reference_graph.lock().get_reachable_files(roots);
+        // textures and materials are referenced from inside binary models,
+        // so assets next to a reachable file are kept as well
+        let reachable_folders: HashSet<(usize, &str)> = reachable
+            .iter()
+            .map(|file| (file.layer_index, get_folder(&file.relative_path.value)))
+            .collect();
+
+        let mut results: Vec<FileAnalysisResult> = Vec::new();
+        for repository_tree in repository_trees {
+            let layer = match virtual_file_system.get_repository_layer(&repository_tree.repository_info) {
+                Some(layer) => layer,
+                None => continue,
+            };
+
+            let mut file_count: usize = 0;
+            let mut wasted_bytes: u64 = 0;
+            for entry in &repository_tree.entries {
+                if entry.is_directory {
+                    continue;
+                }
+
+                let relative_path = get_entry_relative_path(repository_tree, entry);
+                if !is_orphan_candidate(&relative_path) {
+                    continue;
+                }
+                if reachable.contains(&FileLocation::new(layer, &relative_path)) {
+                    continue;
+                }
+                if !relative_path.value.ends_with(".json")
+                    && reachable_folders.contains(&(layer.index, get_folder(&relative_path.value)))
+                {
+                    continue;
+                }
+
+                let size = std::fs::metadata(&entry.path).map(|metadata| metadata.len()).unwrap_or(0);
+                file_count += 1;
+                wasted_bytes += size;
+
+                results.push(FileAnalysisResult {
+                    file_path: entry.path.clone(),
+                    repository_info: repository_tree.repository_info.clone(),
+                    messages: vec![Hint::Orphan {
+                        category: ORPHANED_FILE_RULE.default_category,
+                        rule_id: ORPHANED_FILE_RULE.id.to_string(),
+                        relative_path: relative_path.value.clone(),
+                        size,
+                        message: format!(
+                            "{} is not referenced by anything the game loads ({})",
+                            relative_path.value,
+                            format_bytes(size)
+                        ),
+                    }],
+                    suppressed_messages: Vec::new(),
+                    baselined_messages: Vec::new(),
+                });
+            }
+
+            let modinfo_entry = repository_tree.find_entry(&RelativePath::new(MODINFO_RELATIVE_PATH.to_string()));
+            if file_count == 0 || modinfo_entry.is_none() {
+                continue;
+            }
+            results.push(FileAnalysisResult {
+                file_path: modinfo_entry.unwrap().path.clone(),
+                repository_info: repository_tree.repository_info.clone(),
+                messages: vec![Hint::OrphanSummary {
+                    category: ORPHANED_FILES_SUMMARY_RULE.default_category,
+                    rule_id: ORPHANED_FILES_SUMMARY_RULE.id.to_string(),
+                    file_count,
+                    wasted_bytes,
+                    message: format!(
+                        "{} orphaned files waste {}",
+                        file_count,
+                        format_bytes(wasted_bytes)
+                    ),
+                }],
+                suppressed_messages: Vec::new(),
+                baselined_messages: Vec::new(),
+            });
+        }
+
+        results
+    }
+}
+
+fn get_roots(virtual_file_system: &VirtualFileSystem, repository_trees: &[RepositoryTree]) -> Vec<FileLocation> {
+    let mut roots: Vec<FileLocation> = Vec::new();
+
+    let unit_list_path = RelativePath::new(UNIT_LIST_RELATIVE_PATH.to_string());
+    if let Some(layer) = virtual_file_system.get_provider(&unit_list_path) {
+        roots.push(FileLocation::new(layer, &unit_list_path));
+    }
+
+    for repository_tree in repository_trees {
+        let layer = match virtual_file_system.get_repository_layer(&repository_tree.repository_info) {
+            Some(layer) => layer,
+            None => continue,
+        };
+
+        let modinfo_path = RelativePath::new(MODINFO_RELATIVE_PATH.to_string());
+        roots.push(FileLocation::new(layer, &modinfo_path));
+
+        for scene_path in get_scene_paths(repository_tree) {
+            if let Some(scene_layer) = virtual_file_system.get_provider(&scene_path) {
+                roots.push(FileLocation::new(scene_layer, &scene_path));
+            }
+        }
+
+        // shadowing a base game file replaces it, so the game loads it without any reference
+        for entry in &repository_tree.entries {
+            if entry.is_directory {
+                continue;
+            }
+            let relative_path = get_entry_relative_path(repository_tree, entry);
+            let shadows_image = virtual_file_system
+                .get_layers_providing(&relative_path)
+                .iter()
+                .any(|layer| matches!(layer.source, LayerSource::Image { .. }));
+            if shadows_image {
+                roots.push(FileLocation::new(layer, &relative_path));
+            }
+        }
+    }
+
+    roots
+}
+
+/// Files listed in modinfo.json scenes of the repository.
+fn get_scene_paths(repository_tree: &RepositoryTree) -> Vec<RelativePath> {
+    let modinfo_entry = repository_tree.find_entry(&RelativePath::new(MODINFO_RELATIVE_PATH.to_string()));
+    let modinfo = match modinfo_entry.map(|entry| read_json_file(&entry.path)) {
+        Some(Ok(json_file)) => json_file.value,
+        _ => return Vec::new(),
+    };
+
+    match modinfo.get(SCENES_PROPERTY).and_then(|scenes| scenes.as_object()) {
+        Some(scenes) => scenes
+            .values()
+            .filter_map(|urls| urls.as_array())
+            .flatten()
+            .filter_map(|url| url.as_str())
+            .filter_map(get_coui_relative_path)
+            .collect(),
+        None => Vec::new(),
+    }
+}
+
+fn is_orphan_candidate(relative_path: &RelativePath) -> bool {
+    if relative_path.value.starts_with(SCRIPT_LOADED_FOLDER) || is_lint_file(relative_path) {
+        return false;
+    }
+    match relative_path.value.rsplit_once('.') {
+        Some((_, extension)) => ORPHAN_EXTENSIONS.contains(&extension.to_lowercase().as_str()),
+        None => false,
+    }
+}
+
+fn get_folder(path: &str) -> &str {
+    match path.rsplit_once('/') {
+        Some((folder, _)) => folder,
+        None => "",
+    }
+}
+
+fn format_bytes(bytes: u64) -> String {
+    if bytes < 1024 {
+        format!("{} B", bytes)
+    } else if bytes < 1024 * 1024 {
+        format!("{:.1} KiB", bytes as f64 / 1024.0)
+    } else {
+        format!("{:.1} MiB", bytes as f64 / (1024.0 * 1024.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        analyzers::{
+            analyzer::{AnalysisContext, LintSettings},
+            json_analyzer::JsonAnalyzer,
+            registry::AnalyzerRegistry,
+        },
+        directory_image::DirectoryImage,
+        editor::editor_runtime::EditorRuntimeData,
+        test_folder::TestFolder,
+    };
+
+    use super::*;
+
+    /// Orphans are found from references the json analyzer puts into the graph.
+    fn find_orphans(editor_runtime_data: &EditorRuntimeData) -> Vec<String> {
+        let registry = AnalyzerRegistry::with_default_analyzers();
+        let lint_settings = LintSettings::load(editor_runtime_data, &registry);
+        let context = AnalysisContext::new(editor_runtime_data, &registry, &lint_settings);
+        JsonAnalyzer.analyze(&context);
+
+        let mut relative_paths: Vec<String> = OrphanAnalyzer
+            .analyze(&context)
+            .into_iter()
+            .flat_map(|result| result.messages)
+            .map(|hint| match hint {
+                Hint::Orphan { relative_path, .. } => relative_path,
+                Hint::OrphanSummary { file_count, .. } => format!("{} files", file_count),
+                hint => panic!("unexpected hint {:?}", hint),
+            })
+            .collect();
+        relative_paths.sort();
+        relative_paths
+    }
+
+    fn write_tank_mod(test_folder: &TestFolder) {
+        test_folder.write("/tank/modinfo.json", r#"{"identifier": "com.tank"}"#);
+        test_folder.write("/tank/pa/units/unit_list.json", r#"{"units": ["/pa/units/tank/tank.json"]}"#);
+        test_folder.write(
+            "/tank/pa/units/tank/tank.json",
+            r#"{"tools": [{"spec_id": "/pa/units/tank/tank_weapon.json"}]}"#,
+        );
+        test_folder.write("/tank/pa/units/tank/tank_weapon.json", "{}");
+        test_folder.write("/tank/pa/units/tank/tank_diffuse.papa", "");
+        test_folder.write("/tank/pa/units/old/old.json", "{}");
+        test_folder.write("/tank/pa/units/old/old.papa", "");
+        test_folder.write("/tank/ui/mods/tank/tank.json", "{}");
+        test_folder.write("/tank/pa-linter.json", "{}");
+    }
+
+    #[test]
+    fn files_unreachable_from_roots_are_orphans() {
+        let test_folder = TestFolder::new();
+        write_tank_mod(&test_folder);
+        let editor_runtime_data = test_folder.create_runtime_data(&["tank"]);
+
+        assert_eq!(
+            find_orphans(&editor_runtime_data),
+            vec!["/pa/units/old/old.json", "/pa/units/old/old.papa", "2 files"]
+        );
+    }
+
+    #[test]
+    fn files_shadowing_base_game_files_are_roots() {
+        let test_folder = TestFolder::new();
+        write_tank_mod(&test_folder);
+        let mut editor_runtime_data = test_folder.create_runtime_data(&["tank"]);
+        let files = vec!["pa/units/old/old.json".to_string()];
+        editor_runtime_data.directory_images.push(DirectoryImage::new("pa".to_string(), files, None));
+
+        assert!(find_orphans(&editor_runtime_data).is_empty());
+    }
+}
@@ -0,0 +1,79 @@
+use super::{
+    analyzer::{Analyzer, Rule},
+    conflict_analyzer::ConflictAnalyzer,
+    json_analyzer::JsonAnalyzer,
+    lint_config_analyzer::LintConfigAnalyzer,
+    modinfo_analyzer::ModinfoAnalyzer,
+    orphan_analyzer::OrphanAnalyzer,
+    scene_analyzer::SceneAnalyzer,
+    spec_analyzer::SpecAnalyzer,
+};
+
+/// Ordered list of analyzers. `analyze_repositories` runs them one by one,
+/// so analyzers that depend on results of others go after them.
+#[derive(Default)]
+pub struct AnalyzerRegistry {
+    analyzers: Vec<Box<dyn Analyzer>>,
+}
+
+impl AnalyzerRegistry {
+    pub fn new() -> AnalyzerRegistry {
+        AnalyzerRegistry::default()
+    }
+
+    /// Registry with every analyzer of the editor.
+    pub fn with_default_analyzers() -> AnalyzerRegistry {
+        let mut registry = AnalyzerRegistry::new();
+        registry.register(Box::new(JsonAnalyzer));
+        registry.register(Box::new(ModinfoAnalyzer));
+        registry.register(Box::new(LintConfigAnalyzer));
+        registry.register(Box::new(SceneAnalyzer));
+        registry.register(Box::new(ConflictAnalyzer));
+        registry.register(Box::new(SpecAnalyzer));
+        registry.register(Box::new(OrphanAnalyzer));
+        registry
+    }
+
+    pub fn register(&mut self, analyzer: Box<dyn Analyzer>) {
+        self.analyzers.push(analyzer);
+    }
+
+    pub fn get_analyzers(&self) -> &Vec<Box<dyn Analyzer>> {
+        &self.analyzers
+    }
+
+    pub fn get_rules(&self) -> Vec<&'static Rule> {
+        self.analyzers
+            .iter()
+            .flat_map(|analyzer| analyzer.rules().iter())
+            .collect()
+    }
+
+    pub fn find_rule(&self, rule_id: &str) -> Option<&'static Rule> {
+        self.get_rules().into_iter().find(|rule| rule.id == rule_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[test]
+    fn rule_ids_of_default_analyzers_are_unique() {
+        let rules = AnalyzerRegistry::with_default_analyzers().get_rules();
+        let rule_ids: HashSet<&str> = rules.iter().map(|rule| rule.id).collect();
+
+        assert_eq!(rule_ids.len(), rules.len());
+    }
+
+    #[test]
+    fn rules_are_found_by_id() {
+        let registry = AnalyzerRegistry::with_default_analyzers();
+
+        assert_eq!(registry.find_rule("json-parse-error").unwrap().id, "json-parse-error");
+        assert!(registry.find_rule("no-such-rule").is_none());
+        assert!(AnalyzerRegistry::new().get_rules().is_empty());
+    }
+}
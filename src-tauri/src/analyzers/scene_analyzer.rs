@@ -0,0 +1,212 @@
+use std::collections::HashSet;
+
+use serde_json::Value;
+
+use crate::{directory_image::DirectoryImage, project::repos::repository_tree::RelativePath};
+
+use super::{
+    analyzer::{AnalysisContext, Analyzer, Category, FileAnalysisResult, Hint, Rule},
+    json_hints::JsonHintFactory,
+    json_reader::read_json_file,
+    json_source_map::join_pointer,
+    modinfo_analyzer::MODINFO_RELATIVE_PATH,
+};
+
+pub const SCENE_FILE_NOT_FOUND_RULE: Rule = Rule {
+    id: "scene-file-not-found",
+    description: "File listed in modinfo.json scenes does not exist, the game silently skips it",
+    default_category: Category::Error,
+};
+
+pub const SCENE_INVALID_URL_RULE: Rule = Rule {
+    id: "scene-invalid-url",
+    description: "File listed in modinfo.json scenes must be a coui:// url",
+    default_category: Category::Warning,
+};
+
+pub const UNKNOWN_SCENE_RULE: Rule = Rule {
+    id: "unknown-scene",
+    description: "Scene listed in modinfo.json scenes does not exist in the base game",
+    default_category: Category::Warning,
+};
+
+const SCENE_RULES: [Rule; 3] = [SCENE_FILE_NOT_FOUND_RULE, SCENE_INVALID_URL_RULE, UNKNOWN_SCENE_RULE];
+
+pub const SCENES_PROPERTY: &str = "scenes";
+
+pub const COUI_SCHEME: &str = "coui://";
+
+/// Scenes the game provides without a page of their own.
+const SPECIAL_SCENES: [&str; 1] = ["global_mod_list"];
+
+/// Folder with base game scene pages. Every scene is `<folder>/<scene>.html` somewhere below it.
+const SCENES_FOLDER: &str = "/ui/main/";
+
+/// Checks files and scene names in modinfo.json `scenes`.
+pub struct SceneAnalyzer;
+
+impl Analyzer for SceneAnalyzer {
+    fn name(&self) -> &'static str {
+        "scene analyzer"
+    }
+
+    fn rules(&self) -> &'static [Rule] {
+        &SCENE_RULES
+    }
+
+    fn analyze(&self, context: &AnalysisContext) -> Vec<FileAnalysisResult> {
+        let scenes = get_image_scenes(&context.editor_runtime_data.directory_images);
+        let mut results: Vec<FileAnalysisResult> = Vec::new();
+
+        for repository_tree in &context.editor_runtime_data.repository_trees {
+            let entry = repository_tree.find_entry(&RelativePath::new(MODINFO_RELATIVE_PATH.to_string()));
+            if entry.is_none() {
+                continue;
+            }
+            let entry = entry.unwrap();
+
+            let json_file = match read_json_file(&entry.path) {
+                Ok(json_file) => json_file,
+                Err(_) => continue,
+            };
+            // wrong types are reported by the modinfo analyzer
+            let modinfo_scenes = match json_file.value.get(SCENES_PROPERTY).and_then(|value| value.as_object()) {
+                Some(modinfo_scenes) => modinfo_scenes,
+                None => continue,
+            };
+
+            let hint_factory = JsonHintFactory::new(&json_file.content);
+            let scenes_pointer = join_pointer("", SCENES_PROPERTY);
+            let mut messages: Vec<Hint> = Vec::new();
+
+            for (scene, urls) in modinfo_scenes {
+                let scene_pointer = join_pointer(&scenes_pointer, scene);
+
+                // without images there is nothing to compare scene names with
+                if !scenes.is_empty() && !scenes.contains(scene) && !SPECIAL_SCENES.contains(&scene.as_str()) {
+                    messages.push(hint_factory.create_hint(
+                        &UNKNOWN_SCENE_RULE,
+                        scene,
+                        &scene_pointer,
+                        scene,
+                        &format!("Base game has no scene \"{}\"", scene),
+                        None,
+                    ));
+                }
+
+                let urls = match urls.as_array() {
+                    Some(urls) => urls,
+                    None => continue,
+                };
+                for (index, url) in urls.iter().enumerate() {
+                    let url = match url {
+                        Value::String(url) => url,
+                        _ => continue,
+                    };
+                    let pointer = join_pointer(&scene_pointer, &index.to_string());
+
+                    let relative_path = get_coui_relative_path(url);
+                    if relative_path.is_none() {
+                        messages.push(hint_factory.create_hint(
+                            &SCENE_INVALID_URL_RULE,
+                            scene,
+                            &pointer,
+                            url,
+                            &format!("Scene file must start with {}", COUI_SCHEME),
+                            None,
+                        ));
+                        continue;
+                    }
+
+                    let relative_path = relative_path.unwrap();
+                    if !context.virtual_file_system.contains_file(&relative_path) {
+                        messages.push(hint_factory.create_hint(
+                            &SCENE_FILE_NOT_FOUND_RULE,
+                            scene,
+                            &pointer,
+                            url,
+                            &format!("Scene file not found: {}", relative_path.value),
+                            None,
+                        ));
+                    }
+                }
+            }
+
+            if !messages.is_empty() {
+                results.push(FileAnalysisResult {
+                    file_path: entry.path.clone(),
+                    repository_info: repository_tree.repository_info.clone(),
+                    messages,
+                    suppressed_messages: Vec::new(),
+                    baselined_messages: Vec::new(),
+                });
+            }
+        }
+
+        results
+    }
+}
+
+/// Maps `coui://ui/mods/mod/file.js` to `/ui/mods/mod/file.js`.
+/// `None` if `url` is not a coui url.
+pub fn get_coui_relative_path(url: &str) -> Option<RelativePath> {
+    let path = url.strip_prefix(COUI_SCHEME)?;
+    // query and fragment are not part of the file path
+    let path = path.split(['?', '#']).next().unwrap_or_default();
+    Some(RelativePath::new(path.to_string()))
+}
+
+/// Scene names of the base game: names of all pages under `ui/main`, e.g. `live_game`
+/// for `ui/main/game/live_game/live_game.html`. Pages outside their own folder are scenes too.
+pub fn get_image_scenes(directory_images: &[DirectoryImage]) -> HashSet<String> {
+    let mut scenes: HashSet<String> = HashSet::new();
+
+    for image in directory_images {
+        for file in &image.files {
+            let relative_path = RelativePath::new(file.clone());
+            if !relative_path.value.starts_with(SCENES_FOLDER) {
+                continue;
+            }
+
+            let file_name = relative_path.value.rsplit('/').next().unwrap_or_default();
+            if let Some(scene) = file_name.strip_suffix(".html") {
+                scenes.insert(scene.to_string());
+            }
+        }
+    }
+
+    scenes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_page_under_main_is_a_scene() {
+        let image = DirectoryImage::new(
+            String::from("pa"),
+            vec![
+                String::from("ui/main/game/live_game/live_game.html"),
+                String::from("/ui/main/game/live_game/live_game_options_bar.html"),
+                String::from("/ui/main/shared/js/common.js"),
+                String::from("/ui/mods/other/page.html"),
+            ],
+            None,
+        );
+
+        let scenes = get_image_scenes(&[image]);
+        let mut scenes: Vec<&str> = scenes.iter().map(|scene| scene.as_str()).collect();
+        scenes.sort();
+        assert_eq!(scenes, vec!["live_game", "live_game_options_bar"]);
+    }
+
+    #[test]
+    fn coui_url_maps_to_relative_path() {
+        assert_eq!(
+            get_coui_relative_path("coui://ui/mods/mod/file.js?v=2#top").unwrap().value,
+            "/ui/mods/mod/file.js"
+        );
+        assert!(get_coui_relative_path("/ui/mods/mod/file.js").is_none());
+    }
+}
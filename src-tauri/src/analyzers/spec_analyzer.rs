@@ -0,0 +1,143 @@
+use crate::{
+    project::repos::repository_tree::get_entry_relative_path,
+    specs::spec_resolver::{get_base_spec, SpecResolveError, SpecResolver, BASE_SPEC_PROPERTY},
+    vfs::virtual_file_system::LayerSource,
+};
+
+use super::{
+    analyzer::{AnalysisContext, Analyzer, Category, FileAnalysisResult, Hint, Rule},
+    json_hints::JsonHintFactory,
+    json_reader::read_json_file,
+    json_source_map::join_pointer,
+};
+
+pub const BASE_SPEC_CYCLE_RULE: Rule = Rule {
+    id: "base-spec-cycle",
+    description: "base_spec chain returns to a spec it already contains",
+    default_category: Category::Error,
+};
+
+pub const BASE_SPEC_BROKEN_CHAIN_RULE: Rule = Rule {
+    id: "base-spec-broken-chain",
+    description: "One of the inherited specs references a base_spec that can't be loaded",
+    default_category: Category::Warning,
+};
+
+const SPEC_RULES: [Rule; 2] = [BASE_SPEC_CYCLE_RULE, BASE_SPEC_BROKEN_CHAIN_RULE];
+
+/// Follows `base_spec` chains of repository specs.
+/// A missing direct `base_spec` is reported by the json analyzer as a missing file,
+/// this analyzer reports problems further down the chain.
+pub struct SpecAnalyzer;
+
+impl Analyzer for SpecAnalyzer {
+    fn name(&self) -> &'static str {
+        "spec analyzer"
+    }
+
+    fn rules(&self) -> &'static [Rule] {
+        &SPEC_RULES
+    }
+
+    fn supports_scope(&self) -> bool {
+        true
+    }
+
+    fn analyze(&self, context: &AnalysisContext) -> Vec<FileAnalysisResult> {
+        let virtual_file_system = &context.virtual_file_system;
+        let mut spec_resolver = SpecResolver::new(virtual_file_system);
+        let mut results: Vec<FileAnalysisResult> = Vec::new();
+
+        for repository_tree in &context.editor_runtime_data.repository_trees {
+            for entry in &repository_tree.entries {
+                if entry.is_directory || !entry.path.ends_with(".json") || !context.is_in_scope(&entry.path) {
+                    continue;
+                }
+
+                // shadowed specs are never loaded, so their chains don't matter
+                let relative_path = get_entry_relative_path(repository_tree, entry);
+                let is_provider = match virtual_file_system.get_provider(&relative_path).map(|layer| &layer.source) {
+                    Some(LayerSource::Repository { repository_info }) => {
+                        repository_info.folder_path == repository_tree.repository_info.folder_path
+                    }
+                    _ => false,
+                };
+                if !is_provider {
+                    continue;
+                }
+
+                let json_file = match read_json_file(&entry.path) {
+                    Ok(json_file) => json_file,
+                    Err(_) => continue,
+                };
+                let base_spec = match get_base_spec(&json_file.value) {
+                    Some(base_spec) => base_spec,
+                    None => continue,
+                };
+
+                let resolved_spec = spec_resolver.resolve(&relative_path);
+                let hint_factory = JsonHintFactory::new(&json_file.content);
+                let pointer = join_pointer("", BASE_SPEC_PROPERTY);
+
+                let mut messages: Vec<Hint> = Vec::new();
+                for error in &resolved_spec.errors {
+                    let (rule, message) = match error {
+                        SpecResolveError::Cycle { chain } => (
+                            &BASE_SPEC_CYCLE_RULE,
+                            format!("base_spec chain forms a cycle: {}", chain.join(" -> ")),
+                        ),
+                        SpecResolveError::Missing {
+                            relative_path: missing_path,
+                            referenced_from,
+                        } => {
+                            if referenced_from.as_ref() == Some(&relative_path.value) {
+                                continue;
+                            }
+                            (
+                                &BASE_SPEC_BROKEN_CHAIN_RULE,
+                                format!(
+                                    "base_spec chain is broken: {} referenced from {} does not exist",
+                                    missing_path,
+                                    referenced_from.clone().unwrap_or_default()
+                                ),
+                            )
+                        }
+                        SpecResolveError::Unreadable {
+                            relative_path: unreadable_path,
+                            message,
+                        } => {
+                            if *unreadable_path == relative_path.value {
+                                continue;
+                            }
+                            (
+                                &BASE_SPEC_BROKEN_CHAIN_RULE,
+                                format!("base_spec chain is broken: {} can't be loaded. {}", unreadable_path, message),
+                            )
+                        }
+                    };
+
+                    messages.push(hint_factory.create_hint(
+                        rule,
+                        BASE_SPEC_PROPERTY,
+                        &pointer,
+                        &base_spec,
+                        &message,
+                        None,
+                    ));
+                }
+
+                if !messages.is_empty() {
+                    results.push(FileAnalysisResult {
+                        file_path: entry.path.clone(),
+                        repository_info: repository_tree.repository_info.clone(),
+                        messages,
+                        suppressed_messages: Vec::new(),
+                        baselined_messages: Vec::new(),
+                    });
+                }
+            }
+        }
+
+        results
+    }
+}
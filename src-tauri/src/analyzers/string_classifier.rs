@@ -0,0 +1,200 @@
+use serde::{Deserialize, Serialize};
+
+use crate::project::repos::repository_tree::RelativePath;
+
+/// What a json string value means to the game.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum StringKind {
+    /// Path of a file in the virtual file system, e.g. `/pa/units/land/tank/tank.json`.
+    FilePath,
+    /// `coui://ui/mods/mod/file.js`, `http://...` and other urls.
+    Url,
+    /// FMOD event name, e.g. `/SE/Weapons/Tank/tank_fire`. Looks like a path, but is not a file.
+    AudioEvent,
+    /// Localized text, e.g. `!LOC:Shoots/kills things`.
+    LocalizationKey,
+    /// Unit type filter, e.g. `Mobile & (Land | Naval)`.
+    UnitTypeExpression,
+    Text,
+}
+
+impl StringKind {
+    /// Only path-like values are checked by path rules.
+    pub fn is_path(&self) -> bool {
+        *self == StringKind::FilePath
+    }
+}
+
+/// Kind of the json file a value comes from.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FileKind {
+    /// `/modinfo.json` of a mod.
+    Modinfo,
+    /// `/pa/units/unit_list.json`, the list of every unit spec.
+    UnitList,
+    /// Unit, weapon, ammo, effect and other specs under `/pa/`.
+    Spec,
+    /// Json files of the user interface under `/ui/`.
+    Ui,
+    Other,
+}
+
+impl FileKind {
+    pub fn from_relative_path(relative_path: &RelativePath) -> FileKind {
+        let path = relative_path.value.as_str();
+        if path == "/modinfo.json" {
+            FileKind::Modinfo
+        } else if path == "/pa/units/unit_list.json" {
+            FileKind::UnitList
+        } else if path.starts_with("/pa/") {
+            FileKind::Spec
+        } else if path.starts_with("/ui/") {
+            FileKind::Ui
+        } else {
+            FileKind::Other
+        }
+    }
+}
+
+const LOCALIZATION_PREFIX: &str = "!LOC";
+
+const UNIT_TYPE_PREFIX: &str = "UNITTYPE_";
+
+/// Root folders of FMOD event names.
+const AUDIO_EVENT_ROOTS: [&str; 4] = ["/SE/", "/VO/", "/Music/", "/Audio/"];
+
+/// Property names whose values are always audio events.
+const AUDIO_PROPERTIES: [&str; 6] = ["audio", "audio_cue", "audio_loop", "cue", "sound", "music"];
+
+/// Property names whose values are always unit type filters.
+const UNIT_TYPE_PROPERTIES: [&str; 7] = [
+    "buildable_types",
+    "assist_buildable_units",
+    "target_priorities",
+    "unit_types",
+    "spawn_unit_types",
+    "unit_type",
+    "attackable_types",
+];
+
+/// modinfo.json properties that hold paths. Other modinfo strings are metadata.
+const MODINFO_PATH_PROPERTIES: [&str; 1] = ["icon"];
+
+/// Decides what `value` at `pointer` means, so that only paths reach path rules.
+pub fn classify_string(file_kind: FileKind, pointer: &str, value: &str) -> StringKind {
+    let property_name = get_property_name(pointer);
+
+    if value.starts_with(LOCALIZATION_PREFIX) {
+        return StringKind::LocalizationKey;
+    }
+    if value.contains("://") {
+        return StringKind::Url;
+    }
+    if is_unit_type_expression(&property_name, value) {
+        return StringKind::UnitTypeExpression;
+    }
+    if is_audio_event(&property_name, value) {
+        return StringKind::AudioEvent;
+    }
+    if file_kind == FileKind::Modinfo && !MODINFO_PATH_PROPERTIES.contains(&property_name.as_str()) {
+        return StringKind::Text;
+    }
+    if is_path_like(value) {
+        return StringKind::FilePath;
+    }
+
+    StringKind::Text
+}
+
+/// Name of the property that holds the value. Array indices are skipped,
+/// so items of `"target_priorities": [...]` are named `target_priorities`.
+fn get_property_name(pointer: &str) -> String {
+    pointer
+        .rsplit('/')
+        .find(|token| token.is_empty() || !token.chars().all(|c| c.is_ascii_digit()))
+        .unwrap_or_default()
+        .replace("~1", "/")
+        .replace("~0", "~")
+}
+
+fn is_unit_type_expression(property_name: &str, value: &str) -> bool {
+    if UNIT_TYPE_PROPERTIES.contains(&property_name) || value.starts_with(UNIT_TYPE_PREFIX) {
+        return true;
+    }
+
+    // operators of unit type filters never appear in paths
+    (value.contains('&') || value.contains('|'))
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || " _&|()-!".contains(c))
+}
+
+fn is_audio_event(property_name: &str, value: &str) -> bool {
+    if has_extension(value) {
+        return false;
+    }
+    AUDIO_PROPERTIES.contains(&property_name) || AUDIO_EVENT_ROOTS.iter().any(|root| value.starts_with(root))
+}
+
+/// Paths have slashes and no whitespace, or end with a file extension.
+fn is_path_like(value: &str) -> bool {
+    let has_slash = value.contains('/') || value.contains('\\');
+    has_slash && (!value.chars().any(char::is_whitespace) || has_extension(value))
+}
+
+fn has_extension(value: &str) -> bool {
+    let file_name = value.rsplit(['/', '\\']).next().unwrap_or_default();
+    match file_name.rsplit_once('.') {
+        Some((stem, extension)) => {
+            !stem.is_empty() && !extension.is_empty() && extension.chars().all(|c| c.is_ascii_alphanumeric())
+        }
+        None => false,
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn classify_spec_string(pointer: &str, value: &str) -> StringKind {
+        classify_string(FileKind::Spec, pointer, value)
+    }
+
+    #[test]
+    fn coui_and_other_urls_are_not_paths() {
+        assert_eq!(classify_spec_string("/icon", "coui://ui/mods/mod/icon.png"), StringKind::Url);
+        assert_eq!(classify_spec_string("/url", "https://example.com/mod.zip"), StringKind::Url);
+        assert!(!StringKind::Url.is_path());
+    }
+
+    #[test]
+    fn paths_are_file_paths() {
+        assert_eq!(classify_spec_string("/base_spec", "/pa/units/land/base_vehicle/base_vehicle.json"), StringKind::FilePath);
+        assert_eq!(classify_spec_string("/tools/0/spec_id", "pa\\tools\\gun.json"), StringKind::FilePath);
+        assert_eq!(classify_spec_string("/model/filename", "/pa/units/land/tank/my tank.papa"), StringKind::FilePath);
+        assert!(StringKind::FilePath.is_path());
+    }
+
+    #[test]
+    fn other_strings_are_not_paths() {
+        assert_eq!(classify_spec_string("/display_name", "!LOC:Tank"), StringKind::LocalizationKey);
+        assert_eq!(classify_spec_string("/events/fired/audio_cue", "/SE/Weapons/Tank/tank_fire"), StringKind::AudioEvent);
+        assert_eq!(classify_spec_string("/buildable_types", "Mobile & (Land | Naval)"), StringKind::UnitTypeExpression);
+        assert_eq!(classify_spec_string("/target_priorities/0", "Mobile - Air"), StringKind::UnitTypeExpression);
+        assert_eq!(classify_spec_string("/description", "Shoots and/or kills things"), StringKind::Text);
+        assert_eq!(classify_string(FileKind::Modinfo, "/forum", "forums/mod-thread"), StringKind::Text);
+        assert_eq!(classify_string(FileKind::Modinfo, "/icon", "/ui/mods/mod/icon.png"), StringKind::FilePath);
+    }
+
+    #[test]
+    fn file_kind_comes_from_path() {
+        let get_file_kind = |path: &str| FileKind::from_relative_path(&RelativePath::new(path.to_string()));
+        assert_eq!(get_file_kind("/modinfo.json"), FileKind::Modinfo);
+        assert_eq!(get_file_kind("/pa/units/unit_list.json"), FileKind::UnitList);
+        assert_eq!(get_file_kind("/pa/units/land/tank/tank.json"), FileKind::Spec);
+        assert_eq!(get_file_kind("/ui/mods/mod/settings.json"), FileKind::Ui);
+    }
+}
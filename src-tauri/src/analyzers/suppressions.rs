@@ -0,0 +1,311 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use glob::{MatchOptions, Pattern};
+use serde_json::Value;
+
+use crate::project::repos::repository_tree::RelativePath;
+
+use super::{
+    analyzer::{Hint, Rule},
+    json_reader::read_json_file,
+};
+
+/// Ignore file of a repository, next to modinfo.json.
+pub const IGNORE_FILE_RELATIVE_PATH: &str = "/.pa-linter-ignore";
+
+/// Top level key of a json file with hints suppressed in that file:
+///
+/// ```json
+/// {
+///     "pa-linter-ignore": { "/base_spec": ["file-not-found"] }
+/// }
+/// ```
+///
+/// An array of rule ids instead of the object suppresses the rules in the whole file.
+pub const SUPPRESSIONS_KEY: &str = "pa-linter-ignore";
+
+/// Matches every rule, in the ignore file and in the sidecar key.
+const ANY_RULE: &str = "*";
+
+const PATH_MATCH_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+/// Line of the ignore file: a path glob and rule ids, every rule if there are none.
+#[derive(Debug, Clone)]
+pub struct IgnoreEntry {
+    pub pattern: Pattern,
+    pub rule_ids: Vec<String>,
+}
+
+/// Hints suppressed by the ignore file of a repository:
+///
+/// ```text
+/// # provided by the required base mod
+/// /pa/units/land/tank/tank.json file-not-found
+/// /pa/units/test/**
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct IgnoreFile {
+    pub entries: Vec<IgnoreEntry>,
+}
+
+/// Invalid line of the ignore file. Line and column are 1-based.
+#[derive(Debug, Clone)]
+pub struct IgnoreFileError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl IgnoreFile {
+    /// Reads the valid lines of `content`, the rest is returned as errors.
+    pub fn parse(content: &str, known_rules: &[&'static Rule]) -> (IgnoreFile, Vec<IgnoreFileError>) {
+        let mut ignore_file = IgnoreFile::default();
+        let mut errors: Vec<IgnoreFileError> = Vec::new();
+
+        for (line_index, line) in content.lines().enumerate() {
+            let mut tokens = get_tokens(line).into_iter();
+            let (column, path) = match tokens.next() {
+                Some((_, token)) if token.starts_with('#') => continue,
+                Some(token) => token,
+                None => continue,
+            };
+
+            let pattern = match Pattern::new(&RelativePath::new(path.to_string()).value) {
+                Ok(pattern) => pattern,
+                Err(error) => {
+                    errors.push(IgnoreFileError {
+                        line: line_index + 1,
+                        column,
+                        message: format!("Invalid glob pattern: {}", error.msg),
+                    });
+                    continue;
+                }
+            };
+
+            let mut rule_ids: Vec<String> = Vec::new();
+            let mut has_unknown_rules = false;
+            for (column, rule_id) in tokens {
+                if rule_id != ANY_RULE && !known_rules.iter().any(|rule| rule.id == rule_id) {
+                    errors.push(IgnoreFileError {
+                        line: line_index + 1,
+                        column,
+                        message: format!("Unknown rule \"{}\"", rule_id),
+                    });
+                    has_unknown_rules = true;
+                    continue;
+                }
+                rule_ids.push(rule_id.to_string());
+            }
+            // a line without rules suppresses every rule, a line with only unknown rules suppresses nothing
+            if has_unknown_rules && rule_ids.is_empty() {
+                continue;
+            }
+            ignore_file.entries.push(IgnoreEntry { pattern, rule_ids });
+        }
+
+        (ignore_file, errors)
+    }
+
+    pub fn is_suppressed(&self, relative_path: &RelativePath, rule_id: &str) -> bool {
+        self.entries.iter().any(|entry| {
+            entry.pattern.matches_with(&relative_path.value, PATH_MATCH_OPTIONS)
+                && (entry.rule_ids.is_empty() || contains_rule(&entry.rule_ids, rule_id))
+        })
+    }
+}
+
+/// Hints suppressed by the sidecar key of a json file.
+#[derive(Debug, Clone, Default)]
+pub struct JsonSuppressions {
+    /// Rule ids with the JSON Pointer of the suppressed value, "" for the whole file.
+    pub entries: Vec<(String, Vec<String>)>,
+}
+
+impl JsonSuppressions {
+    /// Suppressions of the json `value`. Values of the key in any other shape suppress nothing.
+    pub fn from_value(value: &Value) -> JsonSuppressions {
+        let mut suppressions = JsonSuppressions::default();
+        match value.get(SUPPRESSIONS_KEY) {
+            Some(Value::Array(rule_ids)) => suppressions.entries.push((String::new(), get_rule_ids(rule_ids))),
+            Some(Value::Object(pointers)) => {
+                for (pointer, rule_ids) in pointers {
+                    if let Value::Array(rule_ids) = rule_ids {
+                        suppressions.entries.push((pointer.clone(), get_rule_ids(rule_ids)));
+                    }
+                }
+            }
+            _ => {}
+        }
+        suppressions
+    }
+
+    /// Whether the hint is about a suppressed value or anything inside of it.
+    pub fn is_suppressed(&self, hint: &Hint) -> bool {
+        let hint_pointer = match hint {
+            Hint::JSON { pointer, .. } => pointer.as_str(),
+            _ => "",
+        };
+        self.entries.iter().any(|(pointer, rule_ids)| {
+            let is_inside = pointer.is_empty()
+                || hint_pointer == pointer
+                || hint_pointer.starts_with(&(pointer.clone() + "/"));
+            is_inside && contains_rule(rule_ids, hint.rule_id())
+        })
+    }
+}
+
+pub fn get_ignore_file_path(folder_path: &str) -> PathBuf {
+    Path::new(folder_path).join(IGNORE_FILE_RELATIVE_PATH.trim_start_matches('/'))
+}
+
+/// Content of the ignore file of the repository at `folder_path`, `None` if there is none.
+pub fn read_ignore_file(folder_path: &str) -> Option<String> {
+    fs::read_to_string(get_ignore_file_path(folder_path)).ok()
+}
+
+/// Ignore file of the repository at `folder_path`, an empty one if there is none.
+pub fn load_ignore_file(folder_path: &str, known_rules: &[&'static Rule]) -> IgnoreFile {
+    match read_ignore_file(folder_path) {
+        Some(content) => IgnoreFile::parse(&content, known_rules).0,
+        None => IgnoreFile::default(),
+    }
+}
+
+/// Moves hints suppressed by `ignore_file` or by the sidecar key of the file to `suppressed_messages`.
+/// `json_suppressions` are the suppressions of the file if it was already read,
+/// otherwise json files are read only when there is something to suppress.
+pub fn apply_suppressions(
+    ignore_file: &IgnoreFile,
+    json_suppressions: Option<&JsonSuppressions>,
+    file_path: &str,
+    relative_path: &RelativePath,
+    messages: &mut Vec<Hint>,
+    suppressed_messages: &mut Vec<Hint>,
+) {
+    if messages.is_empty() {
+        return;
+    }
+
+    // files outside the scope of an incremental run were not read by the json analyzer
+    let read_suppressions;
+    let json_suppressions = match json_suppressions {
+        Some(json_suppressions) => json_suppressions,
+        None => {
+            read_suppressions = match file_path.ends_with(".json") {
+                true => read_json_file(file_path)
+                    .map(|json_file| JsonSuppressions::from_value(&json_file.value))
+                    .unwrap_or_default(),
+                false => JsonSuppressions::default(),
+            };
+            &read_suppressions
+        }
+    };
+
+    let (suppressed, kept): (Vec<Hint>, Vec<Hint>) = messages.drain(..).partition(|hint| {
+        ignore_file.is_suppressed(relative_path, hint.rule_id()) || json_suppressions.is_suppressed(hint)
+    });
+    *messages = kept;
+    suppressed_messages.extend(suppressed);
+}
+
+/// Whitespace separated tokens of `line` with their 1-based columns.
+fn get_tokens(line: &str) -> Vec<(usize, &str)> {
+    let mut tokens: Vec<(usize, &str)> = Vec::new();
+    let mut token_start: Option<usize> = None;
+    for (index, c) in line.char_indices().chain([(line.len(), ' ')]) {
+        match (c.is_whitespace(), token_start) {
+            (true, Some(start)) => {
+                tokens.push((line[..start].chars().count() + 1, &line[start..index]));
+                token_start = None;
+            }
+            (false, None) => token_start = Some(index),
+            _ => {}
+        }
+    }
+    tokens
+}
+
+fn get_rule_ids(values: &[Value]) -> Vec<String> {
+    values
+        .iter()
+        .filter_map(|value| value.as_str().map(|rule_id| rule_id.to_string()))
+        .collect()
+}
+
+fn contains_rule(rule_ids: &[String], rule_id: &str) -> bool {
+    rule_ids.iter().any(|id| id == rule_id || id == ANY_RULE)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analyzers::analyzer::Category;
+
+    use super::*;
+
+    const KNOWN_RULE: Rule = Rule {
+        id: "file-not-found",
+        description: "",
+        default_category: Category::Warning,
+    };
+
+    fn parse(content: &str) -> (IgnoreFile, Vec<IgnoreFileError>) {
+        IgnoreFile::parse(content, &[&KNOWN_RULE])
+    }
+
+    fn is_suppressed(ignore_file: &IgnoreFile, relative_path: &str, rule_id: &str) -> bool {
+        ignore_file.is_suppressed(&RelativePath::new(relative_path.to_string()), rule_id)
+    }
+
+    #[test]
+    fn lines_suppress_their_rules() {
+        let (ignore_file, errors) = parse("# comment\n\n/pa/units/*.json file-not-found\npa/test/**\n");
+        assert!(errors.is_empty());
+        assert!(is_suppressed(&ignore_file, "/pa/units/tank.json", "file-not-found"));
+        assert!(!is_suppressed(&ignore_file, "/pa/units/tank.json", "orphaned-file"));
+        assert!(!is_suppressed(&ignore_file, "/pa/units/land/tank.json", "file-not-found"));
+        assert!(is_suppressed(&ignore_file, "/pa/test/a/b.json", "orphaned-file"));
+    }
+
+    #[test]
+    fn line_with_only_unknown_rules_suppresses_nothing() {
+        let (ignore_file, errors) = parse("/pa/** fle-not-found\n/ui/** fle-not-found file-not-found\n");
+        assert_eq!(errors.len(), 2);
+        assert_eq!((errors[0].line, errors[0].column), (1, 8));
+        assert!(!is_suppressed(&ignore_file, "/pa/tank.json", "file-not-found"));
+        assert!(!is_suppressed(&ignore_file, "/pa/tank.json", "orphaned-file"));
+        assert!(is_suppressed(&ignore_file, "/ui/main.json", "file-not-found"));
+    }
+
+    #[test]
+    fn invalid_pattern_is_reported() {
+        let (ignore_file, errors) = parse("  /pa/[units file-not-found");
+        assert!(ignore_file.entries.is_empty());
+        assert_eq!((errors[0].line, errors[0].column), (1, 3));
+    }
+
+    #[test]
+    fn sidecar_key_suppresses_values_inside_pointer() {
+        let value = serde_json::json!({ SUPPRESSIONS_KEY: { "/tools": ["file-not-found"] } });
+        let suppressions = JsonSuppressions::from_value(&value);
+        let create_hint = |pointer: &str| Hint::JSON {
+            category: Category::Warning,
+            rule_id: String::from("file-not-found"),
+            property_name: String::new(),
+            property_value: String::new(),
+            pointer: pointer.to_string(),
+            span: None,
+            message: String::new(),
+            fix: None,
+            suggestions: Vec::new(),
+        };
+        assert!(suppressions.is_suppressed(&create_hint("/tools/0/spec_id")));
+        assert!(!suppressions.is_suppressed(&create_hint("/tools_extra")));
+    }
+}
@@ -0,0 +1,11 @@
+use app::headless::runner::{run, RunnerOptions, EXIT_CODE_FAILURE, USAGE};
+
+fn main() {
+    let options = RunnerOptions::from_args(std::env::args().skip(1));
+    if options.is_err() {
+        eprintln!("{}\n\n{}", options.err().unwrap(), USAGE);
+        std::process::exit(EXIT_CODE_FAILURE);
+    }
+
+    std::process::exit(run(&options.unwrap()));
+}
@@ -1,3 +1,5 @@
+use std::{collections::HashSet, path::Path, sync::OnceLock};
+
 use serde::{Deserialize, Serialize};
 use walkdir::WalkDir;
 
@@ -7,13 +9,41 @@ use crate::{editor::{editor::*, editor_runtime::EditorRuntimeData}, project::rep
 pub struct DirectoryImage {
     pub name: String,
     pub files: Vec<String>,
+    /// Folder the image was taken from. Images taken before it was stored don't have it.
+    #[serde(default)]
+    pub folder_path: Option<String>,
+    /// Position in the game mount order, images are mounted by ascending index.
+    /// Given on registration, so an image registered later is mounted on top of earlier ones.
+    #[serde(default)]
+    pub mount_index: u32,
+
+    /// Normalized `files`, built on the first lookup.
+    #[serde(skip)]
+    files_index: OnceLock<HashSet<RelativePath>>,
 }
 
 const DIRECTORY_IMAGES_FOLDER_NAME: &str = "images";
 
 impl DirectoryImage {
-    pub fn new(name: String, files: Vec<String>) -> Self {
-        Self { name, files }
+    pub fn new(name: String, files: Vec<String>, folder_path: Option<String>) -> Self {
+        Self {
+            name,
+            files,
+            folder_path,
+            mount_index: 0,
+            files_index: OnceLock::new(),
+        }
+    }
+
+    pub fn contains_file(&self, file_path: &RelativePath) -> bool {
+        self.files_index
+            .get_or_init(|| {
+                self.files
+                    .iter()
+                    .map(|file| RelativePath::new(file.clone()))
+                    .collect()
+            })
+            .contains(file_path)
     }
 }
 
@@ -37,10 +67,15 @@ pub fn take_directory_image(path: &str) -> DirectoryImage {
         }
     }
 
-    DirectoryImage::new(name, files)
+    DirectoryImage::new(name, files, path.to_str().map(|path| path.to_string()))
 }
 
+/// Saves `image` with the mount index of the image with the same name,
+/// a new image gets the index after all registered images.
 pub fn save_directory_image(image: &DirectoryImage, editor_env: &EditorEnvironment) {
+    let mut image = image.clone();
+    image.mount_index = get_mount_index(&get_directory_images(editor_env), &image.name);
+
     let directory_images_folder = editor_env
         .get_or_create_folder(DIRECTORY_IMAGES_FOLDER_NAME)
         .unwrap();
@@ -49,6 +84,7 @@ pub fn save_directory_image(image: &DirectoryImage, editor_env: &EditorEnvironme
     std::fs::write(&file_path, serialized).unwrap();
 }
 
+/// Registered images in the game mount order.
 pub fn get_directory_images(editor_env: &EditorEnvironment) -> Vec<DirectoryImage> {
     let directory_images_folder = editor_env
         .get_or_create_folder(DIRECTORY_IMAGES_FOLDER_NAME)
@@ -65,5 +101,76 @@ pub fn get_directory_images(editor_env: &EditorEnvironment) -> Vec<DirectoryImag
             }
         }
     }
+    // images registered before mount indices were stored all have index 0,
+    // the name only keeps their order stable
+    directory_images.sort_by(|a, b| (a.mount_index, &a.name).cmp(&(b.mount_index, &b.name)));
+    directory_images
+}
+
+fn get_mount_index(directory_images: &[DirectoryImage], name: &str) -> u32 {
+    if let Some(image) = directory_images.iter().find(|image| image.name == name) {
+        return image.mount_index;
+    }
     directory_images
-}
\ No newline at end of file
+        .iter()
+        .map(|image| image.mount_index + 1)
+        .max()
+        .unwrap_or(0)
+}
+
+pub fn load_directory_image(file_path: &Path) -> Result<DirectoryImage, String> {
+    let file_content = std::fs::read_to_string(file_path);
+    if file_content.is_err() {
+        return Err(format!("Could not read directory image {}", file_path.display()));
+    }
+
+    let directory_image = serde_json::from_str(&file_content.unwrap());
+    if directory_image.is_err() {
+        return Err(format!(
+            "{} is not a directory image: {}",
+            file_path.display(),
+            directory_image.err().unwrap()
+        ));
+    }
+
+    Ok(directory_image.unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn files_are_found_by_normalized_path() {
+        let image = DirectoryImage::new(
+            String::from("pa"),
+            vec![String::from("pa\\units\\tank.json"), String::from("ui/main/game.html")],
+            None,
+        );
+
+        assert!(image.contains_file(&RelativePath::new(String::from("/pa/units/tank.json"))));
+        assert!(image.contains_file(&RelativePath::new(String::from("ui/main/game.html"))));
+        assert!(!image.contains_file(&RelativePath::new(String::from("/pa/units/bot.json"))));
+    }
+
+    #[test]
+    fn new_images_are_mounted_after_registered_ones() {
+        let mut base_image = DirectoryImage::new(String::from("pa"), Vec::new(), None);
+        base_image.mount_index = 0;
+        let mut expansion_image = DirectoryImage::new(String::from("pa_ex1"), Vec::new(), None);
+        expansion_image.mount_index = 1;
+        let directory_images = vec![base_image, expansion_image];
+
+        assert_eq!(get_mount_index(&directory_images, "pa_ex1"), 1);
+        assert_eq!(get_mount_index(&directory_images, "balance"), 2);
+        assert_eq!(get_mount_index(&[], "pa"), 0);
+    }
+
+    #[test]
+    fn images_without_folder_path_are_loaded() {
+        let image: DirectoryImage = serde_json::from_str("{\"name\": \"pa\", \"files\": [\"pa/tank.json\"]}").unwrap();
+
+        assert_eq!(image.folder_path, None);
+        assert!(image.contains_file(&RelativePath::new(String::from("/pa/tank.json"))));
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{directory_image::DirectoryImage, project::repos::repository_tree::RepositoryTree, EditorEnvironment};
+use crate::{directory_image::DirectoryImage, editor::editor::EditorEnvironment, project::repos::repository_tree::RepositoryTree};
 
 pub struct EditorRuntimeData{
     pub editor_env: EditorEnvironment,
@@ -0,0 +1,288 @@
+/// Number of unchanged lines around changes in a hunk.
+const CONTEXT_LINES: usize = 3;
+
+/// Values the Myers trace may hold, about 32 MB. The trace grows with the number
+/// of changed lines times the file length, so diffs of large rewritten files
+/// fall back to replacing the whole changed region.
+const MAX_TRACE_CELLS: usize = 4 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DiffOperation {
+    /// Line exists in both texts: (old index, new index).
+    Equal(usize, usize),
+    /// Line exists only in the old text.
+    Delete(usize),
+    /// Line exists only in the new text.
+    Insert(usize),
+}
+
+/// Builds unified diff of two versions of the file at `file_path`.
+/// Returns an empty string if texts have the same lines.
+pub fn unified_diff(file_path: &str, old_text: &str, new_text: &str) -> String {
+    let old_lines: Vec<&str> = old_text.lines().collect();
+    let new_lines: Vec<&str> = new_text.lines().collect();
+    let operations = diff_lines(&old_lines, &new_lines);
+
+    let hunks = group_hunks(&operations);
+    if hunks.is_empty() {
+        return String::new();
+    }
+
+    let mut diff = format!("--- a/{0}\n+++ b/{0}\n", file_path.trim_start_matches('/'));
+    for (start, end) in hunks {
+        diff.push_str(&format_hunk(&operations[start..end], &old_lines, &new_lines));
+    }
+    diff
+}
+
+/// Myers diff of two line lists. Common prefix and suffix are
+/// skipped beforehand, so the search runs only over the changed region.
+fn diff_lines(old_lines: &[&str], new_lines: &[&str]) -> Vec<DiffOperation> {
+    let prefix = old_lines
+        .iter()
+        .zip(new_lines.iter())
+        .take_while(|(old_line, new_line)| old_line == new_line)
+        .count();
+    let suffix = old_lines[prefix..]
+        .iter()
+        .rev()
+        .zip(new_lines[prefix..].iter().rev())
+        .take_while(|(old_line, new_line)| old_line == new_line)
+        .count();
+
+    let old_middle = &old_lines[prefix..old_lines.len() - suffix];
+    let new_middle = &new_lines[prefix..new_lines.len() - suffix];
+
+    let mut operations: Vec<DiffOperation> = (0..prefix)
+        .map(|index| DiffOperation::Equal(index, index))
+        .collect();
+
+    for operation in diff_lines_myers(old_middle, new_middle, MAX_TRACE_CELLS) {
+        operations.push(match operation {
+            DiffOperation::Equal(old_index, new_index) => {
+                DiffOperation::Equal(old_index + prefix, new_index + prefix)
+            }
+            DiffOperation::Delete(old_index) => DiffOperation::Delete(old_index + prefix),
+            DiffOperation::Insert(new_index) => DiffOperation::Insert(new_index + prefix),
+        });
+    }
+
+    for index in 0..suffix {
+        operations.push(DiffOperation::Equal(
+            old_lines.len() - suffix + index,
+            new_lines.len() - suffix + index,
+        ));
+    }
+
+    operations
+}
+
+/// Falls back to `replace_lines` when the trace would hold more than `max_trace_cells` values.
+fn diff_lines_myers(old_lines: &[&str], new_lines: &[&str], max_trace_cells: usize) -> Vec<DiffOperation> {
+    let old_length = old_lines.len() as isize;
+    let new_length = new_lines.len() as isize;
+    let max = old_length + new_length;
+    let offset = max + 1;
+
+    let mut v = vec![0isize; (2 * max + 3) as usize];
+    let mut trace: Vec<Vec<isize>> = Vec::new();
+
+    'search: for d in 0..=max {
+        if (trace.len() + 1) * v.len() > max_trace_cells {
+            return replace_lines(old_lines, new_lines);
+        }
+        trace.push(v.clone());
+        let mut k = -d;
+        while k <= d {
+            let mut x = if k == -d
+                || (k != d && v[(k - 1 + offset) as usize] < v[(k + 1 + offset) as usize])
+            {
+                v[(k + 1 + offset) as usize]
+            } else {
+                v[(k - 1 + offset) as usize] + 1
+            };
+            let mut y = x - k;
+
+            while x < old_length && y < new_length && old_lines[x as usize] == new_lines[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[(k + offset) as usize] = x;
+
+            if x >= old_length && y >= new_length {
+                break 'search;
+            }
+            k += 2;
+        }
+    }
+
+    // walk the trace back from the end to collect operations
+    let mut operations: Vec<DiffOperation> = Vec::new();
+    let mut x = old_length;
+    let mut y = new_length;
+    for d in (0..trace.len() as isize).rev() {
+        let v = &trace[d as usize];
+        let k = x - y;
+        let previous_k = if k == -d
+            || (k != d && v[(k - 1 + offset) as usize] < v[(k + 1 + offset) as usize])
+        {
+            k + 1
+        } else {
+            k - 1
+        };
+        let previous_x = v[(previous_k + offset) as usize];
+        let previous_y = previous_x - previous_k;
+
+        while x > previous_x && y > previous_y {
+            operations.push(DiffOperation::Equal((x - 1) as usize, (y - 1) as usize));
+            x -= 1;
+            y -= 1;
+        }
+
+        if d > 0 {
+            if x == previous_x {
+                operations.push(DiffOperation::Insert((y - 1) as usize));
+            } else {
+                operations.push(DiffOperation::Delete((x - 1) as usize));
+            }
+        }
+
+        x = previous_x;
+        y = previous_y;
+    }
+
+    operations.reverse();
+    operations
+}
+
+/// Deletes every old line and inserts every new line.
+fn replace_lines(old_lines: &[&str], new_lines: &[&str]) -> Vec<DiffOperation> {
+    (0..old_lines.len())
+        .map(DiffOperation::Delete)
+        .chain((0..new_lines.len()).map(DiffOperation::Insert))
+        .collect()
+}
+
+/// Returns ranges of operations that form hunks with context lines.
+fn group_hunks(operations: &[DiffOperation]) -> Vec<(usize, usize)> {
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+
+    for (index, operation) in operations.iter().enumerate() {
+        if matches!(operation, DiffOperation::Equal(..)) {
+            continue;
+        }
+
+        let start = index.saturating_sub(CONTEXT_LINES);
+        let end = (index + 1 + CONTEXT_LINES).min(operations.len());
+        match hunks.last_mut() {
+            Some(last_hunk) if start <= last_hunk.1 => last_hunk.1 = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+
+    hunks
+}
+
+fn format_hunk(operations: &[DiffOperation], old_lines: &[&str], new_lines: &[&str]) -> String {
+    let mut body = String::new();
+    let mut old_start: Option<usize> = None;
+    let mut new_start: Option<usize> = None;
+    let mut old_count = 0;
+    let mut new_count = 0;
+
+    for operation in operations {
+        match *operation {
+            DiffOperation::Equal(old_index, new_index) => {
+                old_start.get_or_insert(old_index);
+                new_start.get_or_insert(new_index);
+                old_count += 1;
+                new_count += 1;
+                body.push_str(&format!(" {}\n", old_lines[old_index]));
+            }
+            DiffOperation::Delete(old_index) => {
+                old_start.get_or_insert(old_index);
+                old_count += 1;
+                body.push_str(&format!("-{}\n", old_lines[old_index]));
+            }
+            DiffOperation::Insert(new_index) => {
+                new_start.get_or_insert(new_index);
+                new_count += 1;
+                body.push_str(&format!("+{}\n", new_lines[new_index]));
+            }
+        }
+    }
+
+    format!(
+        "@@ -{} +{} @@\n{}",
+        format_hunk_range(old_start, old_count),
+        format_hunk_range(new_start, new_count),
+        body
+    )
+}
+
+/// Hunk ranges are 1-based. Empty range points at the line before it.
+fn format_hunk_range(start: Option<usize>, count: usize) -> String {
+    match start {
+        Some(start) if count > 0 => format!("{},{}", start + 1, count),
+        Some(start) => format!("{},0", start),
+        None => String::from("0,0"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_lines_give_empty_diff() {
+        assert_eq!(unified_diff("/a.json", "", ""), "");
+        assert_eq!(unified_diff("/a.json", "{\n}\n", "{\n}"), "");
+    }
+
+    #[test]
+    fn changed_line_has_context() {
+        let old_text = "1\n2\n3\n4\n5\n6\n7\n8\n";
+        let new_text = "1\n2\n3\n4\nfive\n6\n7\n8\n";
+        assert_eq!(
+            unified_diff("/pa/a.json", old_text, new_text),
+            "--- a/pa/a.json\n+++ b/pa/a.json\n@@ -2,7 +2,7 @@\n 2\n 3\n 4\n-5\n+five\n 6\n 7\n 8\n"
+        );
+    }
+
+    #[test]
+    fn insertions_into_empty_text() {
+        assert_eq!(
+            unified_diff("a.json", "", "x\ny\n"),
+            "--- a/a.json\n+++ b/a.json\n@@ -0,0 +1,2 @@\n+x\n+y\n"
+        );
+    }
+
+    #[test]
+    fn distant_changes_get_separate_hunks() {
+        let old_lines: Vec<String> = (0..20).map(|index| index.to_string()).collect();
+        let mut new_lines = old_lines.clone();
+        new_lines[1] = String::from("one");
+        new_lines[18] = String::from("eighteen");
+        let diff = unified_diff("a.json", &old_lines.join("\n"), &new_lines.join("\n"));
+        assert_eq!(diff.matches("@@ -").count(), 2);
+    }
+
+    #[test]
+    fn myers_finds_shortest_edit() {
+        let operations = diff_lines_myers(&["a", "b", "c"], &["a", "c", "d"], MAX_TRACE_CELLS);
+        let edits = operations
+            .iter()
+            .filter(|operation| !matches!(operation, DiffOperation::Equal(..)))
+            .count();
+        assert_eq!(edits, 2);
+    }
+
+    #[test]
+    fn large_trace_falls_back_to_replacement() {
+        let old_lines = ["a", "b", "c"];
+        let new_lines = ["x", "b", "y"];
+        let operations = diff_lines_myers(&old_lines, &new_lines, 1);
+        assert_eq!(operations, replace_lines(&old_lines, &new_lines));
+        assert_eq!(operations.len(), 6);
+    }
+}
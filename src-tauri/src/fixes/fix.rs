@@ -0,0 +1,186 @@
+use std::fs;
+
+use serde::{Deserialize, Serialize};
+
+use crate::analyzers::json_source_map::SourceSpan;
+
+use super::diff::unified_diff;
+
+/// Machine-applicable fix of a hint: replacement of the text at `span`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Fix {
+    pub span: SourceSpan,
+    /// Source text at `span` at the moment of analysis.
+    pub original_text: String,
+    pub replacement_text: String,
+}
+
+impl Fix {
+    /// Fix that replaces the string literal at `span` with `replacement_value`.
+    pub fn replace_string_literal(content: &str, span: &SourceSpan, replacement_value: &str) -> Option<Fix> {
+        let original_text = content.get(span.start.offset..span.end.offset)?;
+        Some(Fix {
+            span: *span,
+            original_text: original_text.to_string(),
+            replacement_text: serde_json::to_string(replacement_value).unwrap(),
+        })
+    }
+}
+
+/// Fixes selected for a single file.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FileFixes {
+    pub file_path: String,
+    pub fixes: Vec<Fix>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FileFixesPreview {
+    pub file_path: String,
+    pub diff: String,
+}
+
+/// Applies fixes to `content` and returns the new text.
+///
+/// Several hints can carry the same fix (e.g. missing leading slash and
+/// incorrect slash of one value), so identical fixes are applied once.
+/// Fails if fixes overlap or if the text under a fix has changed since analysis.
+pub fn apply_fixes_to_text(content: &str, fixes: &[Fix]) -> Result<String, String> {
+    let mut fixes: Vec<&Fix> = fixes.iter().collect();
+    fixes.sort_by_key(|fix| (fix.span.start.offset, fix.span.end.offset));
+    fixes.dedup();
+
+    let mut fixed_content = String::with_capacity(content.len());
+    let mut copied_offset = 0;
+    for fix in fixes {
+        if fix.span.start.offset < copied_offset {
+            return Err(format!(
+                "Fix at line {} column {} overlaps another fix",
+                fix.span.start.line, fix.span.start.column
+            ));
+        }
+
+        let current_text = content.get(fix.span.start.offset..fix.span.end.offset);
+        if current_text != Some(fix.original_text.as_str()) {
+            return Err(format!(
+                "Text at line {} column {} has changed since analysis",
+                fix.span.start.line, fix.span.start.column
+            ));
+        }
+
+        fixed_content.push_str(&content[copied_offset..fix.span.start.offset]);
+        fixed_content.push_str(&fix.replacement_text);
+        copied_offset = fix.span.end.offset;
+    }
+    fixed_content.push_str(&content[copied_offset..]);
+
+    Ok(fixed_content)
+}
+
+pub fn preview_fixes(files_fixes: &[FileFixes]) -> Result<Vec<FileFixesPreview>, String> {
+    let mut previews: Vec<FileFixesPreview> = Vec::new();
+    for file_fixes in &merge_files_fixes(files_fixes) {
+        let content = read_file(&file_fixes.file_path)?;
+        let fixed_content = apply_fixes_to_text(&content, &file_fixes.fixes)?;
+
+        previews.push(FileFixesPreview {
+            file_path: file_fixes.file_path.clone(),
+            diff: unified_diff(&file_fixes.file_path, &content, &fixed_content),
+        });
+    }
+    Ok(previews)
+}
+
+/// Rewrites files on disk. Nothing is written if any of the fixes can't be applied.
+pub fn apply_fixes(files_fixes: &[FileFixes]) -> Result<(), String> {
+    let files_fixes = merge_files_fixes(files_fixes);
+    let mut fixed_contents: Vec<(&String, String)> = Vec::new();
+    for file_fixes in &files_fixes {
+        let content = read_file(&file_fixes.file_path)?;
+        let fixed_content = apply_fixes_to_text(&content, &file_fixes.fixes)?;
+        fixed_contents.push((&file_fixes.file_path, fixed_content));
+    }
+
+    for (file_path, fixed_content) in fixed_contents {
+        if fs::write(file_path, fixed_content).is_err() {
+            return Err(format!("Could not write file {}", file_path));
+        }
+    }
+    Ok(())
+}
+
+/// Joins fixes of the same file, so every file is read and written once.
+fn merge_files_fixes(files_fixes: &[FileFixes]) -> Vec<FileFixes> {
+    let mut merged: Vec<FileFixes> = Vec::new();
+    for file_fixes in files_fixes {
+        match merged
+            .iter_mut()
+            .find(|merged_fixes| merged_fixes.file_path == file_fixes.file_path)
+        {
+            Some(merged_fixes) => merged_fixes.fixes.extend(file_fixes.fixes.iter().cloned()),
+            None => merged.push(file_fixes.clone()),
+        }
+    }
+    merged
+}
+
+pub fn read_file(file_path: &str) -> Result<String, String> {
+    let content = fs::read_to_string(file_path);
+    if content.is_err() {
+        return Err(format!("Could not read file {}", file_path));
+    }
+    Ok(content.unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analyzers::json_source_map::JsonSourceMap;
+
+    use super::*;
+
+    fn create_fix(content: &str, pointer: &str, replacement_value: &str) -> Fix {
+        let source_map = JsonSourceMap::parse(content).unwrap();
+        Fix::replace_string_literal(content, source_map.get_span(pointer).unwrap(), replacement_value).unwrap()
+    }
+
+    #[test]
+    fn applies_fixes_in_any_order() {
+        let content = "{\"a\": \"x\\\\y.json\", \"b\": \"z.json\"}";
+        let fixes = vec![
+            create_fix(content, "/b", "/z.json"),
+            create_fix(content, "/a", "/x/y.json"),
+        ];
+        assert_eq!(
+            apply_fixes_to_text(content, &fixes).unwrap(),
+            "{\"a\": \"/x/y.json\", \"b\": \"/z.json\"}"
+        );
+    }
+
+    #[test]
+    fn identical_fixes_are_applied_once() {
+        let content = "[\"a.json\"]";
+        let fix = create_fix(content, "/0", "/a.json");
+        assert_eq!(apply_fixes_to_text(content, &[fix.clone(), fix]).unwrap(), "[\"/a.json\"]");
+    }
+
+    #[test]
+    fn overlapping_fixes_are_rejected() {
+        let content = "{\"a\": [\"b.json\"]}";
+        let inner_fix = create_fix(content, "/a/0", "/b.json");
+        let mut outer_fix = inner_fix.clone();
+        outer_fix.span.end.offset += 1;
+        outer_fix.original_text = content[outer_fix.span.start.offset..outer_fix.span.end.offset].to_string();
+        outer_fix.replacement_text = String::from("\"/c.json\"]");
+
+        let result = apply_fixes_to_text(content, &[inner_fix, outer_fix]);
+        assert!(result.unwrap_err().contains("overlaps"));
+    }
+
+    #[test]
+    fn changed_text_is_rejected() {
+        let content = "[\"a.json\"]";
+        let fix = create_fix(content, "/0", "/a.json");
+        let result = apply_fixes_to_text("[\"b.json\"]", &[fix]);
+        assert!(result.unwrap_err().contains("has changed"));
+    }
+}
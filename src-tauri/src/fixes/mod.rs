@@ -0,0 +1,3 @@
+pub mod diff;
+pub mod fix;
+pub mod rename;
\ No newline at end of file
@@ -0,0 +1,498 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    analyzers::{
+        json_hints::JsonHintFactory,
+        json_reader::read_json_file,
+        json_source_map::join_pointer,
+        scene_analyzer::{get_coui_relative_path, COUI_SCHEME},
+        string_classifier::{classify_string, FileKind, StringKind},
+    },
+    editor::editor_runtime::EditorRuntimeData,
+    project::repos::repository_tree::{get_entry_relative_path, RelativePath},
+    vfs::virtual_file_system::VirtualFileSystem,
+};
+
+use super::fix::{apply_fixes_to_text, preview_fixes, read_file, FileFixes, FileFixesPreview, Fix};
+
+/// Rename or move of a file or folder inside a repository. Paths are relative to the repository folder.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RenameRequest {
+    pub repository_folder: String,
+    pub from: String,
+    pub to: String,
+}
+
+/// Move of a file or folder on disk.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FileMove {
+    pub from: String,
+    pub to: String,
+}
+
+/// Everything a rename changes: the move itself and references rewritten in every repository.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RenamePlan {
+    pub request: RenameRequest,
+    pub file_move: FileMove,
+    /// Fixes of files at their location before the move.
+    pub files_fixes: Vec<FileFixes>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RenamePreview {
+    pub file_move: FileMove,
+    pub previews: Vec<FileFixesPreview>,
+}
+
+/// Content of a file rewritten by a rename.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct FileSnapshot {
+    /// Location before the rename.
+    file_path: String,
+    /// Location after the rename, differs from `file_path` for files inside the moved folder.
+    renamed_file_path: String,
+    original_content: String,
+    renamed_content: String,
+}
+
+/// What is needed to revert a whole rename as a single step.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RenameUndo {
+    pub request: RenameRequest,
+    file_move: FileMove,
+    files: Vec<FileSnapshot>,
+    /// Folders created for the destination, deepest last.
+    created_folders: Vec<String>,
+}
+
+/// Finds every json reference that resolves to the renamed file or to a file of the renamed folder.
+/// Paths and coui urls are rewritten in the form they were written in.
+/// References resolved to another layer, e.g. a mod with higher priority, are left as they are.
+pub fn plan_rename(editor_runtime_data: &EditorRuntimeData, request: &RenameRequest) -> Result<RenamePlan, String> {
+    let repository_tree = editor_runtime_data
+        .repository_trees
+        .iter()
+        .find(|repository_tree| repository_tree.repository_info.folder_path == request.repository_folder)
+        .ok_or(format!("Repository {} is not registered", request.repository_folder))?;
+
+    let from = RelativePath::new(request.from.clone());
+    let to = RelativePath::new(request.to.clone());
+    if from.value == "/" || to.value == "/" {
+        return Err(String::from("Repository folder itself can't be renamed"));
+    }
+    if from == to {
+        return Err(String::from("New path is the same as the old one"));
+    }
+    if to.value.starts_with(&(from.value.clone() + "/")) {
+        return Err(String::from("Folder can't be moved into itself"));
+    }
+
+    let entry = repository_tree
+        .find_entry(&from)
+        .ok_or(format!("{} does not exist in the repository", from.value))?;
+    let destination = get_file_path(&request.repository_folder, &to);
+    if destination.exists() {
+        return Err(format!("{} already exists", to.value));
+    }
+
+    let virtual_file_system = VirtualFileSystem::build(
+        &editor_runtime_data.directory_images,
+        &editor_runtime_data.repository_trees,
+    );
+    let layer = virtual_file_system
+        .get_repository_layer(&repository_tree.repository_info)
+        .ok_or(String::from("Repository is not mounted"))?;
+
+    // old path to new path of every moved file that the game loads from this repository
+    let mut renamed_paths: HashMap<RelativePath, RelativePath> = HashMap::new();
+    for tree_entry in &repository_tree.entries {
+        if tree_entry.is_directory {
+            continue;
+        }
+        let relative_path = get_entry_relative_path(repository_tree, tree_entry);
+        let renamed_path = match get_renamed_path(&relative_path, &from, &to, entry.is_directory) {
+            Some(renamed_path) => renamed_path,
+            None => continue,
+        };
+        let is_provider = virtual_file_system
+            .get_provider(&relative_path)
+            .is_some_and(|provider| provider.index == layer.index);
+        if is_provider {
+            renamed_paths.insert(relative_path, renamed_path);
+        }
+    }
+
+    let mut files_fixes: Vec<FileFixes> = Vec::new();
+    for tree in &editor_runtime_data.repository_trees {
+        for tree_entry in &tree.entries {
+            if tree_entry.is_directory || !tree_entry.path.ends_with(".json") {
+                continue;
+            }
+            // unreadable files have no references to rewrite
+            let json_file = match read_json_file(&tree_entry.path) {
+                Ok(json_file) => json_file,
+                Err(_) => continue,
+            };
+
+            let file_kind = FileKind::from_relative_path(&get_entry_relative_path(tree, tree_entry));
+            let hint_factory = JsonHintFactory::new(&json_file.content);
+            let mut fixes: Vec<Fix> = Vec::new();
+            for (pointer, value) in get_string_values(&json_file.value) {
+                let renamed_value = match classify_string(file_kind, &pointer, value) {
+                    StringKind::FilePath => get_renamed_file_path(value, &renamed_paths),
+                    StringKind::Url => get_renamed_url(value, &renamed_paths),
+                    _ => None,
+                };
+                if let Some(renamed_value) = renamed_value {
+                    if let Some(fix) = hint_factory.create_string_fix(&pointer, &renamed_value) {
+                        fixes.push(fix);
+                    }
+                }
+            }
+
+            if !fixes.is_empty() {
+                files_fixes.push(FileFixes {
+                    file_path: tree_entry.path.clone(),
+                    fixes,
+                });
+            }
+        }
+    }
+
+    Ok(RenamePlan {
+        request: request.clone(),
+        file_move: FileMove {
+            from: entry.path.clone(),
+            to: destination.to_string_lossy().to_string(),
+        },
+        files_fixes,
+    })
+}
+
+pub fn preview_rename(plan: &RenamePlan) -> Result<RenamePreview, String> {
+    Ok(RenamePreview {
+        file_move: plan.file_move.clone(),
+        previews: preview_fixes(&plan.files_fixes)?,
+    })
+}
+
+/// Rewrites references, then moves the file or folder.
+/// If the move fails, rewritten files are restored and nothing changes.
+pub fn apply_rename(plan: &RenamePlan) -> Result<RenameUndo, String> {
+    let mut files: Vec<FileSnapshot> = Vec::new();
+    for file_fixes in &plan.files_fixes {
+        let original_content = read_file(&file_fixes.file_path)?;
+        let renamed_content = apply_fixes_to_text(&original_content, &file_fixes.fixes)?;
+        files.push(FileSnapshot {
+            file_path: file_fixes.file_path.clone(),
+            renamed_file_path: get_moved_file_path(&file_fixes.file_path, &plan.file_move),
+            original_content,
+            renamed_content,
+        });
+    }
+
+    for (index, file) in files.iter().enumerate() {
+        if fs::write(&file.file_path, &file.renamed_content).is_err() {
+            restore_files(&files[..index]);
+            return Err(format!("Could not write file {}", file.file_path));
+        }
+    }
+
+    let created_folders = match create_parent_folders(Path::new(&plan.file_move.to)) {
+        Ok(created_folders) => created_folders,
+        Err(error) => {
+            restore_files(&files);
+            return Err(error);
+        }
+    };
+    if let Err(error) = fs::rename(&plan.file_move.from, &plan.file_move.to) {
+        remove_empty_folders(&created_folders);
+        restore_files(&files);
+        return Err(format!("Could not move {}: {}", plan.file_move.from, error));
+    }
+
+    Ok(RenameUndo {
+        request: plan.request.clone(),
+        file_move: plan.file_move.clone(),
+        files,
+        created_folders,
+    })
+}
+
+/// Moves the file or folder back and restores rewritten files.
+/// Fails without changes if any of the files was edited after the rename or can't be written.
+pub fn undo_rename(undo: &RenameUndo) -> Result<(), String> {
+    for file in &undo.files {
+        let content = read_file(&file.renamed_file_path)?;
+        if content != file.renamed_content {
+            return Err(format!("{} has changed since the rename", file.renamed_file_path));
+        }
+        if fs::metadata(&file.renamed_file_path).is_ok_and(|metadata| metadata.permissions().readonly()) {
+            return Err(format!("{} is read-only", file.renamed_file_path));
+        }
+    }
+    if Path::new(&undo.file_move.from).exists() {
+        return Err(format!("{} already exists", undo.file_move.from));
+    }
+
+    if let Err(error) = fs::rename(&undo.file_move.to, &undo.file_move.from) {
+        return Err(format!("Could not move {} back: {}", undo.file_move.to, error));
+    }
+
+    for (index, file) in undo.files.iter().enumerate() {
+        if fs::write(&file.file_path, &file.original_content).is_err() {
+            // the rename stays applied, so the undo can be tried again
+            for restored_file in &undo.files[..index] {
+                let _ = fs::write(&restored_file.file_path, &restored_file.renamed_content);
+            }
+            let _ = fs::rename(&undo.file_move.from, &undo.file_move.to);
+            return Err(format!("Could not restore file {}", file.file_path));
+        }
+    }
+    remove_empty_folders(&undo.created_folders);
+    Ok(())
+}
+
+/// New path of `relative_path` if it is the renamed file or lies inside the renamed folder.
+fn get_renamed_path(
+    relative_path: &RelativePath,
+    from: &RelativePath,
+    to: &RelativePath,
+    is_directory: bool,
+) -> Option<RelativePath> {
+    if !is_directory {
+        return match relative_path == from {
+            true => Some(to.clone()),
+            false => None,
+        };
+    }
+
+    let rest = relative_path.value.strip_prefix(&(from.value.clone() + "/"))?;
+    Some(RelativePath::new(format!("{}/{}", to.value, rest)))
+}
+
+/// `value` pointing at the renamed file if it is a path of a renamed file.
+/// Separators and the leading slash are kept as they were written.
+fn get_renamed_file_path(value: &str, renamed_paths: &HashMap<RelativePath, RelativePath>) -> Option<String> {
+    let renamed_path = renamed_paths.get(&RelativePath::new(value.to_string()))?;
+    let mut renamed_value = renamed_path.value.clone();
+    if !value.starts_with(['/', '\\']) {
+        renamed_value.remove(0);
+    }
+    if value.contains('\\') {
+        renamed_value = renamed_value.replace('/', "\\");
+    }
+    Some(renamed_value)
+}
+
+/// `url` pointing at the renamed file if it is a coui url of a renamed file.
+/// Query and fragment of the url are kept.
+fn get_renamed_url(url: &str, renamed_paths: &HashMap<RelativePath, RelativePath>) -> Option<String> {
+    let renamed_path = renamed_paths.get(&get_coui_relative_path(url)?)?;
+    let path = &url[COUI_SCHEME.len()..];
+    let rest = &path[path.find(['?', '#']).unwrap_or(path.len())..];
+    Some(format!("{}{}{}", COUI_SCHEME, renamed_path.value.trim_start_matches('/'), rest))
+}
+
+fn get_file_path(folder_path: &str, relative_path: &RelativePath) -> PathBuf {
+    PathBuf::from(folder_path).join(relative_path.value.trim_start_matches('/'))
+}
+
+/// Where `file_path` ends up after `file_move`.
+fn get_moved_file_path(file_path: &str, file_move: &FileMove) -> String {
+    match Path::new(file_path).strip_prefix(&file_move.from) {
+        Ok(rest) if rest.as_os_str().is_empty() => file_move.to.clone(),
+        Ok(rest) => Path::new(&file_move.to).join(rest).to_string_lossy().to_string(),
+        Err(_) => file_path.to_string(),
+    }
+}
+
+/// Every string value of `value` with its JSON Pointer.
+fn get_string_values(value: &Value) -> Vec<(String, &str)> {
+    let mut values: Vec<(String, &str)> = Vec::new();
+    collect_string_values(value, String::new(), &mut values);
+    values
+}
+
+fn collect_string_values<'a>(value: &'a Value, pointer: String, values: &mut Vec<(String, &'a str)>) {
+    match value {
+        Value::String(string_value) => values.push((pointer, string_value)),
+        Value::Object(object) => {
+            for (key, child_value) in object {
+                collect_string_values(child_value, join_pointer(&pointer, key), values);
+            }
+        }
+        Value::Array(array) => {
+            for (index, child_value) in array.iter().enumerate() {
+                collect_string_values(child_value, join_pointer(&pointer, &index.to_string()), values);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Creates missing parents of `path`. Returns created folders, outermost first.
+fn create_parent_folders(path: &Path) -> Result<Vec<String>, String> {
+    let mut missing_folders: Vec<&Path> = Vec::new();
+    let mut parent = path.parent();
+    while let Some(folder) = parent {
+        if folder.exists() {
+            break;
+        }
+        missing_folders.push(folder);
+        parent = folder.parent();
+    }
+
+    let mut created_folders: Vec<String> = Vec::new();
+    for folder in missing_folders.into_iter().rev() {
+        if fs::create_dir(folder).is_err() {
+            remove_empty_folders(&created_folders);
+            return Err(format!("Could not create folder {}", folder.display()));
+        }
+        created_folders.push(folder.to_string_lossy().to_string());
+    }
+    Ok(created_folders)
+}
+
+fn remove_empty_folders(folders: &[String]) {
+    for folder in folders.iter().rev() {
+        let _ = fs::remove_dir(folder);
+    }
+}
+
+fn restore_files(files: &[FileSnapshot]) {
+    for file in files {
+        let _ = fs::write(&file.file_path, &file.original_content);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_folder::TestFolder;
+
+    use super::*;
+
+    const UNIT_LIST: &str = r#"{"units": ["/pa/units/tank/tank.json", "pa\\units\\tank\\tank.json", "/pa/units/bot.json"]}"#;
+
+    const TANK: &str = r#"{"tools": [{"spec_id": "/pa/units/tank/tank_weapon.json"}]}"#;
+
+    /// "tank" mod with a tank folder and "army" mod whose unit list references it.
+    fn create_mods(test_folder: &TestFolder) -> EditorRuntimeData {
+        test_folder.write("/tank/modinfo.json", r#"{"identifier": "com.tank"}"#);
+        test_folder.write("/tank/pa/units/tank/tank.json", TANK);
+        test_folder.write("/tank/pa/units/tank/tank_weapon.json", "{}");
+        test_folder.write("/army/modinfo.json", r#"{"identifier": "com.army"}"#);
+        test_folder.write("/army/pa/units/unit_list.json", UNIT_LIST);
+        test_folder.write("/army/pa/units/bot.json", "{}");
+        test_folder.create_runtime_data(&["tank", "army"])
+    }
+
+    fn plan_tank_move(test_folder: &TestFolder, editor_runtime_data: &EditorRuntimeData) -> RenamePlan {
+        let request = RenameRequest {
+            repository_folder: test_folder.path().join("tank").to_string_lossy().to_string(),
+            from: String::from("/pa/units/tank"),
+            to: String::from("/pa/units/land/tank"),
+        };
+        plan_rename(editor_runtime_data, &request).unwrap()
+    }
+
+    fn create_renamed_paths(from: &str, to: &str) -> HashMap<RelativePath, RelativePath> {
+        HashMap::from([(RelativePath::new(from.to_string()), RelativePath::new(to.to_string()))])
+    }
+
+    #[test]
+    fn coui_urls_are_renamed_in_coui_form() {
+        let renamed_paths = create_renamed_paths("/ui/mods/mod/old.html", "/ui/mods/mod/new.html");
+        assert_eq!(
+            get_renamed_url("coui://ui/mods/mod/old.html", &renamed_paths).unwrap(),
+            "coui://ui/mods/mod/new.html"
+        );
+        assert_eq!(
+            get_renamed_url("coui://ui/mods/mod/old.html?page=2#top", &renamed_paths).unwrap(),
+            "coui://ui/mods/mod/new.html?page=2#top"
+        );
+        assert!(get_renamed_url("coui://ui/mods/mod/other.html", &renamed_paths).is_none());
+        assert!(get_renamed_url("https://ui/mods/mod/old.html", &renamed_paths).is_none());
+    }
+
+    #[test]
+    fn folder_rename_moves_nested_paths() {
+        let from = RelativePath::new(String::from("/pa/units/tank"));
+        let to = RelativePath::new(String::from("/pa/units/land/tank"));
+        let relative_path = RelativePath::new(String::from("/pa/units/tank/tank.json"));
+        assert_eq!(
+            get_renamed_path(&relative_path, &from, &to, true).unwrap().value,
+            "/pa/units/land/tank/tank.json"
+        );
+        assert!(get_renamed_path(&RelativePath::new(String::from("/pa/units/tanker.json")), &from, &to, true).is_none());
+    }
+
+    #[test]
+    fn file_paths_keep_their_separators_and_leading_slash() {
+        let renamed_paths = create_renamed_paths("/pa/units/tank.json", "/pa/units/land/tank.json");
+        let rename = |value: &str| get_renamed_file_path(value, &renamed_paths);
+        assert_eq!(rename("/pa/units/tank.json").unwrap(), "/pa/units/land/tank.json");
+        assert_eq!(rename("pa/units/tank.json").unwrap(), "pa/units/land/tank.json");
+        assert_eq!(rename("\\pa\\units\\tank.json").unwrap(), "\\pa\\units\\land\\tank.json");
+        assert_eq!(rename("pa\\units\\tank.json").unwrap(), "pa\\units\\land\\tank.json");
+        assert!(rename("/pa/units/bot.json").is_none());
+    }
+
+    #[test]
+    fn folder_move_rewrites_references_of_every_repository() {
+        let test_folder = TestFolder::new();
+        let editor_runtime_data = create_mods(&test_folder);
+
+        let plan = plan_tank_move(&test_folder, &editor_runtime_data);
+        let mut file_paths: Vec<&str> = plan.files_fixes.iter().map(|file_fixes| file_fixes.file_path.as_str()).collect();
+        file_paths.sort();
+        let unit_list_path = test_folder.path().join("army/pa/units/unit_list.json");
+        let tank_path = test_folder.path().join("tank/pa/units/tank/tank.json");
+        assert_eq!(file_paths, vec![unit_list_path.to_str().unwrap(), tank_path.to_str().unwrap()]);
+
+        apply_rename(&plan).unwrap();
+        assert!(!test_folder.path().join("tank/pa/units/tank").exists());
+        assert_eq!(
+            test_folder.read("/army/pa/units/unit_list.json"),
+            r#"{"units": ["/pa/units/land/tank/tank.json", "pa\\units\\land\\tank\\tank.json", "/pa/units/bot.json"]}"#
+        );
+        assert_eq!(
+            test_folder.read("/tank/pa/units/land/tank/tank.json"),
+            r#"{"tools": [{"spec_id": "/pa/units/land/tank/tank_weapon.json"}]}"#
+        );
+        assert_eq!(test_folder.read("/tank/pa/units/land/tank/tank_weapon.json"), "{}");
+    }
+
+    #[test]
+    fn undo_restores_files_and_removes_created_folders() {
+        let test_folder = TestFolder::new();
+        let editor_runtime_data = create_mods(&test_folder);
+        let undo = apply_rename(&plan_tank_move(&test_folder, &editor_runtime_data)).unwrap();
+
+        undo_rename(&undo).unwrap();
+        assert_eq!(test_folder.read("/army/pa/units/unit_list.json"), UNIT_LIST);
+        assert_eq!(test_folder.read("/tank/pa/units/tank/tank.json"), TANK);
+        assert!(!test_folder.path().join("tank/pa/units/land").exists());
+    }
+
+    #[test]
+    fn undo_fails_without_changes_after_files_are_edited() {
+        let test_folder = TestFolder::new();
+        let editor_runtime_data = create_mods(&test_folder);
+        let undo = apply_rename(&plan_tank_move(&test_folder, &editor_runtime_data)).unwrap();
+        let edited_tank = test_folder.write("/tank/pa/units/land/tank/tank.json", "{}");
+
+        assert_eq!(undo_rename(&undo).unwrap_err(), format!("{} has changed since the rename", edited_tank));
+        assert!(!test_folder.path().join("tank/pa/units/tank").exists());
+        assert_eq!(test_folder.read("/tank/pa/units/land/tank/tank.json"), "{}");
+        assert_ne!(test_folder.read("/army/pa/units/unit_list.json"), UNIT_LIST);
+    }
+}
@@ -0,0 +1 @@
+pub mod runner;
\ No newline at end of file
@@ -0,0 +1,472 @@
+use std::path::{Path, PathBuf};
+
+use owo_colors::OwoColorize;
+
+use crate::{
+    analyzers::{
+        analyzer::{analyze_repositories, Category, FileAnalysisResult, Hint},
+        baseline::write_baselines,
+    },
+    directory_image::load_directory_image,
+    editor::{editor::EditorEnvironment, editor_runtime::EditorRuntimeData},
+    logs::logbox,
+    project::repos::{
+        repository::{is_repository_folder, Repository},
+        repository_tree::build_repository_tree,
+    },
+    reports::report::{render_report, write_report, Report, ReportFormat},
+};
+
+pub const EXIT_CODE_CLEAN: i32 = 0;
+pub const EXIT_CODE_WARNINGS: i32 = 1;
+pub const EXIT_CODE_FAILURE: i32 = 2;
+
+pub const USAGE: &str = "\
+Usage: pa_lint [OPTIONS] <MOD_FOLDER>...
+
+Arguments:
+  <MOD_FOLDER>...        folders that contain modinfo.json
+
+Options:
+  -i, --image <FILE>     directory image (.json) of the base game, can be repeated,
+                         images are mounted in the given order, the base game first
+  -f, --format <FORMAT>  report format: json, sarif, junit or html
+  -o, --output <FILE>    write the report to a file instead of stdout, requires --format
+  -s, --show-suppressed  list suppressed and baselined hints as well, they never fail the run
+  -b, --write-baseline   write current hints to pa-linter-baseline.json of every mod,
+                         later runs report and fail only on hints that are not in it
+  -v, --verbose          print analyzer logs
+  -h, --help             print this help";
+
+/// Options of a single headless run.
+#[derive(Debug, Clone, Default)]
+pub struct RunnerOptions {
+    pub repository_folders: Vec<PathBuf>,
+    pub image_files: Vec<PathBuf>,
+    /// Format of the report, `None` prints colored text.
+    pub report_format: Option<ReportFormat>,
+    /// Where to write the report. Without it the report replaces the text output.
+    pub output_file: Option<PathBuf>,
+    /// Lists suppressed and baselined hints in the text output and the report, they are counted anyway.
+    pub show_suppressed: bool,
+    /// Writes baselines instead of printing hints.
+    pub write_baseline: bool,
+    pub verbose: bool,
+    pub help: bool,
+}
+
+impl RunnerOptions {
+    /// Parses command line arguments without the executable name.
+    pub fn from_args(args: impl Iterator<Item = String>) -> Result<RunnerOptions, String> {
+        let mut options = RunnerOptions::default();
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "-h" | "--help" => options.help = true,
+                "-v" | "--verbose" => options.verbose = true,
+                "-s" | "--show-suppressed" => options.show_suppressed = true,
+                "-b" | "--write-baseline" => options.write_baseline = true,
+                "-i" | "--image" => {
+                    let image_file = args.next();
+                    if image_file.is_none() {
+                        return Err(format!("{} requires a file path", arg));
+                    }
+                    options.image_files.push(PathBuf::from(image_file.unwrap()));
+                }
+                "-f" | "--format" => {
+                    let format_name = args.next();
+                    if format_name.is_none() {
+                        return Err(format!("{} requires a format name", arg));
+                    }
+                    options.report_format = Some(ReportFormat::from_name(&format_name.unwrap())?);
+                }
+                "-o" | "--output" => {
+                    let output_file = args.next();
+                    if output_file.is_none() {
+                        return Err(format!("{} requires a file path", arg));
+                    }
+                    options.output_file = Some(PathBuf::from(output_file.unwrap()));
+                }
+                _ if arg.starts_with('-') => {
+                    return Err(format!("Unknown option: {}", arg));
+                }
+                _ => options.repository_folders.push(PathBuf::from(arg)),
+            }
+        }
+
+        if !options.help && options.repository_folders.is_empty() {
+            return Err(String::from("No mod folders were given"));
+        }
+        if options.output_file.is_some() && options.report_format.is_none() {
+            return Err(String::from("--output requires --format"));
+        }
+        if options.write_baseline && options.report_format.is_some() {
+            return Err(String::from("--write-baseline can't be combined with --format"));
+        }
+
+        Ok(options)
+    }
+}
+
+/// Runs the analyzers over the given mod folders and prints hints or the report to stdout.
+/// Returns the process exit code.
+pub fn run(options: &RunnerOptions) -> i32 {
+    if options.help {
+        println!("{}", USAGE);
+        return EXIT_CODE_CLEAN;
+    }
+
+    logbox::set_enabled(options.verbose);
+
+    let editor_runtime_data = build_runtime_data(options);
+    if editor_runtime_data.is_err() {
+        eprintln!("{} {}", "error:".red().bold(), editor_runtime_data.err().unwrap());
+        return EXIT_CODE_FAILURE;
+    }
+    let editor_runtime_data = editor_runtime_data.unwrap();
+
+    let results = analyze_repositories(&editor_runtime_data);
+    if options.write_baseline {
+        let folder_paths: Vec<String> = editor_runtime_data
+            .repository_trees
+            .iter()
+            .map(|repository_tree| repository_tree.repository_info.folder_path.clone())
+            .collect();
+        return match write_baselines(&folder_paths, &results) {
+            Ok(hint_count) => {
+                println!("{} hints written to the baselines of {} mods", hint_count, folder_paths.len());
+                EXIT_CODE_CLEAN
+            }
+            Err(error) => {
+                eprintln!("{} {}", "error:".red().bold(), error);
+                EXIT_CODE_FAILURE
+            }
+        };
+    }
+
+    if let Err(error) = output_results(options, &results) {
+        eprintln!("{} {}", "error:".red().bold(), error);
+        return EXIT_CODE_FAILURE;
+    }
+
+    get_exit_code(&results)
+}
+
+/// Errors and warnings fail the run, suggestions and suppressed or baselined hints don't.
+pub fn get_exit_code(results: &[FileAnalysisResult]) -> i32 {
+    if count_hints(results, |category| matches!(category, Category::Error | Category::Warning)) > 0 {
+        EXIT_CODE_WARNINGS
+    } else {
+        EXIT_CODE_CLEAN
+    }
+}
+
+/// Builds runtime data the same way the editor does after
+/// `c_get_project_trees` and `c_get_directory_images`, but from arguments.
+fn build_runtime_data(options: &RunnerOptions) -> Result<EditorRuntimeData, String> {
+    let editor_env = get_headless_environment()?;
+    let mut editor_runtime_data = EditorRuntimeData::new(editor_env);
+
+    for image_file in &options.image_files {
+        let image = load_directory_image(image_file)?;
+        editor_runtime_data.directory_images.push(image);
+    }
+
+    for repository_folder in &options.repository_folders {
+        if !repository_folder.exists() {
+            return Err(format!("{} does not exist", repository_folder.display()));
+        }
+        if !is_repository_folder(repository_folder) {
+            return Err(format!(
+                "{} is not a mod folder. Mod folder must contain modinfo.json file",
+                repository_folder.display()
+            ));
+        }
+
+        let repository = Repository::new(get_folder_path_string(repository_folder)?);
+        editor_runtime_data
+            .repository_trees
+            .push(build_repository_tree(&repository));
+    }
+
+    Ok(editor_runtime_data)
+}
+
+/// The runner never touches the workspace folder, so the environment
+/// is only filled, not initialized.
+fn get_headless_environment() -> Result<EditorEnvironment, String> {
+    let executable_file_path = std::env::current_exe();
+    if executable_file_path.is_err() {
+        return Err(String::from("Could not get runner executable path"));
+    }
+    let executable_file_path = executable_file_path.unwrap();
+
+    let executable_folder_path = executable_file_path.parent();
+    if executable_folder_path.is_none() {
+        return Err(String::from("Could not get runner folder path"));
+    }
+
+    Ok(EditorEnvironment::new(
+        executable_file_path.to_str().unwrap().to_string(),
+        executable_folder_path.unwrap().to_str().unwrap().to_string(),
+    ))
+}
+
+/// Repository trees compare entry paths with the folder path by prefix,
+/// so the folder path has to be absolute and without trailing separator.
+fn get_folder_path_string(folder_path: &Path) -> Result<String, String> {
+    let folder_path = std::fs::canonicalize(folder_path);
+    if folder_path.is_err() {
+        return Err(format!("Could not resolve folder: {}", folder_path.err().unwrap()));
+    }
+    let folder_path = folder_path.unwrap();
+
+    match folder_path.to_str() {
+        Some(folder_path) => Ok(folder_path.trim_end_matches(['/', '\\']).to_string()),
+        None => Err(format!("{} is not a valid unicode path", folder_path.display())),
+    }
+}
+
+/// Text to stdout, the report to stdout or text to stdout and the report to a file.
+fn output_results(options: &RunnerOptions, results: &[FileAnalysisResult]) -> Result<(), String> {
+    let report_format = match options.report_format {
+        Some(report_format) => report_format,
+        None => {
+            print_results(results, options.show_suppressed);
+            return Ok(());
+        }
+    };
+
+    let report = Report::new(results, options.show_suppressed);
+    match &options.output_file {
+        Some(output_file) => {
+            print_results(results, options.show_suppressed);
+            write_report(&report, report_format, output_file)
+        }
+        None => {
+            println!("{}", render_report(&report, report_format)?);
+            Ok(())
+        }
+    }
+}
+
+fn print_results(results: &[FileAnalysisResult], show_suppressed: bool) {
+    for result in results {
+        let has_hidden_hints = !result.suppressed_messages.is_empty() || !result.baselined_messages.is_empty();
+        if result.messages.is_empty() && (!show_suppressed || !has_hidden_hints) {
+            continue;
+        }
+
+        println!("{}", result.file_path.bold());
+        for hint in &result.messages {
+            print_hint(hint, None);
+        }
+        if show_suppressed {
+            for hint in &result.suppressed_messages {
+                print_hint(hint, Some("suppressed"));
+            }
+            for hint in &result.baselined_messages {
+                print_hint(hint, Some("baselined"));
+            }
+        }
+        println!();
+    }
+
+    let errors = count_hints(results, |category| matches!(category, Category::Error));
+    let warnings = count_hints(results, |category| matches!(category, Category::Warning));
+    let suggestions = count_hints(results, |category| matches!(category, Category::Suggestion));
+    let suppressed: usize = results.iter().map(|result| result.suppressed_messages.len()).sum();
+    let baselined: usize = results.iter().map(|result| result.baselined_messages.len()).sum();
+    let mut summary = format!("{} errors, {} warnings, {} suggestions", errors, warnings, suggestions);
+    if suppressed > 0 {
+        summary.push_str(&format!(", {} suppressed", suppressed));
+    }
+    if baselined > 0 {
+        summary.push_str(&format!(", {} baselined", baselined));
+    }
+    println!("{}", summary);
+}
+
+/// `hidden_state` is "suppressed" or "baselined" for hints that don't fail the run.
+fn print_hint(hint: &Hint, hidden_state: Option<&str>) {
+    match hint {
+        Hint::JSON {
+            category,
+            rule_id,
+            property_value,
+            pointer,
+            span,
+            message,
+            suggestions,
+            ..
+        } => {
+            let position = match span {
+                Some(span) => format!("{}:{} ", span.start.line, span.start.column),
+                None => String::new(),
+            };
+            // hints about the whole document have no value to show
+            let value = match pointer.is_empty() && property_value.is_empty() {
+                true => String::new(),
+                false => format!(" ({}: {})", pointer.cyan(), property_value),
+            };
+            println!(
+                "  {}{} {}{} [{}]",
+                position.bright_black(),
+                format_category(category, hidden_state),
+                message,
+                value,
+                rule_id.bright_black()
+            );
+            for suggestion in suggestions {
+                println!("      {} {} ({})", "help:".green(), suggestion.value, suggestion.message);
+            }
+        }
+        Hint::Conflict {
+            category,
+            rule_id,
+            message,
+            ..
+        }
+        | Hint::Orphan {
+            category,
+            rule_id,
+            message,
+            ..
+        }
+        | Hint::OrphanSummary {
+            category,
+            rule_id,
+            message,
+            ..
+        } => {
+            println!(
+                "  {} {} [{}]",
+                format_category(category, hidden_state),
+                message,
+                rule_id.bright_black()
+            );
+        }
+        Hint::Parse {
+            category,
+            rule_id,
+            message,
+            line,
+            column,
+        } => {
+            let position = match (line, column) {
+                (Some(line), Some(column)) => format!("{}:{} ", line, column),
+                _ => String::new(),
+            };
+            println!(
+                "  {}{} {} [{}]",
+                position.bright_black(),
+                format_category(category, hidden_state),
+                message,
+                rule_id.bright_black()
+            );
+        }
+    }
+}
+
+fn format_category(category: &Category, hidden_state: Option<&str>) -> String {
+    if let Some(hidden_state) = hidden_state {
+        let name = match category {
+            Category::Error => "error",
+            Category::Warning => "warning",
+            Category::Suggestion => "suggestion",
+        };
+        return format!("{} ({}):", name, hidden_state).bright_black().to_string();
+    }
+    match category {
+        Category::Error => "error:".red().bold().to_string(),
+        Category::Warning => "warning:".yellow().bold().to_string(),
+        Category::Suggestion => "suggestion:".blue().bold().to_string(),
+    }
+}
+
+fn count_hints(results: &[FileAnalysisResult], filter: impl Fn(&Category) -> bool) -> usize {
+    results
+        .iter()
+        .flat_map(|result| result.messages.iter())
+        .filter(|hint| filter(&hint.category()))
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::project::repos::repository::RepositoryInfo;
+
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<RunnerOptions, String> {
+        RunnerOptions::from_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    fn create_result(messages: Vec<Hint>, suppressed_messages: Vec<Hint>) -> FileAnalysisResult {
+        FileAnalysisResult {
+            file_path: String::from("/mods/tank/pa/tank.json"),
+            repository_info: RepositoryInfo {
+                folder_path: String::from("/mods/tank"),
+                mod_identifier: String::from("com.tank"),
+                mod_priority: 100,
+            },
+            messages,
+            suppressed_messages,
+            baselined_messages: Vec::new(),
+        }
+    }
+
+    fn create_hint(category: Category) -> Hint {
+        Hint::Parse {
+            category,
+            rule_id: String::from("json-parse-error"),
+            message: String::from("Unexpected end of file"),
+            line: Some(2),
+            column: Some(1),
+        }
+    }
+
+    #[test]
+    fn options_are_parsed_in_any_order() {
+        let args = ["-s", "/mods/a", "--image", "pa.json", "-f", "sarif", "/mods/b", "-o", "report.sarif"];
+        let options = parse(&args).unwrap();
+
+        assert_eq!(options.repository_folders, vec![PathBuf::from("/mods/a"), PathBuf::from("/mods/b")]);
+        assert_eq!(options.image_files, vec![PathBuf::from("pa.json")]);
+        assert_eq!(options.report_format, Some(ReportFormat::Sarif));
+        assert_eq!(options.output_file, Some(PathBuf::from("report.sarif")));
+        assert!(options.show_suppressed);
+        assert!(!options.write_baseline);
+    }
+
+    #[test]
+    fn help_needs_no_mod_folders() {
+        assert!(parse(&["--help"]).unwrap().help);
+        assert_eq!(parse(&[]).err().unwrap(), "No mod folders were given");
+    }
+
+    #[test]
+    fn invalid_arguments_are_rejected() {
+        assert_eq!(parse(&["/mods/a", "-i"]).err().unwrap(), "-i requires a file path");
+        assert_eq!(parse(&["/mods/a", "--bogus"]).err().unwrap(), "Unknown option: --bogus");
+        assert_eq!(parse(&["/mods/a", "-o", "report.json"]).err().unwrap(), "--output requires --format");
+        assert_eq!(
+            parse(&["/mods/a", "-b", "-f", "json"]).err().unwrap(),
+            "--write-baseline can't be combined with --format"
+        );
+        assert!(parse(&["/mods/a", "-f", "xml"]).is_err());
+    }
+
+    #[test]
+    fn only_errors_and_warnings_fail_the_run() {
+        let reported = |category| create_result(vec![create_hint(category)], Vec::new());
+        let suppressed = |category| create_result(Vec::new(), vec![create_hint(category)]);
+
+        assert_eq!(get_exit_code(&[]), EXIT_CODE_CLEAN);
+        assert_eq!(get_exit_code(&[reported(Category::Suggestion)]), EXIT_CODE_CLEAN);
+        assert_eq!(get_exit_code(&[suppressed(Category::Error)]), EXIT_CODE_CLEAN);
+        assert_eq!(get_exit_code(&[reported(Category::Warning)]), EXIT_CODE_WARNINGS);
+        assert_eq!(get_exit_code(&[reported(Category::Suggestion), reported(Category::Error)]), EXIT_CODE_WARNINGS);
+    }
+}
@@ -0,0 +1,17 @@
+pub mod analyzer;
+pub mod analyzers;
+pub mod directory_image;
+pub mod nodes;
+pub mod project;
+pub mod editor;
+pub mod ui;
+pub mod quicks;
+pub mod logs;
+pub mod headless;
+pub mod fixes;
+pub mod vfs;
+pub mod specs;
+pub mod references;
+pub mod watch;
+pub mod reports;#[cfg(test)]
+pub mod test_folder;
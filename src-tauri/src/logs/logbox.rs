@@ -1,5 +1,19 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
 use parking_lot::RwLock;
 
+/// Console output of logboxes. The headless runner turns it off
+/// so only the hints end up in stdout.
+static LOGBOX_ENABLED: AtomicBool = AtomicBool::new(true);
+
+pub fn set_enabled(enabled: bool) {
+    LOGBOX_ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+pub fn is_enabled() -> bool {
+    LOGBOX_ENABLED.load(Ordering::Relaxed)
+}
+
 pub struct Logbox {
     pub logs: RwLock<Vec<Log>>,
 
@@ -32,6 +46,9 @@ impl Logbox {
     }
 
     pub fn print(&mut self) {
+        if !is_enabled() {
+            return;
+        }
 
         // start iterating from index
         while self.print_log_index < self.logs.read().len() {
@@ -4,34 +4,32 @@
 use core::panic;
 use std::{cell::OnceCell, path::{Path, PathBuf}, process::Command, sync::OnceLock};
 
-use analyzers::analyzer::FileAnalysisResult;
-use directory_image::{get_directory_images, save_directory_image};
-use editor::{editor::*, editor_runtime::EditorRuntimeData};
-use nodes::{ArenaTree, Node, NodeId};
-use parking_lot::RwLock;
-use project::repos::{self, repository::{self, add_repository, remove_repository, Repository, RepositoryInfo}, repository_tree::{build_repository_tree, RepositoryTree}};
+use app::{
+    analyzers::{self, analysis_cache::{load_analysis_cache, save_analysis_cache}, analysis_session::AnalysisSession, analyzer::{FileAnalysisResult, Rule}, baseline::write_baselines, registry::AnalyzerRegistry},
+    directory_image::{get_directory_images, save_directory_image, take_directory_image, DirectoryImage},
+    editor::{editor::*, editor_runtime::EditorRuntimeData},
+    fixes::{
+        fix::{apply_fixes, preview_fixes, FileFixes, FileFixesPreview},
+        rename::{apply_rename, plan_rename, preview_rename, undo_rename, RenamePreview, RenameRequest},
+    },
+    logs::logbox::Log,
+    nodes::{ArenaTree, Node, NodeId},
+    project::{
+        project::Project,
+        repos::{self, repository::{self, add_repository, get_repositories, remove_repository, Repository, RepositoryInfo}, repository_tree::{get_entry_relative_path, RelativePath, RepositoryTree, RepositoryTreeEntry}, repository_tree_snapshot::{get_repository_trees, save_repository_trees, update_content_hashes}},
+    },
+    references::reference_graph::FileReferences,
+    reports::report::{write_report, Report, ReportFormat},
+    specs::spec_resolver::{ResolvedSpec, SpecResolver},
+    ui::states::{AnalysisSessionState, EditorRuntimeState, RefactorHistoryState, RepositoryWatcherState},
+    vfs::virtual_file_system::{PathLayers, VirtualFileSystem},
+    watch::repository_watcher::{update_repository_trees, RepositoryWatcher},
+};
+use parking_lot::{Mutex, RwLock};
 use rand::Rng;
-use tauri::{api::file, State};
-use ui::states::EditorRuntimeState;
+use tauri::{api::file, AppHandle, Manager, State};
 use walkdir::{DirEntry, WalkDir};
 
-use crate::{
-    directory_image::{take_directory_image, DirectoryImage},
-    project::project::Project,
-    project::repos::repository::{get_repositories},
-    logs::logbox::Log
-};
-
-mod analyzer;
-mod analyzers;
-mod directory_image;
-mod nodes;
-mod project;
-mod editor;
-mod ui;
-mod quicks;
-mod logs;
-
 static PROJECT: OnceLock<Project> = OnceLock::new();
 static EDITOR_ENVIRONMENT: OnceLock<EditorEnvironment> = OnceLock::new();
 
@@ -63,6 +61,17 @@ fn main() {
     tauri::Builder::default()
         .plugin(devtools)
         .manage(EditorRuntimeState(RwLock::new(editor_runtime_data)))
+        .manage(AnalysisSessionState(RwLock::new(None)))
+        .manage(RefactorHistoryState(Mutex::new(Vec::new())))
+        .setup(|app| {
+            let app_handle = app.handle();
+            let watcher = RepositoryWatcher::new(move |changed_paths| on_repository_files_changed(&app_handle, changed_paths));
+            if let Err(error) = &watcher {
+                println!("{}", error);
+            }
+            app.manage(RepositoryWatcherState(Mutex::new(watcher.ok())));
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             c_take_directory_image,
             c_get_directory_images,
@@ -72,7 +81,18 @@ fn main() {
             c_get_project_trees,
             c_reveal_in_explorer,
             c_reveal_workspace_folder,
-            c_analyze_repositories
+            c_analyze_repositories,
+            c_get_rules,
+            c_preview_fixes,
+            c_apply_fixes,
+            c_get_path_layers,
+            c_resolve_spec,
+            c_get_file_references,
+            c_preview_rename,
+            c_apply_rename,
+            c_undo_rename,
+            c_export_report,
+            c_write_baseline
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -90,18 +110,34 @@ fn c_reveal_workspace_folder(){
 }
 
 #[tauri::command]
-fn c_get_project_trees(state: State<EditorRuntimeState>) -> Vec<RepositoryTree> {
-    let repositories = get_repositories(&EDITOR_ENVIRONMENT.get().unwrap());
-
-    let mut trees:Vec<RepositoryTree> = Vec::new();
-    for repository in repositories {
-        let tree = build_repository_tree(&repository);
-        trees.push(tree);
-    }
+fn c_get_project_trees(state: State<EditorRuntimeState>, watcher_state: State<RepositoryWatcherState>) -> Vec<RepositoryTree> {
+    let trees = build_project_trees();
 
     let mut editor_runtime_data = state.0.write();
     editor_runtime_data.repository_trees = trees.clone();
 
+    if let Some(watcher) = watcher_state.0.lock().as_mut() {
+        let folder_paths: Vec<String> = trees
+            .iter()
+            .map(|tree| tree.repository_info.folder_path.clone())
+            .collect();
+        if let Err(error) = watcher.watch_folders(&folder_paths) {
+            println!("{}", error);
+        }
+    }
+
+    trees
+}
+
+/// Trees from workspace snapshots reconciled with the disk, saved back for the next start.
+fn build_project_trees() -> Vec<RepositoryTree> {
+    let editor_env = EDITOR_ENVIRONMENT.get().unwrap();
+    let repositories = get_repositories(editor_env);
+
+    let trees = get_repository_trees(editor_env, &repositories);
+    if let Err(error) = save_repository_trees(editor_env, &trees) {
+        println!("{}", error);
+    }
     trees
 }
 
@@ -142,13 +178,225 @@ fn c_remove_repository(repository_folder: &Path) {
 }
 
 #[tauri::command]
-fn c_analyze_repositories(state: State<EditorRuntimeState>) -> Vec<FileAnalysisResult> {
-    let mut editor_runtime_data = state.0.read();
-
-    let results = analyzers::analyzer::analyze_repositories(&mut editor_runtime_data);
+fn c_analyze_repositories(
+    state: State<EditorRuntimeState>,
+    analysis_session_state: State<AnalysisSessionState>,
+) -> Vec<FileAnalysisResult> {
+    let editor_runtime_data = state.0.read();
+
+    let analysis_cache = load_analysis_cache(&editor_runtime_data.editor_env);
+    let analysis_session = AnalysisSession::analyze_with_cache(&editor_runtime_data, &analysis_cache);
+    if let Err(error) = save_analysis_cache(&editor_runtime_data.editor_env, &analysis_session.to_cache(&editor_runtime_data)) {
+        println!("{}", error);
+    }
+    let results = analysis_session.get_results().clone();
+    *analysis_session_state.0.write() = Some(analysis_session);
     results
 }
 
+/// Called by the repository watcher. Updates trees, re-analyzes changed files if repositories
+/// were analyzed, and tells the frontend with `repository-trees-updated` and `analysis-updated` events.
+fn on_repository_files_changed(app_handle: &AppHandle, changed_paths: Vec<PathBuf>) {
+    let state = app_handle.state::<EditorRuntimeState>();
+    let mut editor_runtime_data = state.0.write();
+    let changed_files = update_repository_trees(&mut editor_runtime_data.repository_trees, &changed_paths);
+    // only entries whose size or modification time changed have lost their hashes
+    for tree in &mut editor_runtime_data.repository_trees {
+        let folder_path = Path::new(&tree.repository_info.folder_path);
+        if changed_paths.iter().any(|changed_path| changed_path.starts_with(folder_path)) {
+            update_content_hashes(tree);
+        }
+    }
+    if let Err(error) = save_repository_trees(&editor_runtime_data.editor_env, &editor_runtime_data.repository_trees) {
+        println!("{}", error);
+    }
+    let _ = app_handle.emit_all("repository-trees-updated", editor_runtime_data.repository_trees.clone());
+    if changed_files.is_empty() {
+        return;
+    }
+
+    let analysis_session_state = app_handle.state::<AnalysisSessionState>();
+    let mut analysis_session = analysis_session_state.0.write();
+    if let Some(analysis_session) = analysis_session.as_mut() {
+        let update = analysis_session.reanalyze(&editor_runtime_data, &changed_files);
+        let _ = app_handle.emit_all("analysis-updated", update);
+        if let Err(error) = save_analysis_cache(&editor_runtime_data.editor_env, &analysis_session.to_cache(&editor_runtime_data)) {
+            println!("{}", error);
+        }
+    }
+}
+
+#[tauri::command]
+fn c_get_rules() -> Vec<Rule> {
+    AnalyzerRegistry::with_default_analyzers()
+        .get_rules()
+        .into_iter()
+        .cloned()
+        .collect()
+}
+
+#[tauri::command]
+fn c_preview_fixes(files_fixes: Vec<FileFixes>) -> Result<Vec<FileFixesPreview>, String> {
+    preview_fixes(&files_fixes)
+}
+
+#[tauri::command]
+fn c_apply_fixes(files_fixes: Vec<FileFixes>) -> Result<(), String> {
+    apply_fixes(&files_fixes)
+}
+
+#[tauri::command]
+fn c_get_path_layers(state: State<EditorRuntimeState>, relative_path: String) -> PathLayers {
+    let editor_runtime_data = state.0.read();
+    let virtual_file_system = VirtualFileSystem::build(
+        &editor_runtime_data.directory_images,
+        &editor_runtime_data.repository_trees,
+    );
+    virtual_file_system.get_path_layers(&RelativePath::new(relative_path))
+}
+
+#[tauri::command]
+fn c_resolve_spec(state: State<EditorRuntimeState>, relative_path: String) -> ResolvedSpec {
+    let editor_runtime_data = state.0.read();
+    let virtual_file_system = VirtualFileSystem::build(
+        &editor_runtime_data.directory_images,
+        &editor_runtime_data.repository_trees,
+    );
+    SpecResolver::new(&virtual_file_system).resolve(&RelativePath::new(relative_path))
+}
+
+/// What the selected entry references and who references it.
+/// Repositories are analyzed first if no analysis has run yet.
+#[tauri::command]
+fn c_get_file_references(
+    state: State<EditorRuntimeState>,
+    analysis_session_state: State<AnalysisSessionState>,
+    entry: RepositoryTreeEntry,
+) -> Result<FileReferences, String> {
+    let editor_runtime_data = state.0.read();
+    let repository_tree = editor_runtime_data
+        .repository_trees
+        .iter()
+        .find(|repository_tree| {
+            repository_tree
+                .get_entry(entry.id)
+                .is_some_and(|tree_entry| tree_entry.path == entry.path)
+        })
+        .ok_or(String::from("Entry does not belong to any repository"))?;
+    let relative_path = get_entry_relative_path(repository_tree, &entry);
+
+    let virtual_file_system = VirtualFileSystem::build(
+        &editor_runtime_data.directory_images,
+        &editor_runtime_data.repository_trees,
+    );
+    let layer = virtual_file_system
+        .get_repository_layer(&repository_tree.repository_info)
+        .ok_or(String::from("Repository is not mounted"))?;
+
+    let mut analysis_session = analysis_session_state.0.write();
+    if analysis_session.is_none() {
+        *analysis_session = Some(AnalysisSession::analyze(&editor_runtime_data));
+    }
+    Ok(analysis_session
+        .as_ref()
+        .unwrap()
+        .get_reference_graph()
+        .get_file_references(layer, &relative_path))
+}
+
+#[tauri::command]
+fn c_preview_rename(state: State<EditorRuntimeState>, request: RenameRequest) -> Result<RenamePreview, String> {
+    let editor_runtime_data = state.0.read();
+    let plan = plan_rename(&editor_runtime_data, &request)?;
+    preview_rename(&plan)
+}
+
+/// Renames and rewrites references, then returns rebuilt trees.
+#[tauri::command]
+fn c_apply_rename(
+    state: State<EditorRuntimeState>,
+    history_state: State<RefactorHistoryState>,
+    analysis_session_state: State<AnalysisSessionState>,
+    request: RenameRequest,
+) -> Result<Vec<RepositoryTree>, String> {
+    // history is locked before the runtime data here and in c_undo_rename
+    let mut history = history_state.0.lock();
+    let mut editor_runtime_data = state.0.write();
+    let plan = plan_rename(&editor_runtime_data, &request)?;
+    let undo = apply_rename(&plan)?;
+    history.push(undo);
+
+    editor_runtime_data.repository_trees = build_project_trees();
+    *analysis_session_state.0.write() = None;
+    Ok(editor_runtime_data.repository_trees.clone())
+}
+
+/// Reverts the last applied rename, then returns rebuilt trees.
+#[tauri::command]
+fn c_undo_rename(
+    state: State<EditorRuntimeState>,
+    history_state: State<RefactorHistoryState>,
+    analysis_session_state: State<AnalysisSessionState>,
+) -> Result<Vec<RepositoryTree>, String> {
+    let mut history = history_state.0.lock();
+    let undo = history.last().ok_or(String::from("Nothing to undo"))?;
+    undo_rename(undo)?;
+    history.pop();
+
+    let mut editor_runtime_data = state.0.write();
+    editor_runtime_data.repository_trees = build_project_trees();
+    *analysis_session_state.0.write() = None;
+    Ok(editor_runtime_data.repository_trees.clone())
+}
+
+/// Writes results of the last analysis to `file_path`.
+/// Repositories are analyzed first if no analysis has run yet.
+#[tauri::command]
+fn c_export_report(
+    state: State<EditorRuntimeState>,
+    analysis_session_state: State<AnalysisSessionState>,
+    format: ReportFormat,
+    file_path: String,
+    show_suppressed: bool,
+) -> Result<(), String> {
+    let editor_runtime_data = state.0.read();
+    let mut analysis_session = analysis_session_state.0.write();
+    if analysis_session.is_none() {
+        *analysis_session = Some(AnalysisSession::analyze(&editor_runtime_data));
+    }
+
+    let report = Report::new(analysis_session.as_ref().unwrap().get_results(), show_suppressed);
+    write_report(&report, format, Path::new(&file_path))
+}
+
+/// Writes current hints to the baseline of every repository and analyzes again,
+/// so only hints that are not in the baselines are reported.
+#[tauri::command]
+fn c_write_baseline(
+    state: State<EditorRuntimeState>,
+    analysis_session_state: State<AnalysisSessionState>,
+) -> Result<Vec<FileAnalysisResult>, String> {
+    let editor_runtime_data = state.0.read();
+    let mut analysis_session = analysis_session_state.0.write();
+    if analysis_session.is_none() {
+        *analysis_session = Some(AnalysisSession::analyze(&editor_runtime_data));
+    }
+
+    let folder_paths: Vec<String> = editor_runtime_data
+        .repository_trees
+        .iter()
+        .map(|repository_tree| repository_tree.repository_info.folder_path.clone())
+        .collect();
+    write_baselines(&folder_paths, analysis_session.as_ref().unwrap().get_results())?;
+
+    let new_analysis_session = AnalysisSession::analyze(&editor_runtime_data);
+    if let Err(error) = save_analysis_cache(&editor_runtime_data.editor_env, &new_analysis_session.to_cache(&editor_runtime_data)) {
+        println!("{}", error);
+    }
+    let results = new_analysis_session.get_results().clone();
+    *analysis_session = Some(new_analysis_session);
+    Ok(results)
+}
 
 // найти свойства в json файлах со строковыми значениями.
 // если значение это относительный путь, то нужно проверить его на корректность.
@@ -193,5 +441,3 @@ fn c_analyze_repositories(state: State<EditorRuntimeState>) -> Vec<FileAnalysisR
 // узлы справочников могут хранить ссылки друг на друга, если
 // свойства в файлах ссылаются на другие файлы. Но думаю это лучше делать
 // отдельной мета-структурой.
-
-// Добавить авто-фикс проблем с путями.
@@ -1,2 +1,3 @@
 pub mod repository;
-pub mod repository_tree;
\ No newline at end of file
+pub mod repository_tree;
+pub mod repository_tree_snapshot;
\ No newline at end of file
@@ -27,7 +27,7 @@ impl Repository {
     }
 }
 
-const REPOSITORIES_FOLDER_NAME: &str = "repositories";
+pub const REPOSITORIES_FOLDER_NAME: &str = "repositories";
 
 pub fn find_repositories(folder: &PathBuf) -> Vec<Repository> {
     let mut repositories: Vec<Repository> = Vec::new();
@@ -119,18 +119,30 @@ pub fn get_repositories(editor: &EditorEnvironment) -> Vec<Repository> {
 
 // 0000000000000000000000000000000000000000000000000000000000
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct RepositoryInfo {
     /// Path to modinfo.json folder
     pub folder_path: String,
     pub mod_identifier: String,
+    /// Mount order of the mod. Mods with higher priority are mounted later
+    /// and shadow files of mods with lower priority.
+    #[serde(default = "default_mod_priority")]
+    pub mod_priority: i64,
+}
+
+/// Priority the game uses when modinfo.json has none.
+pub const DEFAULT_MOD_PRIORITY: i64 = 100;
+
+fn default_mod_priority() -> i64 {
+    DEFAULT_MOD_PRIORITY
 }
 
 impl RepositoryInfo {
-    pub fn new(folder_path: String, mod_identifier: String) -> Self {
+    pub fn new(folder_path: String, mod_identifier: String, mod_priority: i64) -> Self {
         Self {
             folder_path,
             mod_identifier,
+            mod_priority,
         }
     }
 }
@@ -142,12 +154,55 @@ impl From<Repository> for RepositoryInfo {
 }
 
 fn get_repository_info(repository: &Repository) -> RepositoryInfo {
-    // read modinfo.json and get "identifier" field
+    // read modinfo.json and get "identifier" field.
+    // Broken modinfo.json is reported by the modinfo analyzer, so it must not fail here
     let modinfo_file_path = repository.get_modinfo_file_path();
-    let file_content = std::fs::read_to_string(modinfo_file_path).unwrap();
-    let modinfo: Value = serde_json::from_str(&file_content).unwrap();
+    let modinfo: Value = std::fs::read_to_string(modinfo_file_path)
+        .ok()
+        .and_then(|file_content| serde_json::from_str(&file_content).ok())
+        .unwrap_or(Value::Null);
     RepositoryInfo::new(
         repository.folder_path.clone(),
-        modinfo["identifier"].to_string().replace('"', ""),
+        modinfo["identifier"].as_str().unwrap_or_default().to_string(),
+        modinfo["priority"].as_i64().unwrap_or(DEFAULT_MOD_PRIORITY),
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::test_folder::TestFolder;
+
+    use super::*;
+
+    fn get_info(modinfo: &str) -> RepositoryInfo {
+        let test_folder = TestFolder::new();
+        test_folder.write("/modinfo.json", modinfo);
+        RepositoryInfo::from(Repository::new(test_folder.path_string()))
+    }
+
+    #[test]
+    fn identifier_and_priority_are_read_from_modinfo() {
+        let repository_info = get_info(r#"{"identifier": "com.author.tank", "priority": 250}"#);
+
+        assert_eq!(repository_info.mod_identifier, "com.author.tank");
+        assert_eq!(repository_info.mod_priority, 250);
+    }
+
+    #[test]
+    fn priority_defaults_to_the_game_default() {
+        assert_eq!(get_info(r#"{"identifier": "com.author.tank"}"#).mod_priority, DEFAULT_MOD_PRIORITY);
+        assert_eq!(get_info(r#"{"priority": "high"}"#).mod_priority, DEFAULT_MOD_PRIORITY);
+
+        let repository_info = get_info("{\"identifier\": ");
+        assert_eq!(repository_info.mod_identifier, "");
+        assert_eq!(repository_info.mod_priority, DEFAULT_MOD_PRIORITY);
+    }
+
+    #[test]
+    fn priority_defaults_when_deserializing_older_repository_info() {
+        let repository_info: RepositoryInfo =
+            serde_json::from_str(r#"{"folder_path": "/mods/tank", "mod_identifier": "com.author.tank"}"#).unwrap();
+
+        assert_eq!(repository_info.mod_priority, DEFAULT_MOD_PRIORITY);
+    }
+}
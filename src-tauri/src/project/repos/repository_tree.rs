@@ -1,4 +1,9 @@
-use std::{collections::HashMap, path::Path};
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::Path,
+    time::UNIX_EPOCH,
+};
 
 use serde::{Deserialize, Serialize};
 use walkdir::WalkDir;
@@ -14,8 +19,13 @@ pub struct RelativePath{
 }
 
 impl RelativePath {
+    /// Normalizes `path` to the form used in json files: '/' separators and a leading '/'.
     pub fn new(path: String) -> Self {
-        Self { value: path.replace("\\", "/") }
+        let mut value = path.replace("\\", "/");
+        if !value.starts_with('/') {
+            value.insert(0, '/');
+        }
+        Self { value }
     }
 }
 
@@ -27,25 +37,94 @@ pub struct RepositoryTreeEntry {
     pub path: String,
     pub parent: Option<EntryID>,
     pub children: Vec<EntryID>,
+    #[serde(default)]
+    pub is_directory: bool,
+
+    /// Size in bytes, 0 for folders.
+    #[serde(default)]
+    pub size: u64,
+    /// Last modification time in nanoseconds since the Unix epoch.
+    #[serde(default)]
+    pub modified: u64,
+    /// xxh3 hash of the content, 0 for folders, files that are not analyzed and files that were not hashed yet.
+    #[serde(default)]
+    pub content_hash: u64,
 }
 
 impl RepositoryTreeEntry {
-    pub fn new(id: EntryID, parent: Option<EntryID>, path: String) -> Self {
+    pub fn new(id: EntryID, parent: Option<EntryID>, path: String, is_directory: bool) -> Self {
         Self {
             id,
             path,
             parent,
             children: Vec::new(),
+            is_directory,
+            size: 0,
+            modified: 0,
+            content_hash: 0,
         }
     }
+
+    /// Reads size and modification time from the disk. The content hash is reset if either changed.
+    /// Returns false if nothing changed or the file can't be read.
+    pub fn update_metadata(&mut self) -> bool {
+        let metadata = fs::metadata(&self.path);
+        if metadata.is_err() {
+            return false;
+        }
+        let metadata = metadata.unwrap();
+
+        let size = if self.is_directory { 0 } else { metadata.len() };
+        let modified = metadata
+            .modified()
+            .ok()
+            .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+            .map(|modified| modified.as_nanos() as u64)
+            .unwrap_or_default();
+        if size == self.size && modified == self.modified {
+            return false;
+        }
+
+        self.size = size;
+        self.modified = modified;
+        self.content_hash = 0;
+        true
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(from = "SerializedRepositoryTree")]
 pub struct RepositoryTree {
     pub entries: Vec<RepositoryTreeEntry>,
     pub last_generated_id: EntryID,
 
     pub repository_info: RepositoryInfo,
+
+    /// Entry by its relative path. Filled by `add_entry`
+    /// and rebuilt when the tree is deserialized.
+    #[serde(skip)]
+    path_index: HashMap<RelativePath, EntryID>,
+    /// Position of entry in `entries` by its id.
+    #[serde(skip)]
+    entry_positions: HashMap<EntryID, usize>,
+}
+
+/// Serialized fields of `RepositoryTree`, the indices are rebuilt from them.
+#[derive(Deserialize)]
+struct SerializedRepositoryTree {
+    entries: Vec<RepositoryTreeEntry>,
+    last_generated_id: EntryID,
+    repository_info: RepositoryInfo,
+}
+
+impl From<SerializedRepositoryTree> for RepositoryTree {
+    fn from(serialized: SerializedRepositoryTree) -> RepositoryTree {
+        let mut repository_tree = RepositoryTree::new(serialized.repository_info);
+        repository_tree.entries = serialized.entries;
+        repository_tree.last_generated_id = serialized.last_generated_id;
+        repository_tree.rebuild_index();
+        repository_tree
+    }
 }
 
 impl RepositoryTree {
@@ -54,6 +133,8 @@ impl RepositoryTree {
             entries: Vec::new(),
             last_generated_id: EntryID(0),
             repository_info,
+            path_index: HashMap::new(),
+            entry_positions: HashMap::new(),
         }
     }
 
@@ -65,16 +146,236 @@ impl RepositoryTree {
     pub fn add_entry(&mut self, entry: &mut RepositoryTreeEntry) {
         let id = self.generate_id();
         entry.id = id;
+        self.index_entry(entry, self.entries.len());
         self.entries.push(entry.clone());
         // add entry to parent children
         if let Some(parent_id) = entry.parent {
-            self.entries
-                .iter_mut()
-                .find(|entry| entry.id == parent_id)
+            let parent_position = self.entry_positions[&parent_id];
+            self.entries[parent_position].children.push(id);
+        }
+    }
+
+    pub fn get_entry(&self, id: EntryID) -> Option<&RepositoryTreeEntry> {
+        let position = self.entry_positions.get(&id)?;
+        self.entries.get(*position)
+    }
+
+    pub fn find_entry(&self, relative_path: &RelativePath) -> Option<&RepositoryTreeEntry> {
+        let id = self.path_index.get(relative_path)?;
+        self.get_entry(*id)
+    }
+
+    fn get_entry_mut(&mut self, id: EntryID) -> Option<&mut RepositoryTreeEntry> {
+        let position = self.entry_positions.get(&id)?;
+        self.entries.get_mut(*position)
+    }
+
+    /// Reads metadata of the entry at `path` from the disk, see `RepositoryTreeEntry::update_metadata`.
+    pub fn update_path_metadata(&mut self, path: &Path) -> bool {
+        let id = self
+            .get_relative_path(path)
+            .and_then(|relative_path| self.path_index.get(&relative_path).copied());
+        match id.and_then(|id| self.get_entry_mut(id)) {
+            Some(entry) => entry.update_metadata(),
+            None => false,
+        }
+    }
+
+    /// Brings the tree in line with the disk after it was loaded from a snapshot.
+    /// Only folders whose modification time changed are listed again,
+    /// files elsewhere just get their metadata refreshed. Returns true if anything changed.
+    pub fn reconcile(&mut self) -> bool {
+        let mut is_changed = false;
+
+        let folder_ids: Vec<EntryID> = self
+            .entries
+            .iter()
+            .filter(|entry| entry.is_directory)
+            .map(|entry| entry.id)
+            .collect();
+        for folder_id in folder_ids {
+            // folder was removed together with its parent
+            let folder_path = match self.get_entry(folder_id) {
+                Some(folder) => folder.path.clone(),
+                None => continue,
+            };
+            if !Path::new(&folder_path).is_dir() {
+                is_changed |= self.remove_path(Path::new(&folder_path));
+                continue;
+            }
+            if !self.get_entry_mut(folder_id).unwrap().update_metadata() {
+                continue;
+            }
+            is_changed = true;
+
+            let disk_paths: HashSet<String> = WalkDir::new(&folder_path)
+                .min_depth(1)
+                .max_depth(1)
+                .into_iter()
+                .flatten()
+                .map(|child| Path::new(&folder_path).join(child.file_name()).to_str().unwrap().to_string())
+                .collect();
+            let tree_paths: Vec<String> = self
+                .get_entry(folder_id)
                 .unwrap()
                 .children
-                .push(id);
+                .iter()
+                .filter_map(|child_id| self.get_entry(*child_id))
+                .map(|child| child.path.clone())
+                .collect();
+
+            let removed_paths: Vec<&Path> = tree_paths
+                .iter()
+                .filter(|tree_path| !disk_paths.contains(*tree_path))
+                .map(Path::new)
+                .collect();
+            self.remove_paths(&removed_paths);
+            for disk_path in &disk_paths {
+                if !tree_paths.contains(disk_path) {
+                    self.add_path(Path::new(disk_path));
+                }
+            }
+        }
+
+        for entry in &mut self.entries {
+            if !entry.is_directory {
+                is_changed |= entry.update_metadata();
+            }
+        }
+        is_changed
+    }
+
+    /// Adds entries for `path`, its missing parent folders and, for a folder, everything inside it.
+    /// Returns false if `path` is outside of the repository or already has an entry.
+    pub fn add_path(&mut self, path: &Path) -> bool {
+        let relative_path = match self.get_relative_path(path) {
+            Some(relative_path) => relative_path,
+            None => return false,
+        };
+        if self.path_index.contains_key(&relative_path) {
+            return false;
+        }
+
+        let parent_id = match path.parent() {
+            Some(parent_path) => {
+                self.add_path(parent_path);
+                let parent_relative_path = self.get_relative_path(parent_path);
+                parent_relative_path.and_then(|parent_relative_path| self.path_index.get(&parent_relative_path).copied())
+            }
+            None => None,
+        };
+        if parent_id.is_none() {
+            return false;
+        }
+        // a missing parent folder is added with everything inside it
+        if self.path_index.contains_key(&relative_path) {
+            return true;
+        }
+
+        let is_directory = path.is_dir();
+        let mut entry = RepositoryTreeEntry::new(
+            EntryID(-1),
+            parent_id,
+            path.to_str().unwrap().to_string(),
+            is_directory,
+        );
+        entry.update_metadata();
+        self.add_entry(&mut entry);
+
+        // a folder moved into the repository is reported as a single path
+        if is_directory {
+            for child in WalkDir::new(path).min_depth(1).into_iter().flatten() {
+                self.add_path(child.path());
+            }
         }
+        true
+    }
+
+    /// Removes the entry of `path` and all entries inside it.
+    /// Returns false if `path` has no entry or is the repository folder itself.
+    pub fn remove_path(&mut self, path: &Path) -> bool {
+        self.remove_paths(&[path])
+    }
+
+    /// Removes entries of `paths` and all entries inside them in a single pass over the entries.
+    /// Returns false if none of `paths` has an entry besides the repository folder itself.
+    pub fn remove_paths(&mut self, paths: &[&Path]) -> bool {
+        let mut removed_ids: HashSet<EntryID> = HashSet::new();
+        for path in paths {
+            let id = self
+                .get_relative_path(path)
+                .and_then(|relative_path| self.path_index.get(&relative_path).copied());
+            let id = match id {
+                Some(id) => id,
+                None => continue,
+            };
+            let parent_id = match self.get_entry(id).and_then(|entry| entry.parent) {
+                Some(parent_id) => parent_id,
+                None => continue,
+            };
+            let parent_position = self.entry_positions[&parent_id];
+            self.entries[parent_position].children.retain(|child_id| *child_id != id);
+
+            let mut queue: Vec<EntryID> = vec![id];
+            while let Some(removed_id) = queue.pop() {
+                if !removed_ids.insert(removed_id) {
+                    continue;
+                }
+                if let Some(entry) = self.get_entry(removed_id) {
+                    queue.extend(entry.children.iter().copied());
+                }
+            }
+        }
+        if removed_ids.is_empty() {
+            return false;
+        }
+
+        let first_position = removed_ids.iter().map(|id| self.entry_positions[id]).min().unwrap();
+        let removed_paths: Vec<RelativePath> = self.entries[first_position..]
+            .iter()
+            .filter(|entry| removed_ids.contains(&entry.id))
+            .map(|entry| get_entry_relative_path(self, entry))
+            .collect();
+        for relative_path in &removed_paths {
+            self.path_index.remove(relative_path);
+        }
+        for id in &removed_ids {
+            self.entry_positions.remove(id);
+        }
+
+        self.entries.retain(|entry| !removed_ids.contains(&entry.id));
+        // entries before the first removed one keep their positions
+        for position in first_position..self.entries.len() {
+            self.entry_positions.insert(self.entries[position].id, position);
+        }
+        true
+    }
+
+    /// `None` if `path` is not inside the repository folder.
+    fn get_relative_path(&self, path: &Path) -> Option<RelativePath> {
+        let path = path.to_str()?;
+        let relative_path = path.strip_prefix(&self.repository_info.folder_path)?;
+        if !relative_path.is_empty() && !relative_path.starts_with(['/', '\\']) {
+            return None;
+        }
+        Some(RelativePath::new(relative_path.to_string()))
+    }
+
+    fn rebuild_index(&mut self) {
+        self.path_index.clear();
+        self.entry_positions.clear();
+
+        let entries = std::mem::take(&mut self.entries);
+        for (position, entry) in entries.iter().enumerate() {
+            self.index_entry(entry, position);
+        }
+        self.entries = entries;
+    }
+
+    fn index_entry(&mut self, entry: &RepositoryTreeEntry, position: usize) {
+        self.path_index
+            .insert(get_entry_relative_path(self, entry), entry.id);
+        self.entry_positions.insert(entry.id, position);
     }
 }
 
@@ -98,7 +399,9 @@ fn build_repository_tree_recursive(
         EntryID(-1),
         parent_id,
         tree_entry_path.to_str().unwrap().to_string(),
+        true,
     );
+    tree_entry.update_metadata();
 
     repository_tree.add_entry(&mut tree_entry);
 
@@ -126,7 +429,9 @@ fn build_repository_tree_recursive(
                 EntryID(-1),
                 Some(tree_entry.id),
                 entry_path.to_str().unwrap().to_string(),
+                false,
             );
+            file_tree_entry.update_metadata();
             repository_tree.add_entry(&mut file_tree_entry);
         } else {
             // do nothing
@@ -154,14 +459,171 @@ pub fn find_repository_entry(
     repository_trees: &Vec<RepositoryTree>,
 ) -> Option<(RepositoryInfo, RepositoryTreeEntry)> {
     for repository_tree in repository_trees {
-        
+        if let Some(entry) = repository_tree.find_entry(search_relative_path) {
+            return Some((repository_tree.repository_info.clone(), entry.clone()));
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_folder::TestFolder;
+
+    use super::*;
+
+    fn create_tree(test_folder: &TestFolder) -> RepositoryTree {
+        test_folder.write("/modinfo.json", "{\"identifier\": \"com.tank\"}");
+        test_folder.write("/pa/units/tank.json", "{}");
+        build_repository_tree(&Repository::new(test_folder.path_string()))
+    }
+
+    #[test]
+    fn relative_paths_are_normalized() {
+        assert_eq!(RelativePath::new(String::from("pa\\units\\tank.json")).value, "/pa/units/tank.json");
+        assert_eq!(RelativePath::new(String::from("/pa/units/tank.json")).value, "/pa/units/tank.json");
+        assert_eq!(RelativePath::new(String::new()).value, "/");
+    }
+
+    #[test]
+    fn entries_are_found_by_relative_path() {
+        let test_folder = TestFolder::new();
+        let repository_tree = create_tree(&test_folder);
+
+        let entry = repository_tree
+            .find_entry(&RelativePath::new(String::from("pa\\units\\tank.json")))
+            .unwrap();
+        assert!(!entry.is_directory);
+        assert_eq!(entry.size, 2);
+        let parent = repository_tree.get_entry(entry.parent.unwrap()).unwrap();
+        assert_eq!(get_entry_relative_path(&repository_tree, parent).value, "/pa/units");
+        assert!(parent.children.contains(&entry.id));
+        assert!(repository_tree
+            .find_entry(&RelativePath::new(String::from("/pa/units/missing.json")))
+            .is_none());
+    }
+
+    #[test]
+    fn repository_info_is_read_from_modinfo() {
+        let test_folder = TestFolder::new();
+        let repository_tree = create_tree(&test_folder);
+
+        let found = find_repository_entry(
+            &RelativePath::new(String::from("/modinfo.json")),
+            &vec![repository_tree],
+        );
+        let (repository_info, entry) = found.unwrap();
+        assert_eq!(repository_info.mod_identifier, "com.tank");
+        assert_eq!(entry.path, test_folder.path().join("modinfo.json").to_str().unwrap());
+    }
+
+    /// Relative paths of every entry, checked against the index and the children of their parents.
+    fn get_indexed_paths(repository_tree: &RepositoryTree) -> Vec<String> {
+        let mut relative_paths: Vec<String> = Vec::new();
         for entry in &repository_tree.entries {
-            let entry_relative_path = get_entry_relative_path(repository_tree, entry);
-            if (entry_relative_path == *search_relative_path) {
-                return Some((repository_tree.repository_info.clone(), entry.clone()));
+            let relative_path = get_entry_relative_path(repository_tree, entry);
+            assert_eq!(repository_tree.find_entry(&relative_path).unwrap().id, entry.id);
+            if let Some(parent_id) = entry.parent {
+                assert!(repository_tree.get_entry(parent_id).unwrap().children.contains(&entry.id));
             }
+            relative_paths.push(relative_path.value);
         }
+        assert_eq!(repository_tree.path_index.len(), repository_tree.entries.len());
+        assert_eq!(repository_tree.entry_positions.len(), repository_tree.entries.len());
+        relative_paths.sort();
+        relative_paths
     }
 
-    None
+    fn find(repository_tree: &RepositoryTree, relative_path: &str) -> Option<EntryID> {
+        repository_tree
+            .find_entry(&RelativePath::new(relative_path.to_string()))
+            .map(|entry| entry.id)
+    }
+
+    #[test]
+    fn added_paths_bring_their_parents_and_contents() {
+        let test_folder = TestFolder::new();
+        let mut repository_tree = create_tree(&test_folder);
+        test_folder.write("/pa/units/land/tank/tank.json", "{}");
+        test_folder.write("/pa/units/land/tank/tank.papa", "");
+        test_folder.write("/pa/ammo/shell.json", "{}");
+
+        assert!(repository_tree.add_path(&test_folder.path().join("pa/units/land")));
+        assert!(repository_tree.add_path(&test_folder.path().join("pa/ammo/shell.json")));
+        assert!(!repository_tree.add_path(&test_folder.path().join("pa/ammo/shell.json")));
+        assert!(!repository_tree.add_path(&std::env::temp_dir().join("shell.json")));
+
+        assert_eq!(
+            get_indexed_paths(&repository_tree),
+            vec![
+                "/",
+                "/modinfo.json",
+                "/pa",
+                "/pa/ammo",
+                "/pa/ammo/shell.json",
+                "/pa/units",
+                "/pa/units/land",
+                "/pa/units/land/tank",
+                "/pa/units/land/tank/tank.json",
+                "/pa/units/land/tank/tank.papa",
+                "/pa/units/tank.json",
+            ]
+        );
+    }
+
+    #[test]
+    fn removed_paths_take_their_contents_along() {
+        let test_folder = TestFolder::new();
+        test_folder.write("/pa/units/land/tank/tank.json", "{}");
+        test_folder.write("/pa/units/land/bot.json", "{}");
+        test_folder.write("/pa/ammo/shell.json", "{}");
+        let mut repository_tree = create_tree(&test_folder);
+        let modinfo_id = find(&repository_tree, "/modinfo.json");
+
+        assert!(repository_tree.remove_path(&test_folder.path().join("pa/units/land")));
+        assert!(!repository_tree.remove_path(&test_folder.path().join("pa/units/land/bot.json")));
+        assert!(!repository_tree.remove_path(test_folder.path()));
+        let removed_paths = [test_folder.path().join("pa/ammo"), test_folder.path().join("pa/ammo/shell.json")];
+        assert!(repository_tree.remove_paths(&[&removed_paths[0], &removed_paths[1]]));
+
+        assert_eq!(get_indexed_paths(&repository_tree), vec!["/", "/modinfo.json", "/pa", "/pa/units", "/pa/units/tank.json"]);
+        assert_eq!(find(&repository_tree, "/modinfo.json"), modinfo_id);
+    }
+
+    #[test]
+    fn reconciled_tree_matches_a_new_tree() {
+        let test_folder = TestFolder::new();
+        test_folder.write("/pa/units/bot.json", "{}");
+        test_folder.write("/pa/ammo/shell.json", "{}");
+        let mut repository_tree = create_tree(&test_folder);
+        let tank_id = find(&repository_tree, "/pa/units/tank.json");
+
+        // folder modification times must change, so the folders are listed again
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        fs::remove_file(test_folder.path().join("pa/units/bot.json")).unwrap();
+        fs::remove_dir_all(test_folder.path().join("pa/ammo")).unwrap();
+        test_folder.write("/pa/units/land/tank.json", "{}");
+        test_folder.write("/pa/units/tank.json", "{\"base_spec\": \"\"}");
+
+        assert!(repository_tree.reconcile());
+        let new_tree = build_repository_tree(&Repository::new(test_folder.path_string()));
+        assert_eq!(get_indexed_paths(&repository_tree), get_indexed_paths(&new_tree));
+        let tank = repository_tree.find_entry(&RelativePath::new(String::from("/pa/units/tank.json"))).unwrap();
+        assert_eq!(Some(tank.id), tank_id);
+        assert_eq!(tank.size, 17);
+        assert!(!repository_tree.reconcile());
+    }
+
+    #[test]
+    fn deserialized_tree_finds_entries() {
+        let test_folder = TestFolder::new();
+        let repository_tree = create_tree(&test_folder);
+        let serialized = serde_json::to_string(&repository_tree).unwrap();
+
+        let repository_tree: RepositoryTree = serde_json::from_str(&serialized).unwrap();
+        assert!(repository_tree
+            .find_entry(&RelativePath::new(String::from("/pa/units/tank.json")))
+            .is_some());
+    }
 }
@@ -0,0 +1,136 @@
+use std::fs;
+
+use rayon::prelude::*;
+
+use crate::{
+    analyzers::analysis_cache::{get_content_hash, is_hashed_file},
+    editor::editor::EditorEnvironment,
+};
+
+use super::{
+    repository::{Repository, RepositoryInfo, REPOSITORIES_FOLDER_NAME},
+    repository_tree::{build_repository_tree, RepositoryTree},
+};
+
+/// Stored next to `repositories.json`.
+const REPOSITORY_TREES_FILE_NAME: &str = "repository_trees.json";
+
+/// Trees of `repositories`. Snapshots saved by `save_repository_trees` are reconciled with the disk,
+/// repositories without a snapshot are walked from scratch.
+pub fn get_repository_trees(editor: &EditorEnvironment, repositories: &[Repository]) -> Vec<RepositoryTree> {
+    let mut snapshots = load_repository_trees(editor);
+    let mut repository_trees: Vec<RepositoryTree> = Vec::new();
+
+    for repository in repositories {
+        let snapshot_position = snapshots
+            .iter()
+            .position(|snapshot| snapshot.repository_info.folder_path == repository.folder_path);
+        let mut repository_tree = match snapshot_position {
+            Some(snapshot_position) => {
+                let mut repository_tree = snapshots.swap_remove(snapshot_position);
+                repository_tree.reconcile();
+                // modinfo.json may have changed while the editor was closed
+                repository_tree.repository_info = RepositoryInfo::from(repository.clone());
+                repository_tree
+            }
+            None => build_repository_tree(repository),
+        };
+        update_content_hashes(&mut repository_tree);
+        repository_trees.push(repository_tree);
+    }
+
+    repository_trees
+}
+
+/// Hashes analyzed files whose content hash was never computed or was reset
+/// because their size or modification time changed. Other files are never read.
+pub fn update_content_hashes(repository_tree: &mut RepositoryTree) {
+    repository_tree
+        .entries
+        .par_iter_mut()
+        .filter(|entry| !entry.is_directory && entry.content_hash == 0 && is_hashed_file(&entry.path))
+        .for_each(|entry| entry.content_hash = get_content_hash(&entry.path).unwrap_or_default());
+}
+
+/// Saved snapshots, their indices are rebuilt on deserialization. Empty if there are none or they can't be read.
+pub fn load_repository_trees(editor: &EditorEnvironment) -> Vec<RepositoryTree> {
+    let repositories_folder = editor.get_or_create_folder(REPOSITORIES_FOLDER_NAME);
+    if repositories_folder.is_err() {
+        return Vec::new();
+    }
+
+    let file_content = fs::read_to_string(repositories_folder.unwrap().join(REPOSITORY_TREES_FILE_NAME));
+    if file_content.is_err() {
+        return Vec::new();
+    }
+    serde_json::from_str(&file_content.unwrap()).unwrap_or_default()
+}
+
+pub fn save_repository_trees(editor: &EditorEnvironment, repository_trees: &[RepositoryTree]) -> Result<(), String> {
+    let repositories_folder = editor.get_or_create_folder(REPOSITORIES_FOLDER_NAME)?;
+    let file_content = serde_json::to_string(repository_trees);
+    if file_content.is_err() {
+        return Err(String::from("Could not serialize repository trees"));
+    }
+
+    if fs::write(repositories_folder.join(REPOSITORY_TREES_FILE_NAME), file_content.unwrap()).is_err() {
+        return Err(String::from("Could not write repository trees"));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        analyzers::analysis_cache::get_content_hash, project::repos::repository_tree::RelativePath,
+        test_folder::TestFolder,
+    };
+
+    use super::*;
+
+    fn get_tank_trees(test_folder: &TestFolder, editor: &EditorEnvironment) -> Vec<RepositoryTree> {
+        let repositories = vec![Repository::new(test_folder.path().join("tank").to_string_lossy().to_string())];
+        get_repository_trees(editor, &repositories)
+    }
+
+    fn get_content_hash_of(repository_tree: &RepositoryTree, relative_path: &str) -> Option<u64> {
+        repository_tree
+            .find_entry(&RelativePath::new(relative_path.to_string()))
+            .map(|entry| entry.content_hash)
+    }
+
+    #[test]
+    fn saved_trees_are_reconciled_with_the_disk() {
+        let test_folder = TestFolder::new();
+        test_folder.write("/tank/modinfo.json", r#"{"identifier": "com.tank"}"#);
+        let tank_path = test_folder.write("/tank/pa/units/tank.json", "{}");
+        let editor = test_folder.create_runtime_data(&[]).editor_env;
+        save_repository_trees(&editor, &get_tank_trees(&test_folder, &editor)).unwrap();
+
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        test_folder.write("/tank/modinfo.json", r#"{"identifier": "com.tank", "priority": 200}"#);
+        test_folder.write("/tank/pa/units/tank.json", r#"{"base_spec": "/pa/units/base.json"}"#);
+        let base_path = test_folder.write("/tank/pa/units/base.json", "{}");
+
+        assert_eq!(load_repository_trees(&editor).len(), 1);
+        let repository_tree = &get_tank_trees(&test_folder, &editor)[0];
+        assert_eq!(repository_tree.repository_info.mod_priority, 200);
+        assert_eq!(get_content_hash_of(repository_tree, "/pa/units/tank.json"), get_content_hash(&tank_path));
+        assert_eq!(get_content_hash_of(repository_tree, "/pa/units/base.json"), get_content_hash(&base_path));
+    }
+
+    #[test]
+    fn unreadable_snapshots_are_built_from_scratch() {
+        let test_folder = TestFolder::new();
+        test_folder.write("/tank/modinfo.json", r#"{"identifier": "com.tank"}"#);
+        test_folder.write("/tank/pa/units/tank.json", "{}");
+        let editor = test_folder.create_runtime_data(&[]).editor_env;
+        let repositories_folder = editor.get_or_create_folder(REPOSITORIES_FOLDER_NAME).unwrap();
+        fs::write(repositories_folder.join(REPOSITORY_TREES_FILE_NAME), "[{").unwrap();
+
+        assert!(load_repository_trees(&editor).is_empty());
+        let repository_trees = get_tank_trees(&test_folder, &editor);
+        assert_eq!(repository_trees.len(), 1);
+        assert!(get_content_hash_of(&repository_trees[0], "/pa/units/tank.json").is_some());
+    }
+}
@@ -0,0 +1 @@
+pub mod reference_graph;
\ No newline at end of file
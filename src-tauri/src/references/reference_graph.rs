@@ -0,0 +1,348 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    project::repos::repository_tree::RelativePath,
+    vfs::virtual_file_system::{get_layer_file_path, Layer},
+};
+
+/// File of a single layer of the virtual file system.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FileLocation {
+    pub relative_path: RelativePath,
+    pub layer_index: usize,
+    pub layer_name: String,
+    /// Path on disk, `None` for images without a folder.
+    pub file_path: Option<String>,
+}
+
+impl FileLocation {
+    pub fn new(layer: &Layer, relative_path: &RelativePath) -> FileLocation {
+        FileLocation {
+            relative_path: relative_path.clone(),
+            layer_index: layer.index,
+            layer_name: layer.name.clone(),
+            file_path: get_layer_file_path(layer, relative_path)
+                .map(|file_path| file_path.to_string_lossy().to_string()),
+        }
+    }
+
+    fn get_key(&self) -> FileKey {
+        FileKey {
+            layer_index: self.layer_index,
+            relative_path: self.relative_path.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct FileKey {
+    layer_index: usize,
+    relative_path: RelativePath,
+}
+
+/// Property of `source` that resolves to `target`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ReferenceEdge {
+    pub source: FileLocation,
+    pub target: FileLocation,
+    /// JSON Pointer of the property in `source`.
+    pub pointer: String,
+    /// Value of the property as written in `source`.
+    pub value: String,
+}
+
+/// Both directions of references of a single file.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FileReferences {
+    pub file: Option<FileLocation>,
+    /// Files this file references.
+    pub references: Vec<ReferenceEdge>,
+    /// Files that reference this file.
+    pub referenced_by: Vec<ReferenceEdge>,
+}
+
+/// Resolved file to file references found in json files of repositories.
+/// Targets are the files the game loads, so a shadowed file is never referenced.
+#[derive(Debug, Clone, Default)]
+pub struct ReferenceGraph {
+    edges: Vec<ReferenceEdge>,
+    /// Edge indices by source file.
+    outgoing: HashMap<FileKey, Vec<usize>>,
+    /// Edge indices by target file.
+    incoming: HashMap<FileKey, Vec<usize>>,
+    /// Edge indices by target path in any layer.
+    incoming_by_path: HashMap<RelativePath, Vec<usize>>,
+    /// Files with references to paths no layer provides, by the missing path.
+    /// Creating such a file changes their hints.
+    unresolved: HashMap<RelativePath, Vec<FileLocation>>,
+}
+
+impl ReferenceGraph {
+    pub fn new() -> ReferenceGraph {
+        ReferenceGraph::default()
+    }
+
+    pub fn add_edge(&mut self, edge: ReferenceEdge) {
+        let index = self.edges.len();
+        self.outgoing.entry(edge.source.get_key()).or_default().push(index);
+        self.incoming.entry(edge.target.get_key()).or_default().push(index);
+        self.incoming_by_path
+            .entry(edge.target.relative_path.clone())
+            .or_default()
+            .push(index);
+        self.edges.push(edge);
+    }
+
+    pub fn add_edges(&mut self, edges: Vec<ReferenceEdge>) {
+        for edge in edges {
+            self.add_edge(edge);
+        }
+    }
+
+    pub fn add_unresolved(&mut self, source: FileLocation, relative_path: RelativePath) {
+        let sources = self.unresolved.entry(relative_path).or_default();
+        if !sources.contains(&source) {
+            sources.push(source);
+        }
+    }
+
+    /// Drops edges and unresolved references of files for which `is_removed` returns true.
+    pub fn remove_sources<F: Fn(&FileLocation) -> bool>(&mut self, is_removed: F) {
+        let edges = std::mem::take(&mut self.edges);
+        self.outgoing.clear();
+        self.incoming.clear();
+        self.incoming_by_path.clear();
+        for edge in edges {
+            if !is_removed(&edge.source) {
+                self.add_edge(edge);
+            }
+        }
+
+        for sources in self.unresolved.values_mut() {
+            sources.retain(|source| !is_removed(source));
+        }
+        self.unresolved.retain(|_, sources| !sources.is_empty());
+    }
+
+    /// Files whose analysis depends on `relative_path` in any layer: files referencing it,
+    /// files with references to it that did not resolve, and recursively their dependents.
+    pub fn get_dependents(&self, relative_path: &RelativePath) -> HashSet<FileLocation> {
+        let mut dependents: HashSet<FileLocation> = HashSet::new();
+        let mut queue: Vec<RelativePath> = vec![relative_path.clone()];
+        while let Some(path) = queue.pop() {
+            let sources = self
+                .incoming_by_path
+                .get(&path)
+                .into_iter()
+                .flatten()
+                .map(|index| &self.edges[*index].source)
+                .chain(self.unresolved.get(&path).into_iter().flatten());
+            for source in sources {
+                if dependents.insert(source.clone()) {
+                    queue.push(source.relative_path.clone());
+                }
+            }
+        }
+        dependents
+    }
+
+    /// Files with any reference that did not resolve.
+    pub fn get_unresolved_sources(&self) -> HashSet<FileLocation> {
+        self.unresolved.values().flatten().cloned().collect()
+    }
+
+    /// Paths that did not resolve and files referencing them.
+    pub fn get_unresolved(&self) -> &HashMap<RelativePath, Vec<FileLocation>> {
+        &self.unresolved
+    }
+
+    pub fn get_edges(&self) -> &Vec<ReferenceEdge> {
+        &self.edges
+    }
+
+    /// What `layer` file at `relative_path` references, ordered by pointer.
+    pub fn get_references(&self, layer: &Layer, relative_path: &RelativePath) -> Vec<&ReferenceEdge> {
+        let mut edges = self.get_edges_by_key(&self.outgoing, layer, relative_path);
+        edges.sort_by(|a, b| a.pointer.cmp(&b.pointer));
+        edges
+    }
+
+    /// Who references `layer` file at `relative_path`, ordered by source path and pointer.
+    pub fn get_referenced_by(&self, layer: &Layer, relative_path: &RelativePath) -> Vec<&ReferenceEdge> {
+        let mut edges = self.get_edges_by_key(&self.incoming, layer, relative_path);
+        edges.sort_by(|a, b| {
+            (&a.source.relative_path.value, a.source.layer_index, &a.pointer).cmp(&(
+                &b.source.relative_path.value,
+                b.source.layer_index,
+                &b.pointer,
+            ))
+        });
+        edges
+    }
+
+    /// Files reachable from `roots` by following references, roots included.
+    pub fn get_reachable_files(&self, roots: Vec<FileLocation>) -> HashSet<FileLocation> {
+        let mut reachable: HashSet<FileLocation> = HashSet::new();
+        let mut queue: Vec<FileLocation> = roots;
+        while let Some(file) = queue.pop() {
+            if reachable.contains(&file) {
+                continue;
+            }
+            if let Some(indices) = self.outgoing.get(&file.get_key()) {
+                for index in indices {
+                    queue.push(self.edges[*index].target.clone());
+                }
+            }
+            reachable.insert(file);
+        }
+        reachable
+    }
+
+    pub fn get_file_references(&self, layer: &Layer, relative_path: &RelativePath) -> FileReferences {
+        FileReferences {
+            file: Some(FileLocation::new(layer, relative_path)),
+            references: self.get_references(layer, relative_path).into_iter().cloned().collect(),
+            referenced_by: self
+                .get_referenced_by(layer, relative_path)
+                .into_iter()
+                .cloned()
+                .collect(),
+        }
+    }
+
+    fn get_edges_by_key(
+        &self,
+        edges_by_key: &HashMap<FileKey, Vec<usize>>,
+        layer: &Layer,
+        relative_path: &RelativePath,
+    ) -> Vec<&ReferenceEdge> {
+        let key = FileKey {
+            layer_index: layer.index,
+            relative_path: relative_path.clone(),
+        };
+        match edges_by_key.get(&key) {
+            Some(indices) => indices.iter().map(|index| &self.edges[*index]).collect(),
+            None => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::vfs::virtual_file_system::LayerSource;
+
+    use super::*;
+
+    fn layer(index: usize) -> Layer {
+        Layer {
+            index,
+            name: format!("layer{}", index),
+            source: LayerSource::Image {
+                name: format!("layer{}", index),
+                folder_path: None,
+            },
+        }
+    }
+
+    fn path(value: &str) -> RelativePath {
+        RelativePath::new(value.to_string())
+    }
+
+    fn file(layer_index: usize, relative_path: &str) -> FileLocation {
+        FileLocation::new(&layer(layer_index), &path(relative_path))
+    }
+
+    fn edge(source: FileLocation, target: FileLocation, pointer: &str) -> ReferenceEdge {
+        let value = target.relative_path.value.clone();
+        ReferenceEdge {
+            source,
+            target,
+            pointer: pointer.to_string(),
+            value,
+        }
+    }
+
+    /// unit_list -> tank -> tank weapon -> tank ammo, bot -> tank weapon.
+    fn create_graph() -> ReferenceGraph {
+        let mut graph = ReferenceGraph::new();
+        graph.add_edges(vec![
+            edge(file(0, "/pa/units/unit_list.json"), file(0, "/pa/units/tank.json"), "/units/0"),
+            edge(file(1, "/pa/units/tank.json"), file(0, "/pa/units/tank_weapon.json"), "/tools/0/spec_id"),
+            edge(file(0, "/pa/units/tank_weapon.json"), file(0, "/pa/units/tank_ammo.json"), "/ammo_id"),
+            edge(file(0, "/pa/units/bot.json"), file(0, "/pa/units/tank_weapon.json"), "/tools/1/spec_id"),
+            edge(file(0, "/pa/units/bot.json"), file(0, "/pa/units/tank_weapon.json"), "/tools/0/spec_id"),
+        ]);
+        graph.add_unresolved(file(0, "/pa/units/bot.json"), path("/pa/units/bot_ammo.json"));
+        graph.add_unresolved(file(0, "/pa/units/bot.json"), path("/pa/units/bot_ammo.json"));
+        graph
+    }
+
+    fn get_paths(files: HashSet<FileLocation>) -> Vec<String> {
+        let mut paths: Vec<String> = files
+            .into_iter()
+            .map(|file| format!("{}{}", file.layer_name, file.relative_path.value))
+            .collect();
+        paths.sort();
+        paths
+    }
+
+    #[test]
+    fn references_are_queried_in_both_directions_by_layer() {
+        let graph = create_graph();
+        let weapon = path("/pa/units/tank_weapon.json");
+
+        let pointers: Vec<&str> = graph
+            .get_references(&layer(0), &path("/pa/units/bot.json"))
+            .iter()
+            .map(|edge| edge.pointer.as_str())
+            .collect();
+        assert_eq!(pointers, vec!["/tools/0/spec_id", "/tools/1/spec_id"]);
+
+        let sources: Vec<(&str, usize)> = graph
+            .get_referenced_by(&layer(0), &weapon)
+            .iter()
+            .map(|edge| (edge.source.relative_path.value.as_str(), edge.source.layer_index))
+            .collect();
+        assert_eq!(
+            sources,
+            vec![("/pa/units/bot.json", 0), ("/pa/units/bot.json", 0), ("/pa/units/tank.json", 1)]
+        );
+        assert!(graph.get_referenced_by(&layer(1), &weapon).is_empty());
+        assert!(graph.get_references(&layer(0), &path("/pa/units/tank.json")).is_empty());
+    }
+
+    #[test]
+    fn dependents_include_unresolved_and_indirect_references() {
+        let graph = create_graph();
+
+        // unit_list references tank.json of another layer, paths match in any layer
+        assert_eq!(
+            get_paths(graph.get_dependents(&path("/pa/units/tank_ammo.json"))),
+            vec![
+                "layer0/pa/units/bot.json",
+                "layer0/pa/units/tank_weapon.json",
+                "layer0/pa/units/unit_list.json",
+                "layer1/pa/units/tank.json"
+            ]
+        );
+        assert_eq!(
+            get_paths(graph.get_dependents(&path("/pa/units/bot_ammo.json"))),
+            vec!["layer0/pa/units/bot.json"]
+        );
+        assert!(graph.get_dependents(&path("/pa/units/unit_list.json")).is_empty());
+        assert_eq!(graph.get_unresolved()[&path("/pa/units/bot_ammo.json")].len(), 1);
+    }
+
+    #[test]
+    fn removed_sources_drop_their_edges_and_unresolved_references() {
+        let mut graph = create_graph();
+        graph.remove_sources(|source| source.relative_path.value == "/pa/units/bot.json");
+
+        assert_eq!(graph.get_edges().len(), 3);
+        assert!(graph.get_unresolved().is_empty());
+        assert!(graph.get_unresolved_sources().is_empty());
+        assert_eq!(graph.get_referenced_by(&layer(0), &path("/pa/units/tank_weapon.json")).len(), 1);
+    }
+}
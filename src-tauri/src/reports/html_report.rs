@@ -0,0 +1,319 @@
+use std::{collections::HashMap, fs};
+
+use crate::analyzers::analyzer::Category;
+
+use super::report::{escape_xml, get_file_uri, Report, ReportFile, ReportHint, ReportLocation};
+
+/// Lines shown before and after the lines of a hint.
+const SNIPPET_CONTEXT_LINES: usize = 2;
+
+/// Longer lines, e.g. of minified json, are cut around the hint column.
+const MAX_SNIPPET_LINE_LENGTH: usize = 160;
+
+const STYLE: &str = "
+body { font-family: sans-serif; margin: 2em; color: #222; }
+h1 { margin-bottom: 0.2em; }
+.tool { color: #777; margin-bottom: 1em; }
+.filters label { margin-right: 1.5em; cursor: pointer; }
+.repository { margin-top: 2em; }
+.repository h2 small { font-weight: normal; font-size: 0.6em; }
+.file { border: 1px solid #ddd; border-radius: 4px; margin: 0.5em 0; padding: 0.3em 0.8em; }
+.file summary { cursor: pointer; font-family: monospace; font-size: 1.1em; }
+.file summary .counts { font-family: sans-serif; font-size: 0.8em; color: #777; margin-left: 1em; }
+.hint { margin: 0.8em 0 0.8em 1em; }
+.category { font-weight: bold; }
+.error .category { color: #c62828; }
+.warning .category { color: #b26a00; }
+.suggestion .category { color: #1565c0; }
+.rule, .position { color: #777; font-size: 0.9em; }
+pre.snippet { background: #f6f8fa; padding: 0.5em 0; margin: 0.4em 0 0 0; overflow-x: auto; }
+pre.snippet span.line { display: block; padding: 0 0.8em; }
+pre.snippet span.marked { background: #fff3c4; }
+pre.snippet span.line-number { display: inline-block; width: 4em; color: #999; user-select: none; }
+.hint.suppressed, .hint.baselined { opacity: 0.6; }
+.hint.suppressed .category::after { content: \" (suppressed)\"; font-weight: normal; }
+.hint.baselined .category::after { content: \" (baselined)\"; font-weight: normal; }
+.hide-error .hint.error, .hide-warning .hint.warning, .hide-suggestion .hint.suggestion { display: none; }
+.hide-suppressed .hint.suppressed, .hide-baselined .hint.baselined { display: none; }
+.file.empty, .repository.empty { display: none; }
+";
+
+/// Toggles categories and hides files and repositories without visible hints.
+const SCRIPT: &str = "
+function hideEmptyContainers() {
+    for (const container of document.querySelectorAll('.file, .repository')) {
+        const hasVisibleHints = [...container.querySelectorAll('.hint')].some(hint => getComputedStyle(hint).display !== 'none');
+        container.classList.toggle('empty', !hasVisibleHints);
+    }
+}
+for (const checkbox of document.querySelectorAll('.filters input')) {
+    checkbox.addEventListener('change', () => {
+        document.body.classList.toggle('hide-' + checkbox.value, !checkbox.checked);
+        hideEmptyContainers();
+    });
+}
+hideEmptyContainers();
+";
+
+/// Single html page without external assets, grouped by repository and file.
+/// Only files with hints are listed. Snippets are read from the files at the moment of rendering.
+pub fn render_html_report(report: &Report) -> String {
+    let mut html = String::from("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str(&format!("<title>{} report</title>\n", escape_xml(&report.tool.name)));
+    // suppressed and baselined hints are listed only on request and hidden until their filter is checked
+    html.push_str(&format!(
+        "<style>{}</style>\n</head>\n<body class=\"hide-suppressed hide-baselined\">\n",
+        STYLE
+    ));
+
+    html.push_str(&format!("<h1>{} report</h1>\n", escape_xml(&report.tool.name)));
+    html.push_str(&format!(
+        "<div class=\"tool\">version {}, {} files with hints</div>\n",
+        escape_xml(&report.tool.version),
+        report.summary.files_with_hints
+    ));
+    html.push_str("<div class=\"filters\">\n");
+    for (category, name, count) in [
+        ("error", "errors", report.summary.errors),
+        ("warning", "warnings", report.summary.warnings),
+        ("suggestion", "suggestions", report.summary.suggestions),
+    ] {
+        html.push_str(&format!(
+            "<label><input type=\"checkbox\" value=\"{}\" checked> {} {}</label>\n",
+            category, count, name
+        ));
+    }
+    if report.files.iter().any(|file| !file.suppressed_hints.is_empty()) {
+        html.push_str(&format!(
+            "<label><input type=\"checkbox\" value=\"suppressed\"> {} suppressed</label>\n",
+            report.summary.suppressed
+        ));
+    }
+    if report.files.iter().any(|file| !file.baselined_hints.is_empty()) {
+        html.push_str(&format!(
+            "<label><input type=\"checkbox\" value=\"baselined\"> {} baselined</label>\n",
+            report.summary.baselined
+        ));
+    }
+    html.push_str("</div>\n");
+
+    let files_with_hints: Vec<&ReportFile> = report.files.iter().filter(|file| file.has_hints()).collect();
+    let mut repository_folders: Vec<&String> = Vec::new();
+    for file in &files_with_hints {
+        if !repository_folders.contains(&&file.repository_folder) {
+            repository_folders.push(&file.repository_folder);
+        }
+    }
+    for repository_folder in repository_folders {
+        let files: Vec<&ReportFile> = files_with_hints
+            .iter()
+            .filter(|file| file.repository_folder == *repository_folder)
+            .copied()
+            .collect();
+        let repository_name = match files[0].mod_identifier.is_empty() {
+            true => repository_folder.clone(),
+            false => files[0].mod_identifier.clone(),
+        };
+
+        html.push_str("<section class=\"repository\">\n");
+        html.push_str(&format!(
+            "<h2>{} <small><a href=\"{}\">{}</a></small></h2>\n",
+            escape_xml(&repository_name),
+            escape_xml(&get_file_uri(repository_folder)),
+            escape_xml(repository_folder)
+        ));
+        for file in files {
+            render_file(&mut html, file);
+        }
+        html.push_str("</section>\n");
+    }
+
+    html.push_str(&format!("<script>{}</script>\n</body>\n</html>\n", SCRIPT));
+    html
+}
+
+fn render_file(html: &mut String, file: &ReportFile) {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for hint in &file.hints {
+        *counts.entry(get_category_name(hint.category)).or_default() += 1;
+    }
+    if !file.suppressed_hints.is_empty() {
+        counts.insert("suppressed", file.suppressed_hints.len());
+    }
+    if !file.baselined_hints.is_empty() {
+        counts.insert("baselined", file.baselined_hints.len());
+    }
+    let counts = ["error", "warning", "suggestion", "suppressed", "baselined"]
+        .iter()
+        .filter_map(|name| counts.get(name).map(|count| format!("{} {}", count, name)))
+        .collect::<Vec<String>>()
+        .join(", ");
+
+    html.push_str("<details class=\"file\" open>\n");
+    html.push_str(&format!(
+        "<summary><a href=\"{}\">{}</a><span class=\"counts\">{}</span></summary>\n",
+        escape_xml(&get_file_uri(&file.file_path)),
+        escape_xml(&file.relative_path),
+        counts
+    ));
+
+    // unreadable files are reported without snippets
+    let content = fs::read_to_string(&file.file_path).unwrap_or_default();
+    let lines: Vec<&str> = content.lines().collect();
+    for hint in &file.hints {
+        render_hint(html, hint, None, &lines);
+    }
+    for hint in &file.suppressed_hints {
+        render_hint(html, hint, Some("suppressed"), &lines);
+    }
+    for hint in &file.baselined_hints {
+        render_hint(html, hint, Some("baselined"), &lines);
+    }
+    html.push_str("</details>\n");
+}
+
+/// `hidden_state` is the class of hints that are hidden by default, "suppressed" or "baselined".
+fn render_hint(html: &mut String, hint: &ReportHint, hidden_state: Option<&str>, lines: &[&str]) {
+    let category = get_category_name(hint.category);
+    let position = match &hint.location {
+        Some(location) => format!(" <span class=\"position\">{}:{}</span>", location.start_line, location.start_column),
+        None => String::new(),
+    };
+
+    html.push_str(&format!(
+        "<div class=\"hint {}{}\">\n",
+        category,
+        hidden_state.map(|state| format!(" {}", state)).unwrap_or_default()
+    ));
+    html.push_str(&format!(
+        "<div><span class=\"category\">{}</span> {} <span class=\"rule\">[{}]</span>{}</div>\n",
+        category,
+        escape_xml(&hint.message),
+        escape_xml(&hint.rule_id),
+        position
+    ));
+    if let Some(location) = &hint.location {
+        render_snippet(html, location, lines);
+    }
+    html.push_str("</div>\n");
+}
+
+fn render_snippet(html: &mut String, location: &ReportLocation, lines: &[&str]) {
+    if location.start_line == 0 || location.start_line > lines.len() {
+        return;
+    }
+
+    let first_line = location.start_line.saturating_sub(SNIPPET_CONTEXT_LINES).max(1);
+    let last_line = (location.end_line.max(location.start_line) + SNIPPET_CONTEXT_LINES).min(lines.len());
+    html.push_str("<pre class=\"snippet\">");
+    for line_number in first_line..=last_line {
+        let is_marked = line_number >= location.start_line && line_number <= location.end_line.max(location.start_line);
+        let column = match line_number == location.start_line {
+            true => location.start_column,
+            false => 1,
+        };
+        html.push_str(&format!(
+            "<span class=\"line{}\"><span class=\"line-number\">{}</span>{}</span>",
+            if is_marked { " marked" } else { "" },
+            line_number,
+            escape_xml(&clip_line(lines[line_number - 1], column))
+        ));
+    }
+    html.push_str("</pre>\n");
+}
+
+/// Part of `line` around the 1-based `column`, at most `MAX_SNIPPET_LINE_LENGTH` characters.
+fn clip_line(line: &str, column: usize) -> String {
+    let chars: Vec<char> = line.chars().collect();
+    if chars.len() <= MAX_SNIPPET_LINE_LENGTH {
+        return line.to_string();
+    }
+
+    let start = column
+        .saturating_sub(1 + MAX_SNIPPET_LINE_LENGTH / 2)
+        .min(chars.len() - MAX_SNIPPET_LINE_LENGTH);
+    let end = start + MAX_SNIPPET_LINE_LENGTH;
+    let mut clipped: String = chars[start..end].iter().collect();
+    if start > 0 {
+        clipped.insert(0, '…');
+    }
+    if end < chars.len() {
+        clipped.push('…');
+    }
+    clipped
+}
+
+fn get_category_name(category: Category) -> &'static str {
+    match category {
+        Category::Error => "error",
+        Category::Warning => "warning",
+        Category::Suggestion => "suggestion",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        analyzers::analyzer::{FileAnalysisResult, Hint},
+        project::repos::repository::RepositoryInfo,
+        test_folder::TestFolder,
+    };
+
+    use super::*;
+
+    fn create_result(file_path: String, message: &str) -> FileAnalysisResult {
+        FileAnalysisResult {
+            file_path,
+            repository_info: RepositoryInfo {
+                folder_path: String::from("/mods/tank"),
+                mod_identifier: String::from("com.tank&co"),
+                mod_priority: 100,
+            },
+            messages: vec![Hint::Parse {
+                category: Category::Error,
+                rule_id: String::from("json-parse-error"),
+                message: message.to_string(),
+                line: Some(2),
+                column: Some(14),
+            }],
+            suppressed_messages: Vec::new(),
+            baselined_messages: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn messages_and_snippets_are_escaped() {
+        let test_folder = TestFolder::new();
+        let file_path = test_folder.write("/tank.json", "{\n    \"name\": \"<script>alert('tank & bot')</script>\"\n}");
+        let results = vec![create_result(file_path, "Unexpected \"<\" & '>'")];
+        let html = render_html_report(&Report::new(&results, false));
+
+        assert!(!html.contains("<script>alert"));
+        assert!(html.contains("Unexpected &quot;&lt;&quot; &amp; &apos;&gt;&apos;"));
+        assert!(html.contains(
+            "<span class=\"line marked\"><span class=\"line-number\">2</span>    &quot;name&quot;: \
+             &quot;&lt;script&gt;alert(&apos;tank &amp; bot&apos;)&lt;/script&gt;&quot;</span>"
+        ));
+        assert!(html.contains("<h2>com.tank&amp;co "));
+    }
+
+    #[test]
+    fn unreadable_files_have_no_snippets() {
+        let results = vec![create_result(String::from("/mods/tank/missing.json"), "Unexpected end of file")];
+        let html = render_html_report(&Report::new(&results, false));
+
+        assert!(html.contains("Unexpected end of file"));
+        assert!(!html.contains("<pre class=\"snippet\">"));
+    }
+
+    #[test]
+    fn long_lines_are_clipped_around_the_column() {
+        let line = "a".repeat(100) + "<b>" + &"c".repeat(100);
+        let clipped = clip_line(&line, 101);
+
+        assert_eq!(clipped.chars().count(), MAX_SNIPPET_LINE_LENGTH + 2);
+        assert!(clipped.starts_with('…') && clipped.ends_with('…'));
+        assert!(clipped.contains("<b>"));
+        assert_eq!(clip_line("short", 3), "short");
+    }
+}
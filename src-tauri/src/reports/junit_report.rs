@@ -0,0 +1,161 @@
+use crate::analyzers::analyzer::Category;
+
+use super::report::{escape_xml, Report, ReportFile, ReportHint};
+
+/// One test suite per repository and one test case per analyzed file.
+/// Errors and warnings fail the test case, clean files and files with only suggestions pass,
+/// suggestions are listed in `system-out`.
+/// Suppressed and baselined hints never fail a test case, they are listed in `system-out` when the report has them.
+pub fn render_junit_report(report: &Report) -> String {
+    let mut repository_folders: Vec<&String> = Vec::new();
+    for file in &report.files {
+        if !repository_folders.contains(&&file.repository_folder) {
+            repository_folders.push(&file.repository_folder);
+        }
+    }
+
+    let failures = report.files.iter().filter(|file| is_failed(file)).count();
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str(&format!(
+        "<testsuites name=\"{}\" tests=\"{}\" failures=\"{}\">\n",
+        escape_xml(&report.tool.name),
+        report.files.len(),
+        failures
+    ));
+
+    for repository_folder in repository_folders {
+        let files: Vec<&ReportFile> = report
+            .files
+            .iter()
+            .filter(|file| file.repository_folder == *repository_folder)
+            .collect();
+        let suite_name = match files[0].mod_identifier.is_empty() {
+            true => repository_folder.clone(),
+            false => files[0].mod_identifier.clone(),
+        };
+        xml.push_str(&format!(
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\">\n",
+            escape_xml(&suite_name),
+            files.len(),
+            files.iter().filter(|file| is_failed(file)).count()
+        ));
+
+        for file in files {
+            xml.push_str(&format!(
+                "    <testcase name=\"{}\" classname=\"{}\" file=\"{}\">\n",
+                escape_xml(&file.relative_path),
+                escape_xml(&suite_name),
+                escape_xml(&file.file_path)
+            ));
+            let text = file.hints.iter().map(format_hint).collect::<Vec<String>>().join("\n");
+            let suppressed_text = file
+                .suppressed_hints
+                .iter()
+                .map(|hint| format!("suppressed {}", format_hint(hint)))
+                .chain(file.baselined_hints.iter().map(|hint| format!("baselined {}", format_hint(hint))))
+                .collect::<Vec<String>>()
+                .join("\n");
+            if is_failed(file) {
+                let errors = count_hints(file, Category::Error);
+                let warnings = count_hints(file, Category::Warning);
+                xml.push_str(&format!(
+                    "      <failure message=\"{} errors, {} warnings\" type=\"{}\">{}</failure>\n",
+                    errors,
+                    warnings,
+                    if errors > 0 { "error" } else { "warning" },
+                    escape_xml(&text)
+                ));
+                if !suppressed_text.is_empty() {
+                    xml.push_str(&format!("      <system-out>{}</system-out>\n", escape_xml(&suppressed_text)));
+                }
+            } else {
+                let text = [text, suppressed_text]
+                    .into_iter()
+                    .filter(|text| !text.is_empty())
+                    .collect::<Vec<String>>()
+                    .join("\n");
+                if !text.is_empty() {
+                    xml.push_str(&format!("      <system-out>{}</system-out>\n", escape_xml(&text)));
+                }
+            }
+            xml.push_str("    </testcase>\n");
+        }
+        xml.push_str("  </testsuite>\n");
+    }
+
+    xml.push_str("</testsuites>\n");
+    xml
+}
+
+fn is_failed(file: &ReportFile) -> bool {
+    file.hints
+        .iter()
+        .any(|hint| matches!(hint.category, Category::Error | Category::Warning))
+}
+
+fn count_hints(file: &ReportFile, category: Category) -> usize {
+    file.hints.iter().filter(|hint| hint.category == category).count()
+}
+
+/// Same layout as the text output of the runner: `line:column category: message [rule]`.
+fn format_hint(hint: &ReportHint) -> String {
+    let position = match &hint.location {
+        Some(location) => format!("{}:{} ", location.start_line, location.start_column),
+        None => String::new(),
+    };
+    let category = match hint.category {
+        Category::Error => "error",
+        Category::Warning => "warning",
+        Category::Suggestion => "suggestion",
+    };
+    format!("{}{}: {} [{}]", position, category, hint.message, hint.rule_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        analyzers::analyzer::{FileAnalysisResult, Hint},
+        project::repos::repository::RepositoryInfo,
+    };
+
+    use super::*;
+
+    fn create_result(relative_path: &str, messages: Vec<Hint>) -> FileAnalysisResult {
+        FileAnalysisResult {
+            file_path: format!("/mods/tank{}", relative_path),
+            repository_info: RepositoryInfo {
+                folder_path: String::from("/mods/tank"),
+                mod_identifier: String::from("com.tank"),
+                mod_priority: 100,
+            },
+            messages,
+            suppressed_messages: Vec::new(),
+            baselined_messages: Vec::new(),
+        }
+    }
+
+    fn create_hint(category: Category) -> Hint {
+        Hint::Parse {
+            category,
+            rule_id: String::from("json-parse-error"),
+            message: String::from("Unexpected end of file"),
+            line: Some(2),
+            column: Some(1),
+        }
+    }
+
+    #[test]
+    fn every_analyzed_file_is_a_test_case() {
+        let results = vec![
+            create_result("/pa/clean.json", Vec::new()),
+            create_result("/pa/broken.json", vec![create_hint(Category::Error)]),
+            create_result("/pa/suggested.json", vec![create_hint(Category::Suggestion)]),
+        ];
+        let xml = render_junit_report(&Report::new(&results, false));
+
+        assert!(xml.contains("<testsuites name=\"pa-linter\" tests=\"3\" failures=\"1\">"));
+        assert!(xml.contains("<testcase name=\"/pa/clean.json\" classname=\"com.tank\" file=\"/mods/tank/pa/clean.json\">\n    </testcase>"));
+        assert!(xml.contains("<failure message=\"1 errors, 0 warnings\" type=\"error\">2:1 error: Unexpected end of file [json-parse-error]</failure>"));
+        assert!(xml.contains("<system-out>2:1 suggestion: Unexpected end of file [json-parse-error]</system-out>"));
+    }
+}
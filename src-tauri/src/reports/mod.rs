@@ -0,0 +1,4 @@
+pub mod html_report;
+pub mod junit_report;
+pub mod report;
+pub mod sarif_report;
\ No newline at end of file
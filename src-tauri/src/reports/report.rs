@@ -0,0 +1,285 @@
+use std::{fs, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    analyzers::analyzer::{Category, FileAnalysisResult, Hint},
+    project::repos::repository_tree::RelativePath,
+};
+
+use super::{html_report::render_html_report, junit_report::render_junit_report, sarif_report::render_sarif_report};
+
+/// Must be increased whenever a field of the json report is renamed or removed.
+pub const REPORT_SCHEMA_VERSION: u32 = 1;
+
+pub const REPORT_TOOL_NAME: &str = "pa-linter";
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportFormat {
+    Json,
+    /// SARIF 2.1.0, read by code scanning viewers.
+    Sarif,
+    /// JUnit XML, one test case per analyzed file.
+    Junit,
+    /// Single static html page for people without the editor.
+    Html,
+}
+
+impl ReportFormat {
+    pub fn from_name(name: &str) -> Result<ReportFormat, String> {
+        match name {
+            "json" => Ok(ReportFormat::Json),
+            "sarif" => Ok(ReportFormat::Sarif),
+            "junit" => Ok(ReportFormat::Junit),
+            "html" => Ok(ReportFormat::Html),
+            _ => Err(format!("Unknown report format: {}, expected json, sarif, junit or html", name)),
+        }
+    }
+}
+
+/// Analysis results in a form that does not change with the editor internals.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Report {
+    pub schema_version: u32,
+    pub tool: ReportTool,
+    pub summary: ReportSummary,
+    pub files: Vec<ReportFile>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ReportTool {
+    pub name: String,
+    pub version: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ReportSummary {
+    pub errors: usize,
+    pub warnings: usize,
+    pub suggestions: usize,
+    /// Suppressed hints of any category, counted even when they are not listed.
+    #[serde(default)]
+    pub suppressed: usize,
+    /// Hints of any category known from the baselines, counted even when they are not listed.
+    #[serde(default)]
+    pub baselined: usize,
+    /// Files in the report, clean files included.
+    pub files: usize,
+    /// Files with listed hints.
+    #[serde(default)]
+    pub files_with_hints: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ReportFile {
+    pub file_path: String,
+    /// Path inside the repository, e.g. `/pa/units/land/tank/tank.json`.
+    pub relative_path: String,
+    pub repository_folder: String,
+    pub mod_identifier: String,
+    pub hints: Vec<ReportHint>,
+    /// Listed only when the report is created with suppressed hints.
+    #[serde(default)]
+    pub suppressed_hints: Vec<ReportHint>,
+    /// Listed only when the report is created with suppressed hints.
+    #[serde(default)]
+    pub baselined_hints: Vec<ReportHint>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ReportHint {
+    pub rule_id: String,
+    pub category: Category,
+    pub message: String,
+    /// `None` for hints about the whole file.
+    pub location: Option<ReportLocation>,
+    /// JSON Pointer of the value, only for hints about a json value.
+    pub pointer: Option<String>,
+    pub value: Option<String>,
+}
+
+/// Lines and columns are 1-based, the end is right after the value.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct ReportLocation {
+    pub start_line: usize,
+    pub start_column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+}
+
+impl Report {
+    /// Report of every analyzed file, ordered by file path. Clean files have no hints,
+    /// so formats that count passed files can list them.
+    /// With `show_suppressed` suppressed and baselined hints are listed as well.
+    pub fn new(results: &[FileAnalysisResult], show_suppressed: bool) -> Report {
+        let mut files: Vec<ReportFile> = results
+            .iter()
+            .map(|result| ReportFile {
+                file_path: result.file_path.clone(),
+                relative_path: result
+                    .file_path
+                    .strip_prefix(&result.repository_info.folder_path)
+                    .map(|relative_path| RelativePath::new(relative_path.to_string()).value)
+                    .unwrap_or(result.file_path.clone()),
+                repository_folder: result.repository_info.folder_path.clone(),
+                mod_identifier: result.repository_info.mod_identifier.clone(),
+                hints: result.messages.iter().map(ReportHint::from).collect(),
+                suppressed_hints: match show_suppressed {
+                    true => result.suppressed_messages.iter().map(ReportHint::from).collect(),
+                    false => Vec::new(),
+                },
+                baselined_hints: match show_suppressed {
+                    true => result.baselined_messages.iter().map(ReportHint::from).collect(),
+                    false => Vec::new(),
+                },
+            })
+            .collect();
+        files.sort_by(|a, b| a.file_path.cmp(&b.file_path));
+
+        let mut summary = ReportSummary {
+            suppressed: results.iter().map(|result| result.suppressed_messages.len()).sum(),
+            baselined: results.iter().map(|result| result.baselined_messages.len()).sum(),
+            files: files.len(),
+            files_with_hints: files.iter().filter(|file| file.has_hints()).count(),
+            ..ReportSummary::default()
+        };
+        for hint in files.iter().flat_map(|file| file.hints.iter()) {
+            match hint.category {
+                Category::Error => summary.errors += 1,
+                Category::Warning => summary.warnings += 1,
+                Category::Suggestion => summary.suggestions += 1,
+            }
+        }
+
+        Report {
+            schema_version: REPORT_SCHEMA_VERSION,
+            tool: ReportTool {
+                name: REPORT_TOOL_NAME.to_string(),
+                version: env!("CARGO_PKG_VERSION").to_string(),
+            },
+            summary,
+            files,
+        }
+    }
+}
+
+impl ReportFile {
+    /// Whether the file has hints or listed suppressed or baselined hints.
+    pub fn has_hints(&self) -> bool {
+        !self.hints.is_empty() || !self.suppressed_hints.is_empty() || !self.baselined_hints.is_empty()
+    }
+}
+
+impl From<&Hint> for ReportHint {
+    fn from(hint: &Hint) -> ReportHint {
+        let mut report_hint = ReportHint {
+            rule_id: hint.rule_id().to_string(),
+            category: hint.category(),
+            message: hint.message().to_string(),
+            location: None,
+            pointer: None,
+            value: None,
+        };
+
+        match hint {
+            Hint::JSON {
+                property_value,
+                pointer,
+                span,
+                ..
+            } => {
+                report_hint.location = span.map(|span| ReportLocation {
+                    start_line: span.start.line,
+                    start_column: span.start.column,
+                    end_line: span.end.line,
+                    end_column: span.end.column,
+                });
+                // hints about the whole document have no value
+                if !pointer.is_empty() || !property_value.is_empty() {
+                    report_hint.pointer = Some(pointer.clone());
+                    report_hint.value = Some(property_value.clone());
+                }
+            }
+            Hint::Orphan { relative_path, .. } | Hint::Conflict { relative_path, .. } => {
+                report_hint.value = Some(relative_path.clone());
+            }
+            Hint::Parse {
+                line: Some(line),
+                column: Some(column),
+                ..
+            } => {
+                report_hint.location = Some(ReportLocation {
+                    start_line: *line,
+                    start_column: *column,
+                    end_line: *line,
+                    end_column: *column,
+                });
+            }
+            _ => {}
+        }
+
+        report_hint
+    }
+}
+
+pub fn render_report(report: &Report, format: ReportFormat) -> Result<String, String> {
+    match format {
+        ReportFormat::Json => match serde_json::to_string_pretty(report) {
+            Ok(content) => Ok(content),
+            Err(error) => Err(format!("Could not serialize report: {}", error)),
+        },
+        ReportFormat::Sarif => render_sarif_report(report),
+        ReportFormat::Junit => Ok(render_junit_report(report)),
+        ReportFormat::Html => Ok(render_html_report(report)),
+    }
+}
+
+pub fn write_report(report: &Report, format: ReportFormat, file_path: &Path) -> Result<(), String> {
+    let content = render_report(report, format)?;
+    if fs::write(file_path, content).is_err() {
+        return Err(format!("Could not write report to {}", file_path.display()));
+    }
+    Ok(())
+}
+
+/// `file:///` uri of an absolute path.
+pub fn get_file_uri(file_path: &str) -> String {
+    let file_path = file_path.replace('\\', "/");
+    match file_path.starts_with('/') {
+        true => format!("file://{}", encode_uri_path(&file_path)),
+        false => format!("file:///{}", encode_uri_path(&file_path)),
+    }
+}
+
+/// Percent-encodes everything except unreserved characters, '/' and the ':' of drive letters.
+pub fn encode_uri_path(path: &str) -> String {
+    let mut encoded = String::new();
+    for byte in path.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' | b':' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+/// Escapes text for xml and html content and attribute values.
+pub fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // characters XML 1.0 does not allow at all
+            c if (c as u32) < 0x20 && !matches!(c, '\t' | '\n' | '\r') => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
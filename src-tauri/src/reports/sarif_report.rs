@@ -0,0 +1,141 @@
+use serde_json::{json, Map, Value};
+
+use crate::analyzers::{analyzer::Category, registry::AnalyzerRegistry};
+
+use super::report::{encode_uri_path, get_file_uri, Report};
+
+pub const SARIF_VERSION: &str = "2.1.0";
+
+pub const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// Where a hint of the report was put by the lint settings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum HintState {
+    Reported,
+    Suppressed,
+    Baselined,
+}
+
+/// Single run with every rule of the editor. File locations are relative to a base id
+/// per repository, `originalUriBaseIds` maps the ids to repository folders.
+pub fn render_sarif_report(report: &Report) -> Result<String, String> {
+    let registry = AnalyzerRegistry::with_default_analyzers();
+    let rules = registry.get_rules();
+
+    let mut repository_folders: Vec<&String> = Vec::new();
+    for file in &report.files {
+        if !repository_folders.contains(&&file.repository_folder) {
+            repository_folders.push(&file.repository_folder);
+        }
+    }
+    let mut uri_base_ids: Map<String, Value> = Map::new();
+    for (index, repository_folder) in repository_folders.iter().enumerate() {
+        uri_base_ids.insert(
+            get_uri_base_id(index),
+            json!({ "uri": get_folder_uri(repository_folder) }),
+        );
+    }
+
+    // states are given to every result or to none of them
+    let has_baseline_states = report.files.iter().any(|file| !file.baselined_hints.is_empty());
+    let mut results: Vec<Value> = Vec::new();
+    for file in &report.files {
+        let uri_base_index = repository_folders
+            .iter()
+            .position(|repository_folder| **repository_folder == file.repository_folder)
+            .unwrap();
+        let hints = file.hints.iter().map(|hint| (hint, HintState::Reported));
+        let suppressed_hints = file.suppressed_hints.iter().map(|hint| (hint, HintState::Suppressed));
+        let baselined_hints = file.baselined_hints.iter().map(|hint| (hint, HintState::Baselined));
+        for (hint, state) in hints.chain(suppressed_hints).chain(baselined_hints) {
+            let mut physical_location = json!({
+                "artifactLocation": {
+                    "uri": encode_uri_path(file.relative_path.trim_start_matches('/')),
+                    "uriBaseId": get_uri_base_id(uri_base_index),
+                }
+            });
+            if let Some(location) = &hint.location {
+                physical_location["region"] = json!({
+                    "startLine": location.start_line,
+                    "startColumn": location.start_column,
+                    "endLine": location.end_line,
+                    "endColumn": location.end_column,
+                });
+            }
+
+            let mut result = json!({
+                "ruleId": hint.rule_id,
+                "level": get_level(hint.category),
+                "message": { "text": hint.message },
+                "locations": [{ "physicalLocation": physical_location }],
+            });
+            if let Some(rule_index) = rules.iter().position(|rule| rule.id == hint.rule_id) {
+                result["ruleIndex"] = json!(rule_index);
+            }
+            if let Some(pointer) = &hint.pointer {
+                result["locations"][0]["logicalLocations"] = json!([{
+                    "fullyQualifiedName": pointer,
+                    "kind": "member",
+                }]);
+            }
+            if let Some(value) = &hint.value {
+                result["properties"] = json!({ "value": value });
+            }
+            // suppressed by the ignore file or the sidecar key of the file
+            if state == HintState::Suppressed {
+                result["suppressions"] = json!([{ "kind": "external" }]);
+            }
+            if has_baseline_states {
+                result["baselineState"] = match state {
+                    HintState::Baselined => json!("unchanged"),
+                    _ => json!("new"),
+                };
+            }
+            results.push(result);
+        }
+    }
+
+    let sarif = json!({
+        "$schema": SARIF_SCHEMA,
+        "version": SARIF_VERSION,
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": report.tool.name,
+                    "version": report.tool.version,
+                    "rules": rules.iter().map(|rule| json!({
+                        "id": rule.id,
+                        "shortDescription": { "text": rule.description },
+                        "defaultConfiguration": { "level": get_level(rule.default_category) },
+                    })).collect::<Vec<Value>>(),
+                }
+            },
+            "originalUriBaseIds": uri_base_ids,
+            // columns of json source maps are counted in characters
+            "columnKind": "unicodeCodePoints",
+            "results": results,
+        }]
+    });
+
+    match serde_json::to_string_pretty(&sarif) {
+        Ok(content) => Ok(content),
+        Err(error) => Err(format!("Could not serialize SARIF report: {}", error)),
+    }
+}
+
+fn get_level(category: Category) -> &'static str {
+    match category {
+        Category::Error => "error",
+        Category::Warning => "warning",
+        Category::Suggestion => "note",
+    }
+}
+
+fn get_uri_base_id(index: usize) -> String {
+    format!("MOD{}", index)
+}
+
+/// `file:///` uri of a folder, with the trailing slash SARIF requires for base ids.
+fn get_folder_uri(folder_path: &str) -> String {
+    get_file_uri(folder_path.trim_end_matches(['/', '\\'])) + "/"
+}
@@ -0,0 +1 @@
+pub mod spec_resolver;
\ No newline at end of file
@@ -0,0 +1,272 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::{
+    analyzers::{json_reader::read_json_file, json_source_map::join_pointer},
+    project::repos::repository_tree::RelativePath,
+    vfs::virtual_file_system::{Layer, VirtualFileSystem},
+};
+
+/// Property that links a spec to the spec it inherits from.
+pub const BASE_SPEC_PROPERTY: &str = "base_spec";
+
+/// Single file of an inheritance chain.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SpecLink {
+    pub relative_path: String,
+    /// Layer the file was loaded from.
+    pub layer: Option<Layer>,
+    pub file_path: Option<String>,
+}
+
+/// Value of a property of the merged spec and the file it came from.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ResolvedProperty {
+    pub pointer: String,
+    pub value: Value,
+    pub source: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "lowercase", tag = "error_type")]
+pub enum SpecResolveError {
+    /// `base_spec` of `referenced_from` points at a file no layer provides.
+    Missing {
+        relative_path: String,
+        referenced_from: Option<String>,
+    },
+    /// File exists, but could not be read or parsed.
+    Unreadable {
+        relative_path: String,
+        message: String,
+    },
+    /// Chain returns to a file it already contains. The last path repeats an earlier one.
+    Cycle { chain: Vec<String> },
+}
+
+/// Spec with its whole `base_spec` chain applied.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ResolvedSpec {
+    pub relative_path: String,
+    /// Files of the chain from the requested spec to its farthest base.
+    pub chain: Vec<SpecLink>,
+    /// Spec as the game sees it after inheritance.
+    pub merged: Value,
+    /// Every leaf property of `merged`. Arrays are leaves, because they are not merged.
+    pub properties: Vec<ResolvedProperty>,
+    pub errors: Vec<SpecResolveError>,
+}
+
+/// Follows `base_spec` links through the virtual file system.
+/// Parsed files are kept, so resolving many specs with shared bases reads each file once.
+pub struct SpecResolver<'a> {
+    virtual_file_system: &'a VirtualFileSystem,
+    files: HashMap<RelativePath, Result<Value, String>>,
+}
+
+impl<'a> SpecResolver<'a> {
+    pub fn new(virtual_file_system: &'a VirtualFileSystem) -> SpecResolver<'a> {
+        SpecResolver {
+            virtual_file_system,
+            files: HashMap::new(),
+        }
+    }
+
+    /// Chains reaching a file of an image without a folder end at that file,
+    /// its properties are not part of `merged`.
+    pub fn resolve(&mut self, relative_path: &RelativePath) -> ResolvedSpec {
+        let mut chain: Vec<SpecLink> = Vec::new();
+        let mut chain_values: Vec<(String, Value)> = Vec::new();
+        let mut errors: Vec<SpecResolveError> = Vec::new();
+
+        let mut next_path = Some(relative_path.clone());
+        let mut referenced_from: Option<String> = None;
+        while let Some(current_path) = next_path.take() {
+            if chain.iter().any(|link| link.relative_path == current_path.value) {
+                let mut cycle: Vec<String> = chain.iter().map(|link| link.relative_path.clone()).collect();
+                cycle.push(current_path.value.clone());
+                errors.push(SpecResolveError::Cycle { chain: cycle });
+                break;
+            }
+
+            let layer = self.virtual_file_system.get_provider(&current_path);
+            if layer.is_none() {
+                errors.push(SpecResolveError::Missing {
+                    relative_path: current_path.value.clone(),
+                    referenced_from: referenced_from.clone(),
+                });
+                break;
+            }
+
+            let file_path = self
+                .virtual_file_system
+                .get_provider_file_path(&current_path)
+                .and_then(|file_path| file_path.to_str().map(|file_path| file_path.to_string()));
+            let has_file = file_path.is_some();
+            chain.push(SpecLink {
+                relative_path: current_path.value.clone(),
+                layer: layer.cloned(),
+                file_path,
+            });
+
+            // images without a folder only list their paths, the file exists but its content
+            // is unknown, so the chain ends here without an error
+            if !has_file {
+                break;
+            }
+
+            let value = match self.read_spec(&current_path) {
+                Ok(value) => value,
+                Err(message) => {
+                    errors.push(SpecResolveError::Unreadable {
+                        relative_path: current_path.value.clone(),
+                        message,
+                    });
+                    break;
+                }
+            };
+
+            next_path = get_base_spec(&value).map(RelativePath::new);
+            referenced_from = Some(current_path.value.clone());
+            chain_values.push((current_path.value, value));
+        }
+
+        let merged = merge_chain(&chain_values);
+        let properties = collect_properties(&merged, &chain_values);
+
+        ResolvedSpec {
+            relative_path: relative_path.value.clone(),
+            chain,
+            merged,
+            properties,
+            errors,
+        }
+    }
+
+    fn read_spec(&mut self, relative_path: &RelativePath) -> Result<Value, String> {
+        if let Some(value) = self.files.get(relative_path) {
+            return value.clone();
+        }
+
+        let value = match self.virtual_file_system.get_provider_file_path(relative_path) {
+            Some(file_path) => read_json_file(&file_path.to_string_lossy())
+                .map(|json_file| json_file.value)
+                .map_err(|error| error.get_message().to_string()),
+            None => Err(String::from("Layer of the file has no folder on disk")),
+        };
+        self.files.insert(relative_path.clone(), value.clone());
+        value
+    }
+}
+
+pub fn get_base_spec(value: &Value) -> Option<String> {
+    value
+        .get(BASE_SPEC_PROPERTY)
+        .and_then(|base_spec| base_spec.as_str())
+        .map(|base_spec| base_spec.to_string())
+}
+
+/// Applies specs from the farthest base to the requested one.
+/// Objects are merged property by property, any other value replaces the inherited one.
+fn merge_chain(chain_values: &[(String, Value)]) -> Value {
+    let mut merged = Value::Object(Map::new());
+    for (_, value) in chain_values.iter().rev() {
+        merge_value(&mut merged, value);
+    }
+
+    if let Value::Object(merged_object) = &mut merged {
+        merged_object.remove(BASE_SPEC_PROPERTY);
+    }
+    merged
+}
+
+fn merge_value(target: &mut Value, source: &Value) {
+    match (target, source) {
+        (Value::Object(target_object), Value::Object(source_object)) => {
+            for (key, source_value) in source_object {
+                match target_object.get_mut(key) {
+                    Some(target_value) => merge_value(target_value, source_value),
+                    None => {
+                        target_object.insert(key.clone(), source_value.clone());
+                    }
+                }
+            }
+        }
+        (target, source) => *target = source.clone(),
+    }
+}
+
+fn collect_properties(merged: &Value, chain_values: &[(String, Value)]) -> Vec<ResolvedProperty> {
+    let mut properties: Vec<ResolvedProperty> = Vec::new();
+    collect_properties_recursive(merged, "", chain_values, &mut properties);
+    properties
+}
+
+fn collect_properties_recursive(
+    value: &Value,
+    pointer: &str,
+    chain_values: &[(String, Value)],
+    properties: &mut Vec<ResolvedProperty>,
+) {
+    if let Value::Object(object) = value {
+        if !object.is_empty() || pointer.is_empty() {
+            for (key, child_value) in object {
+                collect_properties_recursive(child_value, &join_pointer(pointer, key), chain_values, properties);
+            }
+            return;
+        }
+    }
+
+    // the nearest spec of the chain that has the value is the one it came from
+    let source = chain_values
+        .iter()
+        .find(|(_, chain_value)| chain_value.pointer(pointer).is_some())
+        .map(|(relative_path, _)| relative_path.clone())
+        .unwrap_or_default();
+
+    properties.push(ResolvedProperty {
+        pointer: pointer.to_string(),
+        value: value.clone(),
+        source,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::directory_image::DirectoryImage;
+
+    use super::*;
+
+    #[test]
+    fn chain_ends_quietly_at_image_without_folder() {
+        let image = DirectoryImage::new(
+            String::from("pa"),
+            vec![String::from("/pa/units/land/tank/tank.json")],
+            None,
+        );
+        let virtual_file_system = VirtualFileSystem::build(&[image], &[]);
+        let mut spec_resolver = SpecResolver::new(&virtual_file_system);
+
+        let resolved_spec = spec_resolver.resolve(&RelativePath::new(String::from("/pa/units/land/tank/tank.json")));
+        assert!(resolved_spec.errors.is_empty());
+        assert_eq!(resolved_spec.chain.len(), 1);
+        assert_eq!(resolved_spec.chain[0].file_path, None);
+    }
+
+    #[test]
+    fn missing_spec_is_reported() {
+        let virtual_file_system = VirtualFileSystem::default();
+        let mut spec_resolver = SpecResolver::new(&virtual_file_system);
+
+        let resolved_spec = spec_resolver.resolve(&RelativePath::new(String::from("/pa/missing.json")));
+        assert_eq!(
+            resolved_spec.errors,
+            vec![SpecResolveError::Missing {
+                relative_path: String::from("/pa/missing.json"),
+                referenced_from: None,
+            }]
+        );
+    }
+}
@@ -0,0 +1,80 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crate::{
+    editor::{editor::EditorEnvironment, editor_runtime::EditorRuntimeData},
+    project::repos::{repository::Repository, repository_tree::build_repository_tree},
+};
+
+static FOLDER_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Folder in the system temp folder for tests that read or write files.
+/// Tests run in parallel, so every folder has its own name. Removed on drop.
+pub struct TestFolder {
+    path: PathBuf,
+}
+
+impl Default for TestFolder {
+    fn default() -> TestFolder {
+        TestFolder::new()
+    }
+}
+
+impl TestFolder {
+    pub fn new() -> TestFolder {
+        let name = format!(
+            "pa-linter-test-{}-{}",
+            std::process::id(),
+            FOLDER_COUNTER.fetch_add(1, Ordering::Relaxed)
+        );
+        let path = std::env::temp_dir().join(name);
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        TestFolder { path }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Path as repository trees store it, without trailing separator.
+    pub fn path_string(&self) -> String {
+        self.path.to_str().unwrap().to_string()
+    }
+
+    /// Writes `content` to `relative_path`, creating its folders, and returns the file path.
+    pub fn write(&self, relative_path: &str, content: impl AsRef<[u8]>) -> String {
+        let file_path = self.path.join(relative_path.trim_start_matches('/'));
+        fs::create_dir_all(file_path.parent().unwrap()).unwrap();
+        fs::write(&file_path, content).unwrap();
+        file_path.to_str().unwrap().to_string()
+    }
+
+    /// Runtime data with trees of the mods in `mod_folders`, subfolders of the test folder.
+    /// The workspace folder of the editor is inside the test folder as well.
+    pub fn create_runtime_data(&self, mod_folders: &[&str]) -> EditorRuntimeData {
+        let executable_file_path = self.path.join("editor").to_str().unwrap().to_string();
+        let editor_env = EditorEnvironment::new(executable_file_path, self.path_string());
+        fs::create_dir_all(editor_env.get_workspace_folder().unwrap()).unwrap();
+        let mut editor_runtime_data = EditorRuntimeData::new(editor_env);
+        for mod_folder in mod_folders {
+            let folder_path = self.path.join(mod_folder.trim_start_matches('/'));
+            let repository = Repository::new(folder_path.to_str().unwrap().to_string());
+            editor_runtime_data.repository_trees.push(build_repository_tree(&repository));
+        }
+        editor_runtime_data
+    }
+
+    pub fn read(&self, relative_path: &str) -> String {
+        fs::read_to_string(self.path.join(relative_path.trim_start_matches('/'))).unwrap()
+    }
+}
+
+impl Drop for TestFolder {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
@@ -1,5 +1,17 @@
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 
-use crate::editor::editor_runtime::EditorRuntimeData;
+use crate::{
+    analyzers::analysis_session::AnalysisSession, editor::editor_runtime::EditorRuntimeData, fixes::rename::RenameUndo,
+    watch::repository_watcher::RepositoryWatcher,
+};
 
-pub struct EditorRuntimeState(pub RwLock<EditorRuntimeData>);
\ No newline at end of file
+pub struct EditorRuntimeState(pub RwLock<EditorRuntimeData>);
+
+/// Results and references of the last analysis, `None` until repositories are analyzed.
+pub struct AnalysisSessionState(pub RwLock<Option<AnalysisSession>>);
+
+/// Watcher of repository folders, `None` if the platform watcher could not be created.
+pub struct RepositoryWatcherState(pub Mutex<Option<RepositoryWatcher>>);
+
+/// Applied renames, the last one is undone first.
+pub struct RefactorHistoryState(pub Mutex<Vec<RenameUndo>>);
\ No newline at end of file
@@ -0,0 +1,2 @@
+pub mod path_suggestions;
+pub mod virtual_file_system;
\ No newline at end of file
@@ -0,0 +1,255 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::project::repos::repository_tree::RelativePath;
+
+use super::virtual_file_system::VirtualFileSystem;
+
+/// Number of candidates offered for a single unresolved path.
+pub const MAX_PATH_SUGGESTIONS: usize = 3;
+
+/// Why a path was offered instead of an unresolved one.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum PathSuggestionReason {
+    /// Paths differ only in letter case.
+    CaseMismatch,
+    /// File with the same name lives in another folder.
+    SameFileName,
+    /// Path is a few edits away.
+    EditDistance,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PathCandidate {
+    pub relative_path: RelativePath,
+    pub reason: PathSuggestionReason,
+    /// Edit distance between the unresolved path and the candidate.
+    pub distance: usize,
+}
+
+/// Lookup tables over every path of the virtual file system for finding
+/// existing files close to a path that does not resolve.
+#[derive(Debug, Clone, Default)]
+pub struct PathSuggestionIndex {
+    /// Lowercase path to the paths with that spelling.
+    by_lowercase_path: HashMap<String, Vec<RelativePath>>,
+    /// Lowercase file name to the paths with that name.
+    by_file_name: HashMap<String, Vec<RelativePath>>,
+    /// Lowercase folder to the paths of the files directly inside it.
+    by_folder: HashMap<String, Vec<RelativePath>>,
+}
+
+impl PathSuggestionIndex {
+    pub fn build(virtual_file_system: &VirtualFileSystem) -> PathSuggestionIndex {
+        let mut index = PathSuggestionIndex::default();
+
+        let mut relative_paths: Vec<&RelativePath> = virtual_file_system.get_paths().collect();
+        relative_paths.sort_by(|a, b| a.value.cmp(&b.value));
+        for relative_path in relative_paths {
+            let lowercase_path = relative_path.value.to_lowercase();
+            index
+                .by_file_name
+                .entry(get_file_name(&lowercase_path).to_string())
+                .or_default()
+                .push(relative_path.clone());
+            index
+                .by_folder
+                .entry(get_folder(&lowercase_path).to_string())
+                .or_default()
+                .push(relative_path.clone());
+            index
+                .by_lowercase_path
+                .entry(lowercase_path)
+                .or_default()
+                .push(relative_path.clone());
+        }
+
+        index
+    }
+
+    /// Existing paths that equal `relative_path` when case is ignored.
+    pub fn find_case_insensitive(&self, relative_path: &RelativePath) -> Vec<&RelativePath> {
+        match self.by_lowercase_path.get(&relative_path.value.to_lowercase()) {
+            Some(paths) => paths.iter().filter(|path| *path != relative_path).collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// The closest existing paths, best first: case-only differences,
+    /// files with the same name in other folders, then files of the same folder
+    /// and extension a few edits away. Only these groups are compared, so the
+    /// cost does not grow with the number of files in the project.
+    pub fn find_candidates(&self, relative_path: &RelativePath, limit: usize) -> Vec<PathCandidate> {
+        let lowercase_path = relative_path.value.to_lowercase();
+        let mut candidates: Vec<PathCandidate> = Vec::new();
+
+        for path in self.find_case_insensitive(relative_path) {
+            candidates.push(PathCandidate {
+                relative_path: path.clone(),
+                reason: PathSuggestionReason::CaseMismatch,
+                distance: 0,
+            });
+        }
+
+        if let Some(paths) = self.by_file_name.get(get_file_name(&lowercase_path)) {
+            for path in paths {
+                if path.value.to_lowercase() == lowercase_path {
+                    continue;
+                }
+                candidates.push(PathCandidate {
+                    relative_path: path.clone(),
+                    reason: PathSuggestionReason::SameFileName,
+                    distance: get_edit_distance(&lowercase_path, &path.value.to_lowercase(), usize::MAX),
+                });
+            }
+        }
+
+        let max_distance = get_max_edit_distance(&lowercase_path);
+        if let Some(paths) = self.by_folder.get(get_folder(&lowercase_path)) {
+            for path in paths {
+                let candidate_path = path.value.to_lowercase();
+                if get_file_name(&candidate_path) == get_file_name(&lowercase_path)
+                    || get_extension(&candidate_path) != get_extension(&lowercase_path)
+                {
+                    continue;
+                }
+                let distance = get_edit_distance(&lowercase_path, &candidate_path, max_distance);
+                if distance <= max_distance {
+                    candidates.push(PathCandidate {
+                        relative_path: path.clone(),
+                        reason: PathSuggestionReason::EditDistance,
+                        distance,
+                    });
+                }
+            }
+        }
+
+        candidates.sort_by(|a, b| {
+            (a.reason, a.distance, &a.relative_path.value).cmp(&(b.reason, b.distance, &b.relative_path.value))
+        });
+        candidates.truncate(limit);
+        candidates
+    }
+}
+
+/// Typos grow with the path length, but far away paths are not suggestions anymore.
+fn get_max_edit_distance(path: &str) -> usize {
+    (path.chars().count() / 10).clamp(2, 5)
+}
+
+fn get_file_name(path: &str) -> &str {
+    path.rsplit('/').next().unwrap_or_default()
+}
+
+fn get_folder(path: &str) -> &str {
+    match path.rsplit_once('/') {
+        Some((folder, _)) => folder,
+        None => "",
+    }
+}
+
+fn get_extension(path: &str) -> &str {
+    match get_file_name(path).rsplit_once('.') {
+        Some((_, extension)) => extension,
+        None => "",
+    }
+}
+
+/// Levenshtein distance of `a` and `b`. Stops early and returns `max_distance + 1`
+/// once the distance is known to be bigger than `max_distance`.
+fn get_edit_distance(a: &str, b: &str, max_distance: usize) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    if a.len().abs_diff(b.len()) > max_distance {
+        return max_distance.saturating_add(1);
+    }
+
+    let mut previous_row: Vec<usize> = (0..=b.len()).collect();
+    let mut current_row: Vec<usize> = vec![0; b.len() + 1];
+    for i in 1..=a.len() {
+        current_row[0] = i;
+        let mut row_minimum = current_row[0];
+        for j in 1..=b.len() {
+            let substitution_cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+            current_row[j] = (previous_row[j] + 1)
+                .min(current_row[j - 1] + 1)
+                .min(previous_row[j - 1] + substitution_cost);
+            row_minimum = row_minimum.min(current_row[j]);
+        }
+        if row_minimum > max_distance {
+            return max_distance.saturating_add(1);
+        }
+        std::mem::swap(&mut previous_row, &mut current_row);
+    }
+
+    previous_row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::directory_image::DirectoryImage;
+
+    use super::*;
+
+    fn create_index(files: &[&str]) -> PathSuggestionIndex {
+        let files = files.iter().map(|file| file.to_string()).collect();
+        let directory_image = DirectoryImage::new("pa".to_string(), files, Some("/games/pa".to_string()));
+        PathSuggestionIndex::build(&VirtualFileSystem::build(&[directory_image], &[]))
+    }
+
+    fn find(index: &PathSuggestionIndex, path: &str, limit: usize) -> Vec<(String, PathSuggestionReason, usize)> {
+        index
+            .find_candidates(&RelativePath::new(path.to_string()), limit)
+            .into_iter()
+            .map(|candidate| (candidate.relative_path.value, candidate.reason, candidate.distance))
+            .collect()
+    }
+
+    #[test]
+    fn edit_distance_counts_insertions_deletions_and_substitutions() {
+        assert_eq!(get_edit_distance("tank", "tank", 5), 0);
+        assert_eq!(get_edit_distance("tank", "tanks", 5), 1);
+        assert_eq!(get_edit_distance("tank", "tak", 5), 1);
+        assert_eq!(get_edit_distance("tank", "tonk", 5), 1);
+        assert_eq!(get_edit_distance("kitten", "sitting", 5), 3);
+        assert_eq!(get_edit_distance("", "bot", 5), 3);
+    }
+
+    #[test]
+    fn edit_distance_stops_above_the_maximum() {
+        assert_eq!(get_edit_distance("kitten", "sitting", 2), 3);
+        assert_eq!(get_edit_distance("a", "abcdef", 2), 3);
+        assert_eq!(get_edit_distance("tank", "tonk", usize::MAX), 1);
+    }
+
+    #[test]
+    fn candidates_are_ranked_by_reason_then_distance() {
+        let index = create_index(&[
+            "pa/units/Tank.json",
+            "pa/units/tanks.json",
+            "pa/units/tan.json",
+            "pa/ammo/tank.json",
+            "pa/units/tank.papa",
+        ]);
+
+        assert_eq!(
+            find(&index, "/pa/units/tank.json", MAX_PATH_SUGGESTIONS),
+            vec![
+                ("/pa/units/Tank.json".to_string(), PathSuggestionReason::CaseMismatch, 0),
+                ("/pa/ammo/tank.json".to_string(), PathSuggestionReason::SameFileName, 5),
+                ("/pa/units/tan.json".to_string(), PathSuggestionReason::EditDistance, 1),
+            ]
+        );
+        assert_eq!(find(&index, "/pa/units/tank.json", 5).len(), 4);
+    }
+
+    #[test]
+    fn far_paths_and_other_folders_are_not_candidates() {
+        // "/pa/unit/tonk.json" is two edits away, but typos in both folder and file name are not looked for
+        let index = create_index(&["pa/units/commander.json", "pa/unit/tonk.json", "pa/units/tonk.papa"]);
+
+        assert!(find(&index, "/pa/units/tank.json", MAX_PATH_SUGGESTIONS).is_empty());
+    }
+}
@@ -0,0 +1,310 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    analyzers::lint_config::is_lint_file,
+    directory_image::DirectoryImage,
+    project::repos::{
+        repository::RepositoryInfo,
+        repository_tree::{get_entry_relative_path, RelativePath, RepositoryTree},
+    },
+};
+
+/// Where files of a layer come from.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "lowercase", tag = "layer_type")]
+pub enum LayerSource {
+    /// Snapshot of the base game folder.
+    Image {
+        name: String,
+        folder_path: Option<String>,
+    },
+    /// Registered mod folder.
+    Repository { repository_info: RepositoryInfo },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Layer {
+    /// Mount position. Layers with bigger index shadow layers with smaller index.
+    pub index: usize,
+    pub name: String,
+    pub source: LayerSource,
+}
+
+/// Layers providing a single path, ordered from the top one to the bottom one.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PathLayers {
+    pub relative_path: RelativePath,
+    /// Layer whose file the game loads.
+    pub provider: Option<Layer>,
+    /// Layers whose files are hidden by the provider.
+    pub shadowed: Vec<Layer>,
+}
+
+/// Merged view of directory images and repositories in the order the game mounts them:
+/// base game images in the order they are given, then mods by ascending priority.
+/// A path provided by several layers resolves to the last mounted one,
+/// the rest of them are kept as alternatives.
+#[derive(Debug, Clone, Default)]
+pub struct VirtualFileSystem {
+    layers: Vec<Layer>,
+    /// Indices of layers providing the path, in mount order.
+    files: HashMap<RelativePath, Vec<usize>>,
+}
+
+impl VirtualFileSystem {
+    /// `directory_images` must be in the game mount order, e.g. the base game before its expansion,
+    /// see `get_directory_images`.
+    pub fn build(directory_images: &[DirectoryImage], repository_trees: &[RepositoryTree]) -> VirtualFileSystem {
+        let mut virtual_file_system = VirtualFileSystem::default();
+
+        for image in directory_images {
+            let layer_index = virtual_file_system.add_layer(
+                image.name.clone(),
+                LayerSource::Image {
+                    name: image.name.clone(),
+                    folder_path: image.folder_path.clone(),
+                },
+            );
+            for file in &image.files {
+                virtual_file_system.add_file(RelativePath::new(file.clone()), layer_index);
+            }
+        }
+
+        for repository_tree in get_trees_in_mount_order(repository_trees) {
+            let repository_info = &repository_tree.repository_info;
+            let layer_index = virtual_file_system.add_layer(
+                repository_info.mod_identifier.clone(),
+                LayerSource::Repository {
+                    repository_info: repository_info.clone(),
+                },
+            );
+            for entry in &repository_tree.entries {
+                if entry.is_directory {
+                    continue;
+                }
+                // files of the linter are not game files, so they never shadow each other
+                let relative_path = get_entry_relative_path(repository_tree, entry);
+                if is_lint_file(&relative_path) {
+                    continue;
+                }
+                virtual_file_system.add_file(relative_path, layer_index);
+            }
+        }
+
+        virtual_file_system
+    }
+
+    fn add_layer(&mut self, name: String, source: LayerSource) -> usize {
+        let index = self.layers.len();
+        self.layers.push(Layer {
+            index,
+            name,
+            source,
+        });
+        index
+    }
+
+    fn add_file(&mut self, relative_path: RelativePath, layer_index: usize) {
+        let providers = self.files.entry(relative_path).or_default();
+        if !providers.contains(&layer_index) {
+            providers.push(layer_index);
+        }
+    }
+
+    pub fn get_layers(&self) -> &Vec<Layer> {
+        &self.layers
+    }
+
+    /// Layer mounted for the repository with `repository_info`.
+    pub fn get_repository_layer(&self, repository_info: &RepositoryInfo) -> Option<&Layer> {
+        self.layers.iter().find(|layer| match &layer.source {
+            LayerSource::Repository {
+                repository_info: layer_repository_info,
+            } => layer_repository_info.folder_path == repository_info.folder_path,
+            LayerSource::Image { .. } => false,
+        })
+    }
+
+    pub fn get_paths(&self) -> impl Iterator<Item = &RelativePath> {
+        self.files.keys()
+    }
+
+    pub fn contains_file(&self, relative_path: &RelativePath) -> bool {
+        self.files.contains_key(relative_path)
+    }
+
+    /// All layers containing `relative_path` in mount order.
+    pub fn get_layers_providing(&self, relative_path: &RelativePath) -> Vec<&Layer> {
+        match self.files.get(relative_path) {
+            Some(providers) => providers.iter().map(|index| &self.layers[*index]).collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Layer whose file the game loads for `relative_path`.
+    pub fn get_provider(&self, relative_path: &RelativePath) -> Option<&Layer> {
+        let providers = self.files.get(relative_path)?;
+        providers.last().map(|index| &self.layers[*index])
+    }
+
+    /// Layers that also contain `relative_path` but are hidden by the provider,
+    /// from the top one to the bottom one.
+    pub fn get_shadowed_layers(&self, relative_path: &RelativePath) -> Vec<&Layer> {
+        match self.files.get(relative_path) {
+            Some(providers) => providers
+                .iter()
+                .rev()
+                .skip(1)
+                .map(|index| &self.layers[*index])
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Path on disk of the file the game loads for `relative_path`.
+    /// `None` if no layer provides it or the layer has no folder on disk.
+    pub fn get_provider_file_path(&self, relative_path: &RelativePath) -> Option<PathBuf> {
+        get_layer_file_path(self.get_provider(relative_path)?, relative_path)
+    }
+
+    pub fn get_path_layers(&self, relative_path: &RelativePath) -> PathLayers {
+        PathLayers {
+            relative_path: relative_path.clone(),
+            provider: self.get_provider(relative_path).cloned(),
+            shadowed: self
+                .get_shadowed_layers(relative_path)
+                .into_iter()
+                .cloned()
+                .collect(),
+        }
+    }
+}
+
+pub fn get_layer_file_path(layer: &Layer, relative_path: &RelativePath) -> Option<PathBuf> {
+    let folder_path = match &layer.source {
+        LayerSource::Image { folder_path, .. } => folder_path.as_ref()?,
+        LayerSource::Repository { repository_info } => &repository_info.folder_path,
+    };
+    Some(PathBuf::from(folder_path).join(relative_path.value.trim_start_matches('/')))
+}
+
+/// Repository trees sorted the way the game mounts mods: by ascending priority.
+/// Mods with equal priority keep the order they were registered in.
+pub fn get_trees_in_mount_order(repository_trees: &[RepositoryTree]) -> Vec<&RepositoryTree> {
+    let mut repository_trees: Vec<&RepositoryTree> = repository_trees.iter().collect();
+    repository_trees.sort_by_key(|repository_tree| repository_tree.repository_info.mod_priority);
+    repository_trees
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        project::repos::{repository::Repository, repository_tree::build_repository_tree},
+        test_folder::TestFolder,
+    };
+
+    use super::*;
+
+    fn create_image(name: &str, files: &[&str]) -> DirectoryImage {
+        let files = files.iter().map(|file| file.to_string()).collect();
+        DirectoryImage::new(name.to_string(), files, Some(format!("/games/{}", name)))
+    }
+
+    /// Mod in a subfolder of `test_folder` with the files given relative to the mod folder.
+    fn create_tree(test_folder: &TestFolder, name: &str, priority: Option<i64>, files: &[&str]) -> RepositoryTree {
+        let modinfo = match priority {
+            Some(priority) => format!("{{\"identifier\": \"{}\", \"priority\": {}}}", name, priority),
+            None => format!("{{\"identifier\": \"{}\"}}", name),
+        };
+        test_folder.write(&format!("/{}/modinfo.json", name), modinfo);
+        for file in files {
+            test_folder.write(&format!("/{}{}", name, file), "{}");
+        }
+        let folder_path = test_folder.path().join(name).to_str().unwrap().to_string();
+        build_repository_tree(&Repository::new(folder_path))
+    }
+
+    fn get_names(layers: Vec<&Layer>) -> Vec<&str> {
+        layers.iter().map(|layer| layer.name.as_str()).collect()
+    }
+
+    fn path(value: &str) -> RelativePath {
+        RelativePath::new(value.to_string())
+    }
+
+    #[test]
+    fn images_are_mounted_in_the_given_order() {
+        let directory_images = vec![
+            create_image("pa_ex1", &["pa/units/tank.json", "pa/units/titan.json"]),
+            create_image("pa", &["pa/units/tank.json", "pa/units/bot.json"]),
+        ];
+        let virtual_file_system = VirtualFileSystem::build(&directory_images, &[]);
+
+        assert_eq!(virtual_file_system.get_provider(&path("/pa/units/tank.json")).unwrap().name, "pa");
+        assert_eq!(get_names(virtual_file_system.get_shadowed_layers(&path("/pa/units/tank.json"))), vec!["pa_ex1"]);
+        assert_eq!(virtual_file_system.get_provider(&path("/pa/units/titan.json")).unwrap().name, "pa_ex1");
+        assert_eq!(
+            virtual_file_system.get_provider_file_path(&path("/pa/units/bot.json")),
+            Some(PathBuf::from("/games/pa/pa/units/bot.json"))
+        );
+        assert!(virtual_file_system.get_provider(&path("/pa/units/missing.json")).is_none());
+    }
+
+    #[test]
+    fn mods_shadow_images_and_mods_with_lower_priority() {
+        let test_folder = TestFolder::new();
+        let repository_trees = vec![
+            create_tree(&test_folder, "high", Some(200), &["/pa/units/tank.json"]),
+            create_tree(&test_folder, "low", Some(50), &["/pa/units/tank.json", "/pa/units/bot.json"]),
+        ];
+        let directory_images = vec![create_image("pa", &["pa/units/tank.json", "pa/units/bot.json"])];
+        let virtual_file_system = VirtualFileSystem::build(&directory_images, &repository_trees);
+
+        let tank_path = path("/pa/units/tank.json");
+        assert_eq!(virtual_file_system.get_provider(&tank_path).unwrap().name, "high");
+        assert_eq!(get_names(virtual_file_system.get_shadowed_layers(&tank_path)), vec!["low", "pa"]);
+        assert_eq!(get_names(virtual_file_system.get_layers_providing(&tank_path)), vec!["pa", "low", "high"]);
+        let path_layers = virtual_file_system.get_path_layers(&path("/pa/units/bot.json"));
+        assert_eq!(path_layers.provider.unwrap().name, "low");
+        assert_eq!(path_layers.shadowed.len(), 1);
+        assert_eq!(
+            virtual_file_system.get_provider_file_path(&tank_path),
+            Some(test_folder.path().join("high/pa/units/tank.json"))
+        );
+    }
+
+    #[test]
+    fn mods_with_equal_priority_keep_registration_order() {
+        let test_folder = TestFolder::new();
+        let repository_trees = vec![
+            create_tree(&test_folder, "first", None, &["/pa/units/tank.json"]),
+            create_tree(&test_folder, "second", Some(100), &["/pa/units/tank.json"]),
+        ];
+        let virtual_file_system = VirtualFileSystem::build(&[], &repository_trees);
+
+        let tank_path = path("/pa/units/tank.json");
+        assert_eq!(virtual_file_system.get_provider(&tank_path).unwrap().name, "second");
+        assert_eq!(get_names(virtual_file_system.get_shadowed_layers(&tank_path)), vec!["first"]);
+        let layer = virtual_file_system.get_repository_layer(&repository_trees[0].repository_info);
+        assert_eq!(layer.unwrap().name, "first");
+    }
+
+    #[test]
+    fn linter_files_are_not_mounted() {
+        let test_folder = TestFolder::new();
+        let repository_trees = vec![create_tree(
+            &test_folder,
+            "tank",
+            None,
+            &["/pa-linter.json", "/pa-linter-baseline.json", "/pa/units/tank.json"],
+        )];
+        let virtual_file_system = VirtualFileSystem::build(&[], &repository_trees);
+
+        assert!(!virtual_file_system.contains_file(&path("/pa-linter.json")));
+        assert!(!virtual_file_system.contains_file(&path("/pa-linter-baseline.json")));
+        assert!(virtual_file_system.contains_file(&path("/modinfo.json")));
+        assert!(virtual_file_system.contains_file(&path("/pa/units/tank.json")));
+    }
+}
@@ -0,0 +1 @@
+pub mod repository_watcher;
\ No newline at end of file
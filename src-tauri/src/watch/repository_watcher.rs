@@ -0,0 +1,150 @@
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    sync::mpsc::{self, RecvTimeoutError},
+    time::Duration,
+};
+
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+
+use crate::project::repos::repository_tree::RepositoryTree;
+
+/// Editors save files in several steps, changes closer than this are handled together.
+const DEBOUNCE_INTERVAL: Duration = Duration::from_millis(300);
+
+/// Watches repository folders and reports changed paths in batches.
+/// Uses inotify on Linux, FSEvents on macOS and ReadDirectoryChangesW on Windows.
+pub struct RepositoryWatcher {
+    watcher: RecommendedWatcher,
+    watched_folders: Vec<PathBuf>,
+}
+
+impl RepositoryWatcher {
+    /// `on_change` is called from a background thread with every path that was
+    /// created, modified or removed since the previous call.
+    pub fn new<F>(on_change: F) -> Result<RepositoryWatcher, String>
+    where
+        F: Fn(Vec<PathBuf>) + Send + 'static,
+    {
+        let (sender, receiver) = mpsc::channel::<Event>();
+        let watcher = notify::recommended_watcher(move |event: notify::Result<Event>| {
+            if let Ok(event) = event {
+                let _ = sender.send(event);
+            }
+        });
+        if let Err(error) = watcher {
+            return Err(format!("Could not create file watcher: {}", error));
+        }
+
+        // the thread ends when the watcher and its sender are dropped
+        std::thread::spawn(move || {
+            while let Ok(event) = receiver.recv() {
+                let mut changed_paths: Vec<PathBuf> = Vec::new();
+                push_event_paths(&mut changed_paths, event);
+                loop {
+                    match receiver.recv_timeout(DEBOUNCE_INTERVAL) {
+                        Ok(event) => push_event_paths(&mut changed_paths, event),
+                        Err(RecvTimeoutError::Timeout) => break,
+                        Err(RecvTimeoutError::Disconnected) => return,
+                    }
+                }
+                if !changed_paths.is_empty() {
+                    on_change(changed_paths);
+                }
+            }
+        });
+
+        Ok(RepositoryWatcher {
+            watcher: watcher.unwrap(),
+            watched_folders: Vec::new(),
+        })
+    }
+
+    /// Watches exactly `folder_paths`: new folders are added, folders missing from the list are released.
+    pub fn watch_folders(&mut self, folder_paths: &[String]) -> Result<(), String> {
+        let folder_paths: Vec<PathBuf> = folder_paths.iter().map(PathBuf::from).collect();
+
+        let removed_folders: Vec<PathBuf> = self
+            .watched_folders
+            .iter()
+            .filter(|folder_path| !folder_paths.contains(folder_path))
+            .cloned()
+            .collect();
+        for folder_path in removed_folders {
+            let _ = self.watcher.unwatch(&folder_path);
+            self.watched_folders.retain(|watched_folder| *watched_folder != folder_path);
+        }
+
+        for folder_path in folder_paths {
+            if self.watched_folders.contains(&folder_path) {
+                continue;
+            }
+            if let Err(error) = self.watcher.watch(&folder_path, RecursiveMode::Recursive) {
+                return Err(format!("Could not watch {}: {}", folder_path.display(), error));
+            }
+            self.watched_folders.push(folder_path);
+        }
+        Ok(())
+    }
+}
+
+fn push_event_paths(changed_paths: &mut Vec<PathBuf>, event: Event) {
+    // access events don't change anything
+    if !matches!(
+        event.kind,
+        EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_) | EventKind::Any
+    ) {
+        return;
+    }
+    for path in event.paths {
+        if !changed_paths.contains(&path) {
+            changed_paths.push(path);
+        }
+    }
+}
+
+/// Brings trees in line with the disk for `changed_paths`.
+/// Returns paths of changed files, including files of created and removed folders.
+pub fn update_repository_trees(repository_trees: &mut [RepositoryTree], changed_paths: &[PathBuf]) -> Vec<String> {
+    let mut changed_files: HashSet<String> = HashSet::new();
+
+    for changed_path in changed_paths {
+        let repository_tree = repository_trees.iter_mut().find(|repository_tree| {
+            changed_path.starts_with(Path::new(&repository_tree.repository_info.folder_path))
+        });
+        let repository_tree = match repository_tree {
+            Some(repository_tree) => repository_tree,
+            None => continue,
+        };
+
+        // files of a created or removed folder are changed files as well
+        if changed_path.exists() {
+            if !repository_tree.add_path(changed_path) {
+                repository_tree.update_path_metadata(changed_path);
+            }
+            changed_files.extend(get_files_inside(repository_tree, changed_path));
+        } else {
+            changed_files.extend(get_files_inside(repository_tree, changed_path));
+            changed_files.insert(changed_path.to_string_lossy().to_string());
+            repository_tree.remove_path(changed_path);
+        }
+        // keeps the snapshot from listing the folder again on the next start
+        if let Some(parent_path) = changed_path.parent() {
+            repository_tree.update_path_metadata(parent_path);
+        }
+    }
+
+    let mut changed_files: Vec<String> = changed_files.into_iter().collect();
+    changed_files.sort();
+    changed_files
+}
+
+/// Paths of file entries at `path` or inside it.
+fn get_files_inside(repository_tree: &RepositoryTree, path: &Path) -> Vec<String> {
+    repository_tree
+        .entries
+        .iter()
+        .filter(|entry| !entry.is_directory && Path::new(&entry.path).starts_with(path))
+        .map(|entry| entry.path.clone())
+        .collect()
+}